@@ -73,6 +73,16 @@ macro_rules! new_full_start {
             io.extend_with(pallet_root_of_trust_rpc::RootOfTrustApi::to_delegate(
                 pallet_root_of_trust_rpc::RootOfTrust::new(builder.client().clone()),
             ));
+            io.extend_with(pallet_root_of_trust_rpc::RevocationListApi::to_delegate(
+                pallet_root_of_trust_rpc::RevocationLists::<_, _, _, pki_runtime::AccountId>::new(
+                    builder.client().clone(),
+                ),
+            ));
+            io.extend_with(pallet_root_of_trust_rpc::CertificateProofApi::to_delegate(
+                pallet_root_of_trust_rpc::CertificateProofs::<_, _, _, pki_runtime::AccountId>::new(
+                    builder.client().clone(),
+                ),
+            ));
 
             Ok(io)
         })?;
@@ -3,11 +3,16 @@
 const { Keyring } = require('@polkadot/api');
 const { u8aToHex } = require('@polkadot/util');
 const { randomAsU8a } = require('@polkadot/util-crypto');
-const moment = require('moment');
 
 const { Certificate, Runtime } = require('pki');
 const { FirmwareClient } = require('client');
 
+// Revocations are permanent, `CertificateHold` is refused by the chain in favour of suspensions
+const REVOCATION_REASONS = ['Unspecified', 'KeyCompromise', 'CACompromise', 'Superseded', 'CessationOfOperation'];
+
+// Certificates are valid for about 30 days by default, with 6 seconds blocks
+const CERTIFICATE_VALIDITY = 30 * 24 * 600;
+
 require('yargs')
 	.usage('Usage: $0 [--seed <seed>] <command> [options]')
 	.command(
@@ -51,27 +56,29 @@ require('yargs')
 			describe: 'the address of the device to certify',
 			type: 'string'
 		}).positional('expiry', {
-			describe: 'specify in how much time the certificate expires',
-			type: 'string',
-			default: '1 month'
+			describe: 'in how many blocks the certificate expires',
+			type: 'number',
+			default: CERTIFICATE_VALIDITY
 		}),
-		(argv) => {
-			const splitted = argv.expiry.split(" ");
-			const amount = parseInt(splitted[0]);
-			const unit = splitted[1];
+		async (argv) => {
+			const runtime = new Runtime(argv.wsRpc);
+			await runtime.connect();
+			const created = await runtime.currentBlock();
 
 			const keyring = new Keyring({ type: 'ed25519' });
 			const pair = keyring.addFromUri(argv.seed);
 
-			const certificate = new Certificate({ device: argv.deviceAddress, pair: pair, expiry: moment().add(amount, unit) });
+			const certificate = new Certificate({ device: argv.deviceAddress, pair: pair, created: created, expiry: created + argv.expiry });
 			const encoded = certificate.signAndEncode();
 
 			console.log(`Device ......... : ${certificate.deviceAddress}`);
 			console.log(`Signer ......... : ${certificate.signerAddress}`);
-			console.log(`Creation date .. : ${certificate.creationDate.format()}`);
-			console.log(`Expiry date .... : ${certificate.expirationDate.format()}`);
+			console.log(`Creation block . : ${certificate.creationBlock}`);
+			console.log(`Expiry block ... : ${certificate.expirationBlock}`);
 			console.log('------------------')
 			console.log(encoded);
+
+			process.exit(0);
 		},
 	)
 	.command(
@@ -99,35 +106,41 @@ require('yargs')
 		},
 	)
 	.command(
-		'book <signingAddress>',
+		'book <signingSeed> <validity>',
 		'Book a slot and link it to a given signing key',
-		(b) => b.positional('signingAddress', {
-			describe: 'the to-be-registered on-chain public signing key',
+		(b) => b.positional('signingSeed', {
+			describe: 'seed of the to-be-registered signing key, used to prove that we hold it',
 			type: 'string'
+		}).positional('validity', {
+			describe: 'for how many blocks the slot should be valid',
+			type: 'number'
 		}),
 		async (argv) => {
 			const runtime = new Runtime(argv.wsRpc);
 			await runtime.connect();
 			runtime.setSigner(argv.seed);
 
-			console.log(`Submitted transaction ${await runtime.bookSlot(argv.signingAddress)}`);
+			console.log(`Submitted transaction ${await runtime.bookSlot(argv.signingSeed, argv.validity)}`);
 
 			process.exit(0);
 		},
 	)
 	.command(
-		'renew <signingAddress>',
+		'renew <signingAddress> <validity>',
 		'Renew a given slot',
 		(b) => b.positional('signingAddress', {
 			describe: 'the registered on-chain public signing key',
 			type: 'string'
+		}).positional('validity', {
+			describe: 'for how many blocks the slot should be valid',
+			type: 'number'
 		}),
 		async (argv) => {
 			const runtime = new Runtime(argv.wsRpc);
 			await runtime.connect();
 			runtime.setSigner(argv.seed);
 
-			console.log(`Submitted transaction ${await runtime.renewSlot(argv.signingAddress)}`);
+			console.log(`Submitted transaction ${await runtime.renewSlot(argv.signingAddress, argv.validity)}`);
 
 			process.exit(0);
 		},
@@ -138,13 +151,17 @@ require('yargs')
 		(b) => b.positional('signingAddress', {
 			describe: 'the registered on-chain public signing key',
 			type: 'string'
+		}).option('reason', {
+			describe: 'why the slot is revoked',
+			choices: REVOCATION_REASONS,
+			default: 'Unspecified'
 		}),
 		async (argv) => {
 			const runtime = new Runtime(argv.wsRpc);
 			await runtime.connect();
 			runtime.setSigner(argv.seed);
 
-			console.log(`Submitted transaction ${await runtime.revokeSlot(argv.signingAddress)}`);
+			console.log(`Submitted transaction ${await runtime.revokeSlot(argv.signingAddress, argv.reason)}`);
 
 			process.exit(0);
 		},
@@ -158,13 +175,17 @@ require('yargs')
 		}).positional('deviceAddress', {
 			describe: 'the public key of the device',
 			type: 'string'
+		}).option('reason', {
+			describe: 'why the certificate is revoked',
+			choices: REVOCATION_REASONS,
+			default: 'Unspecified'
 		}),
 		async (argv) => {
 			const runtime = new Runtime(argv.wsRpc);
 			await runtime.connect();
 			runtime.setSigner(argv.seed);
 
-			console.log(`Submitted transaction ${await runtime.revokeChild(argv.signingAddress, argv.deviceAddress)}`);
+			console.log(`Submitted transaction ${await runtime.revokeChild(argv.signingAddress, argv.deviceAddress, argv.reason)}`);
 
 			process.exit(0);
 		},
@@ -176,20 +197,20 @@ require('yargs')
 			describe: 'url to reach out to talk to the device',
 			type: 'string'
 		}).positional('expiry', {
-			describe: 'specify in how much time the certificate expires',
-			type: 'string',
-			default: '1 month'
+			describe: 'in how many blocks the certificate expires',
+			type: 'number',
+			default: CERTIFICATE_VALIDITY
 		}),
 		async (argv) => {
-			const splitted = argv.expiry.split(" ");
-			const amount = parseInt(splitted[0]);
-			const unit = splitted[1];
+			const runtime = new Runtime(argv.wsRpc);
+			await runtime.connect();
+			const created = await runtime.currentBlock();
 
 			const keyring = new Keyring({ type: 'ed25519' });
 			const pair = keyring.addFromUri(argv.seed);
 
 			const client = new FirmwareClient(argv.url);
-			await client.burn(pair, moment().add(amount, unit));
+			await client.burn(pair, created, created + argv.expiry);
 
 			process.exit(0);
 		},
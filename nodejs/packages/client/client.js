@@ -27,12 +27,13 @@ class FirmwareClient {
     }
 
     // This call can be used to 'burn' a certificate on a device running our 'firmware.
-    // It will connect to it, get its public key and issue a certificate.
-    async burn(pair, expiry) {
+    // It will connect to it, get its public key and issue a certificate valid from
+    // the block `created` up to the block `expiry`.
+    async burn(pair, created, expiry) {
         const details = await this.fetchDetails();
         const deviceAddress = details.address;
 
-        const certificate = new Certificate({ device: deviceAddress, pair: pair, expiry: expiry });
+        const certificate = new Certificate({ device: deviceAddress, pair: pair, created: created, expiry: expiry });
         const encoded = certificate.signAndEncode();
 
         await axios.post(urljoin(this.url, PATH_BURN), {
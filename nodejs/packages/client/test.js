@@ -4,7 +4,6 @@ const handlers = require('firmware/handlers');
 const { FirmwareClient } = require('./');
 const Keystore = require('firmware/keystore');
 const { Keyring } = require('@polkadot/api');
-const moment = require('moment');
 
 const createKeystore = () => {
     const randomKeystoreName = Math.random().toString(36).substring(2, 15) + Math.random().toString(36).substring(2, 15);
@@ -83,6 +82,7 @@ const createSigningPair = () => {
 
 const buildMockRuntime = (ret) => {
     return {
+        currentBlock: async () => 1000,
         rootAndChildValid: async (unusedA, unusedB) => {
             return ret;
         }
@@ -112,7 +112,7 @@ describe('Client to Firmware', () => {
 
     context('Burning new certificates', () => {
         it('send a valid certificate to the correct endpoint', async () => {
-            await client.burn(pair, moment().add(1, 'month'));
+            await client.burn(pair, 100, 432100);
 
             const details = await client.fetchDetails();
 
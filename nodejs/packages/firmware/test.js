@@ -4,7 +4,6 @@ const { Certificate } = require('pki');
 const { Keyring } = require('@polkadot/api');
 const { randomAsU8a } = require('@polkadot/util-crypto');
 const { u8aToHex } = require('@polkadot/util');
-const moment = require('moment');
 const chai = require('chai');
 const { expect } = require('chai');
 const chaiHttp = require('chai-http');
@@ -20,7 +19,8 @@ const mockCert = (signer, device) => {
     const paramsCert = {
         device: device,
         pair: pair,
-        expiry: moment().add(1, 'month')
+        created: 100,
+        expiry: 432100
     };
     const cert = new Certificate(paramsCert);
     return cert.signAndEncode();
@@ -151,7 +151,8 @@ describe('Firmware', () => {
                 const paramsCert = {
                     device: keystore.account.address,
                     pair: pair,
-                    expiry: moment().subtract(1, 'month') // This certificate is already expired
+                    created: 100,
+                    expiry: 50 // This certificate expires before its creation
                 };
                 const expiredCert = new Certificate(paramsCert);
                 const expiredEncodedCert = expiredCert.signAndEncode();
@@ -1,8 +1,22 @@
 const errors = require('./errors');
 const { Keyring } = require('@polkadot/api');
-const { u8aToHex, u8aToU8a } = require('@polkadot/util');
+const { u8aConcat, u8aToHex, u8aToU8a } = require('@polkadot/util');
 const blake = require('blakejs')
-const moment = require('moment');
+
+// Index of the `V0_1` variant of the `Version` enum of the `pki-certificate` crate
+const VERSION_0_1 = 0;
+
+// Index of the `Ed25519` variant of the `CertificateKey` enum, the keys of the
+// certificates issued by this package are ed25519 keys
+const KEY_ED25519 = 0;
+
+// SCALE encoding of a block number, an unsigned 32 bits integer, little endian
+const encodeBlockNumber = (value) => {
+	const encoded = new Uint8Array(4);
+	new DataView(encoded.buffer).setUint32(0, value, true);
+
+	return encoded;
+}
 
 class Certificate {
 	version = '0.1'
@@ -11,24 +25,21 @@ class Certificate {
 		this.deviceAddress = description.device;
 		this.signerKeypair = description.pair;
 		this.signerAddress = description.pair.address;
-		this.creationDate = moment();
-		this.expirationDate = description.expiry;
+		this.creationBlock = description.created;
+		this.expirationBlock = description.expiry;
 	}
 
 	sign() {
 		const rawMessage = {
 			deviceAddress: this.deviceAddress,
 			signerAddress: this.signerAddress,
-			creationDate: this.creationDate.unix(),
-			expirationDate: this.expirationDate.unix()
+			creationBlock: this.creationBlock,
+			expirationBlock: this.expirationBlock
 		};
 
-		const u8aMessage = u8aToU8a(rawMessage.deviceAddress)
-			+ this.signerKeypair.publicKey
-			+ new Uint8Array([rawMessage.creationDate])
-			+ new Uint8Array([rawMessage.expirationDate]);
-		const u8aHash = blake.blake2b(u8aMessage);
-		const u8aSignature = this.signerKeypair.sign(u8aHash);
+		const u8aMessage = Certificate.message(rawMessage);
+		const u8aHash = blake.blake2b(u8aMessage, null, 32);
+		const u8aSignature = this.signerKeypair.sign(u8aMessage);
 
 		return {
 			version: this.version,
@@ -38,6 +49,23 @@ class Certificate {
 		};
 	}
 
+	// Bytes covered by the signature, the SCALE encoding of the runtime's
+	// `Payload<CertificateKey, BlockNumber>`: version, device and signer keys,
+	// creation and expiration blocks
+	static message(payload) {
+		const keyring = new Keyring({ type: 'ed25519' });
+
+		return u8aConcat(
+			new Uint8Array([VERSION_0_1]),
+			new Uint8Array([KEY_ED25519]),
+			keyring.decodeAddress(payload.deviceAddress),
+			new Uint8Array([KEY_ED25519]),
+			keyring.decodeAddress(payload.signerAddress),
+			encodeBlockNumber(payload.creationBlock),
+			encodeBlockNumber(payload.expirationBlock)
+		);
+	}
+
 	signAndEncode() {
 		const signed = this.sign();
 
@@ -50,7 +78,9 @@ class Certificate {
 		return JSON.parse(json);
 	}
 
-	static verifyCertificateWithoutIssuerChecks(encodedCertificate, onCertificateInvalid) {
+	// `currentBlock` may be omitted when the chain can not be reached, only
+	// certificates expiring before their creation are then reported as expired
+	static verifyCertificateWithoutIssuerChecks(encodedCertificate, onCertificateInvalid, currentBlock) {
 		const decoded = this.decodeCertificate(encodedCertificate);
 
 		if (decoded.version !== '0.1') {
@@ -58,7 +88,9 @@ class Certificate {
 			return false;
 		}
 
-		const expired = moment.unix(decoded.payload.expirationDate).isBefore();
+		const expiration = decoded.payload.expirationBlock;
+		const expired = expiration <= decoded.payload.creationBlock ||
+			(currentBlock !== undefined && expiration <= currentBlock);
 		if (expired) {
 			onCertificateInvalid(encodedCertificate, errors.errExpired);
 			return false
@@ -67,11 +99,8 @@ class Certificate {
 		const keyring = new Keyring({ type: 'ed25519' });
 		const signerPair = keyring.addFromAddress(decoded.payload.signerAddress);
 
-		const u8aMessage = u8aToU8a(decoded.payload.deviceAddress)
-			+ signerPair.publicKey
-			+ new Uint8Array([decoded.payload.creationDate])
-			+ new Uint8Array([decoded.payload.expirationDate]);
-		const u8aHash = blake.blake2b(u8aMessage);
+		const u8aMessage = this.message(decoded.payload);
+		const u8aHash = blake.blake2b(u8aMessage, null, 32);
 
 		const hashMatch = decoded.hash == u8aToHex(u8aHash);
 		if (!hashMatch) {
@@ -79,7 +108,7 @@ class Certificate {
 			return false
 		}
 
-		const signatureOk = signerPair.verify(u8aHash, u8aToU8a(decoded.signature));
+		const signatureOk = signerPair.verify(u8aMessage, u8aToU8a(decoded.signature));
 		if (!signatureOk) {
 			onCertificateInvalid(encodedCertificate, errors.errBadSignature)
 			return false
@@ -89,7 +118,8 @@ class Certificate {
 	}
 
 	static async verify(encodedCertificate, runtime, onCertificateInvalid) {
-		if (!this.verifyCertificateWithoutIssuerChecks(encodedCertificate, onCertificateInvalid)) {
+		const currentBlock = await runtime.currentBlock();
+		if (!this.verifyCertificateWithoutIssuerChecks(encodedCertificate, onCertificateInvalid, currentBlock)) {
 			return false; // Callback already called
 		}
 
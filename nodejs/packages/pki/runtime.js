@@ -1,5 +1,6 @@
 const { ApiPromise, WsProvider } = require('@polkadot/api');
 const { Keyring } = require('@polkadot/api');
+const { u8aConcat } = require('@polkadot/util');
 
 class Runtime {
 	constructor(wsRpcUrl) {
@@ -16,7 +17,23 @@ class Runtime {
 		this.api = await ApiPromise.create({
 			provider: this.provider,
 			types: {
-				CertificateId: "AccountId",
+				CertificateId: "CertificateKey",
+				CertificateKey: {
+					_enum: {
+						Ed25519: "[u8; 32]",
+						Sr25519: "[u8; 32]",
+						Ecdsa: "[u8; 33]",
+						P256: "[u8; 33]"
+					}
+				},
+				CertificateSignature: {
+					_enum: {
+						Ed25519: "H512",
+						Sr25519: "H512",
+						Ecdsa: "[u8; 65]",
+						P256: "H512"
+					}
+				},
 				Address: "AccountId",
 				RootCertificate: {
 					owner: "AccountId",
@@ -24,8 +41,64 @@ class Runtime {
 					created: "BlockNumber",
 					renewed: "BlockNumber",
 					revoked: "bool",
-					validity: "BlockNumber",
-					child_revocations: "Vec<CertificateId>"
+					validity: "BlockNumber"
+				},
+				RevocationReason: {
+					_enum: ["Unspecified", "KeyCompromise", "CACompromise", "Superseded", "CessationOfOperation", "CertificateHold"]
+				},
+				Revocation: {
+					revoked_at: "BlockNumber",
+					reason: "RevocationReason"
+				},
+				RevocationList: {
+					root: "CertificateId",
+					blockHash: "Hash",
+					blockNumber: "BlockNumber",
+					stateRoot: "Hash",
+					rootStatus: "CertificateStatus",
+					rootRevocation: "Option<Revocation>",
+					issuers: "Vec<CertificateId>",
+					revocations: "Vec<(CertificateId, CertificateId, Revocation)>",
+					suspensions: "Vec<(CertificateId, CertificateId, BlockNumber)>",
+					revokedBefore: "Vec<(CertificateId, BlockNumber)>",
+					proof: "Vec<Bytes>"
+				},
+				CertificateProof: {
+					root: "CertificateId",
+					child: "CertificateId",
+					blockHash: "Hash",
+					blockNumber: "BlockNumber",
+					stateRoot: "Hash",
+					status: "VerificationResult",
+					entries: "Vec<(Bytes, Option<Bytes>)>",
+					proof: "Vec<Bytes>"
+				},
+				CertificateVersion: {
+					_enum: ["V0_1"]
+				},
+				Certificate: {
+					version: "CertificateVersion",
+					device: "CertificateId",
+					signer: "CertificateId",
+					created: "BlockNumber",
+					expires: "BlockNumber",
+					signature: "CertificateSignature"
+				},
+				CertificateStatus: {
+					_enum: ["Valid", "NotFound", "OwnerNotAMember", "Revoked", "Expired", "Superseded"]
+				},
+				VerificationResult: {
+					_enum: {
+						Valid: "Null",
+						Expired: "CertificateId",
+						Revoked: "CertificateId",
+						RootNotAMember: "CertificateId",
+						BadSignature: "CertificateId",
+						UnknownRoot: "CertificateId",
+						InvalidChain: "Null",
+						Superseded: "CertificateId",
+						Suspended: "CertificateId"
+					}
 				}
 			},
 			rpc: {
@@ -51,6 +124,80 @@ class Runtime {
 							}
 						],
 						type: "bool"
+					},
+					rootCertificateStatus: {
+						description: "Why a root certificate is valid or not",
+						params: [{
+							name: "cert",
+							type: "CertificateId"
+						}],
+						type: "CertificateStatus"
+					},
+					childCertificateStatus: {
+						description: "Why a child certificate is valid or not",
+						params: [
+							{
+								name: "root",
+								type: "CertificateId"
+							},
+							{
+								name: "child",
+								type: "CertificateId"
+							}
+						],
+						type: "VerificationResult"
+					},
+					verifyChain: {
+						description: "Verify a chain of certificates, starting with the leaf",
+						params: [{
+							name: "chain",
+							type: "Vec<Certificate>"
+						}],
+						type: "VerificationResult"
+					},
+					rootCertificateRevocation: {
+						description: "When and why a root certificate was revoked",
+						params: [{
+							name: "cert",
+							type: "CertificateId"
+						}],
+						type: "Option<Revocation>"
+					},
+					childCertificateRevocation: {
+						description: "When and why a child certificate was revoked",
+						params: [
+							{
+								name: "root",
+								type: "CertificateId"
+							},
+							{
+								name: "child",
+								type: "CertificateId"
+							}
+						],
+						type: "Option<Revocation>"
+					},
+					revocationList: {
+						description: "Revocations in effect under a root certificate, with a storage proof",
+						params: [{
+							name: "root",
+							type: "CertificateId"
+						}],
+						type: "RevocationList"
+					},
+					proveCertificate: {
+						description: "Storage entries proving the status of a child certificate",
+						params: [
+							{
+								name: "root",
+								type: "CertificateId"
+							},
+							{
+								name: "child",
+								type: "CertificateId"
+							}
+						],
+						type: "CertificateProof"
 					}
 				}
 			}
@@ -58,45 +205,135 @@ class Runtime {
 	}
 
 	async slotStatus(signerAddress) {
-		const slot = await this.api.query.rootOfTrust.slots(signerAddress);
-		const isValid = await this.api.rpc.rootOfTrust.isRootCertificateValid(signerAddress);
+		const key = this.certificateKey(signerAddress);
+		const slot = await this.api.query.rootOfTrust.slots(key);
+		const isValid = await this.api.rpc.rootOfTrust.isRootCertificateValid(key);
+		const status = await this.api.rpc.rootOfTrust.rootCertificateStatus(key);
 
 		return {
 			signingAddress: slot.key,
 			ownerAddress: slot.owner,
-			valid: isValid
+			valid: isValid,
+			status: status.toString()
 		}
 	}
 
+	async currentBlock() {
+		const header = await this.api.rpc.chain.getHeader();
+
+		return header.number.toNumber();
+	}
+
 	async rootAndChildValid(root, child) {
-		return await this.api.rpc.rootOfTrust.isChildCertificateValid(root, child)
+		return await this.api.rpc.rootOfTrust.isChildCertificateValid(this.certificateKey(root), this.certificateKey(child))
+	}
+
+	async revocationList(root) {
+		return await this.api.rpc.rootOfTrust.revocationList(this.certificateKey(root))
+	}
+
+	async proveCertificate(root, child) {
+		return await this.api.rpc.rootOfTrust.proveCertificate(this.certificateKey(root), this.certificateKey(child))
+	}
+
+	// Certificates issued by this package are signed with ed25519 keys, their
+	// addresses are mapped to ed25519 certificate keys
+	certificateKey(key) {
+		if (typeof key === 'string') {
+			return { Ed25519: this.keyring.decodeAddress(key) };
+		}
+
+		return key;
 	}
 
 	setSigner(seed) {
 		this.signer = this.keyring.addFromUri(seed);
 	}
 
-	async bookSlot(slotAddress) {
+	// The certificate key proves it is held by the signer by signing the signer
+	// account, the genesis hash and the booking nonce of the certificate
+	async possessionProof(certificateSeed) {
+		const certificate = new Keyring({ type: 'ed25519' }).addFromUri(certificateSeed);
+		const key = this.certificateKey(certificate.address);
+		const nonce = await this.api.query.rootOfTrust.bookingNonces(key);
+		const payload = u8aConcat(
+			this.api.createType('AccountId', this.signer.address).toU8a(),
+			this.api.genesisHash.toU8a(),
+			nonce.toU8a()
+		);
+
+		return { key, proof: { Ed25519: certificate.sign(payload) } };
+	}
+
+	async bookSlot(certificateSeed, validity) {
+		const { key, proof } = await this.possessionProof(certificateSeed);
+
+		return await this.api.tx.rootOfTrust
+			.bookSlot(key, validity, proof)
+			.signAndSend(this.signer)
+	}
+
+	async renewSlot(slotAddress, validity) {
 		return await this.api.tx.rootOfTrust
-			.bookSlot(slotAddress)
+			.renewSlot(this.certificateKey(slotAddress), validity)
 			.signAndSend(this.signer)
 	}
 
-	async renewSlot(slotAddress) {
+	async revokeSlot(slotAddress, reason) {
 		return await this.api.tx.rootOfTrust
-			.renewSlot(slotAddress)
+			.revokeSlot(this.certificateKey(slotAddress), reason)
 			.signAndSend(this.signer)
 	}
 
-	async revokeSlot(slotAddress) {
+	async revokeChild(root, child, reason) {
 		return await this.api.tx.rootOfTrust
-			.revokeSlot(slotAddress)
+			.revokeChild(this.certificateKey(root), this.certificateKey(child), reason)
 			.signAndSend(this.signer)
 	}
 
-	async revokeChild(root, child) {
+	async revokeChildren(root, children, reason) {
+		return await this.api.tx.rootOfTrust
+			.revokeChildren(this.certificateKey(root), children.map(child => this.certificateKey(child)), reason)
+			.signAndSend(this.signer)
+	}
+
+	async suspendChild(root, child) {
+		return await this.api.tx.rootOfTrust
+			.suspendChild(this.certificateKey(root), this.certificateKey(child))
+			.signAndSend(this.signer)
+	}
+
+	async reinstateChild(root, child) {
+		return await this.api.tx.rootOfTrust
+			.reinstateChild(this.certificateKey(root), this.certificateKey(child))
+			.signAndSend(this.signer)
+	}
+
+	async revokeChildrenIssuedBefore(root, block) {
+		return await this.api.tx.rootOfTrust
+			.revokeChildrenIssuedBefore(this.certificateKey(root), block)
+			.signAndSend(this.signer)
+	}
+
+	async transferSlot(slotAddress, newOwner) {
+		return await this.api.tx.rootOfTrust
+			.transferSlot(this.certificateKey(slotAddress), newOwner)
+			.signAndSend(this.signer)
+	}
+
+	async acceptSlotTransfer(slotAddress) {
+		return await this.api.tx.rootOfTrust
+			.acceptSlotTransfer(this.certificateKey(slotAddress))
+			.signAndSend(this.signer)
+	}
+
+	// Rotating with a `KeyCompromise` or `CACompromise` reason revokes the old key and
+	// every certificate it signed, the new key has to prove it is held by the signer
+	async rotateRoot(oldAddress, newSeed, reason) {
+		const { key, proof } = await this.possessionProof(newSeed);
+
 		return await this.api.tx.rootOfTrust
-			.revokeChild(root, child)
+			.rotateRoot(this.certificateKey(oldAddress), key, reason, proof)
 			.signAndSend(this.signer)
 	}
 }
@@ -1,7 +1,7 @@
 const { Certificate } = require('./');
 const errors = require('./errors');
 const { Keyring } = require('@polkadot/api');
-const moment = require('moment');
+const { hexToU8a, u8aToHex } = require('@polkadot/util');
 const { expect } = require('chai');
 
 describe('Certificate', () => {
@@ -11,7 +11,8 @@ describe('Certificate', () => {
     const paramsCert = {
         device: '5GdqmKwPke1CmjcAfTwe5dJbHqFUsYFCypRqsQBNNRDzghJJ',
         pair: pair,
-        expiry: moment().add(1, 'month')
+        created: 100,
+        expiry: 432100
     };
     const testCert = new Certificate(paramsCert);
     const encodedCert = testCert.signAndEncode();
@@ -23,6 +24,31 @@ describe('Certificate', () => {
         })
     })
 
+    // Interop vector of the `pki-certificate` crate, signer and device are derived
+    // from the ed25519 seeds `[1; 32]` and `[2; 32]`
+    context('Rust vectors', () => {
+        const signer = keyring.addFromSeed(new Uint8Array(32).fill(1));
+        const device = '0x8139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b394';
+        const message = '0x00008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b394008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c64000000e4970600';
+        const signature = '0x22d6bbb906521a5eb2ce46a4eb0d4a7e20ed7c6ff9f85d8ea9cb57b2001cc3e38a57f15133a3ec5214c4d23f16b5aaaf4adb7dadba618dbf096e7cfc86ad5403';
+
+        const vectorCert = new Certificate({
+            device: keyring.encodeAddress(hexToU8a(device)),
+            pair: signer,
+            created: 100,
+            expiry: 432100
+        });
+
+        it('message is the SCALE encoded runtime payload', () => {
+            expect(u8aToHex(signer.publicKey)).to.be.equal('0x8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c');
+            expect(u8aToHex(Certificate.message(vectorCert.sign().payload))).to.be.equal(message);
+        })
+
+        it('signature matches the one of the rust implementation', () => {
+            expect(vectorCert.sign().signature).to.be.equal(signature);
+        })
+    })
+
     context('Encode / Decode', () => {
         it('encoding and decoding return the expected values', () => {
             const expected = testCert.sign();
@@ -36,7 +62,7 @@ describe('Certificate', () => {
         context('Offline', () => {
             it('return true if certificate is valid', () => {
                 expect(
-                    Certificate.verifyCertificateWithoutIssuerChecks(encodedCert, console.log)
+                    Certificate.verifyCertificateWithoutIssuerChecks(encodedCert, console.log, 1000)
                 ).to.be.true;
             })
 
@@ -59,8 +85,16 @@ describe('Certificate', () => {
             })
 
             it('fail if expired', () => {
+                expect(
+                    Certificate.verifyCertificateWithoutIssuerChecks(encodedCert, (c, r) => {
+                        expect(r).to.be.equal(errors.errExpired);
+                    }, 432100)
+                ).to.be.false;
+            })
+
+            it('fail if expiring before its creation', () => {
                 const badCert = new Certificate(paramsCert);
-                badCert.expirationDate = moment().subtract(1, 'day');
+                badCert.expirationBlock = 50;
 
                 const encodedBadCert = badCert.signAndEncode();
 
@@ -99,8 +133,24 @@ describe('Certificate', () => {
         })
 
         context('Online', () => {
+            it('fail if expired at the current block', async () => {
+                const mockRuntime = {
+                    currentBlock: async () => 432100,
+                    rootAndChildValid: async (s, d) => {
+                        return true;
+                    }
+                };
+
+                expect(
+                    await Certificate.verify(encodedCert, mockRuntime, (c, r) => {
+                        expect(r).to.be.equal(errors.errExpired);
+                    })
+                ).to.be.false;
+            })
+
             it('return true if confirmed by the chain', async () => {
                 const mockRuntime = {
+                    currentBlock: async () => 1000,
                     rootAndChildValid: async (s, d) => {
                         return true;
                     }
@@ -113,6 +163,7 @@ describe('Certificate', () => {
 
             it('fail if chain says it is revoked or invalid', async () => {
                 const mockRuntime = {
+                    currentBlock: async () => 1000,
                     rootAndChildValid: async (s, d) => {
                         return false;
                     }
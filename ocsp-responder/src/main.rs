@@ -0,0 +1,260 @@
+//! Certificate status responder for the tools speaking OCSP style queries rather than
+//! JSON-RPC. It answers `GET /status/<root>/<child>` requests, where both certificates
+//! are written as `<algorithm>:0x<hex public key>`, with a status signed by the
+//! responder key. Statuses are computed at the last finalized block and cached until
+//! the next one is finalized or their validity window ends.
+
+#[cfg(test)]
+mod tests;
+
+mod response;
+mod rpc;
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode, Uri,
+};
+use log::{info, warn};
+use pki_certificate::CertificateKey;
+use response::{CertificateStatus, SignedResponse, StatusResponse};
+use rpc::{FinalizedHead, NodeClient};
+use sp_core::{
+    crypto::{AccountId32, Ss58Codec},
+    sr25519::Pair,
+    Pair as PairT,
+};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use structopt::StructOpt;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "pki-ocsp-responder")]
+struct Opt {
+    /// HTTP JSON-RPC endpoint of the node
+    #[structopt(long, default_value = "http://localhost:9933")]
+    rpc_url: Uri,
+
+    /// Address to serve status requests on
+    #[structopt(long, default_value = "127.0.0.1:8080")]
+    listen: SocketAddr,
+
+    /// Secret URI of the sr25519 key signing the responses
+    #[structopt(long)]
+    key: String,
+
+    /// How often to check for a new finalized block, in milliseconds
+    #[structopt(long, default_value = "1000")]
+    poll_interval: u64,
+
+    /// How long a response may be relied upon after it was produced, in seconds
+    #[structopt(long, default_value = "300")]
+    validity: u64,
+}
+
+/// Statuses computed at `head`, the last finalized block we know of
+#[derive(Default)]
+struct Cache {
+    head: Option<FinalizedHead>,
+    statuses: HashMap<(CertificateKey, CertificateKey), SignedResponse>,
+}
+
+impl Cache {
+    /// Record the finalized head, cached statuses are dropped if it changed
+    fn set_head(&mut self, head: FinalizedHead) {
+        if self.head.map(|head| head.hash) != Some(head.hash) {
+            self.head = Some(head);
+            self.statuses.clear();
+        }
+    }
+
+    /// Cached status of `ids` if its validity window did not end by `now`
+    fn get(&self, ids: &(CertificateKey, CertificateKey), now: u64) -> Option<SignedResponse> {
+        self.statuses
+            .get(ids)
+            .filter(|response| response.response.next_update > now)
+            .cloned()
+    }
+}
+
+struct Responder {
+    node: NodeClient,
+    key: Pair,
+    /// Length of the validity window of the responses, in seconds
+    validity: u64,
+    cache: Mutex<Cache>,
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+impl Responder {
+    async fn status(&self, root: CertificateKey, child: CertificateKey) -> SignedResponse {
+        let now = unix_time();
+        let cached_head = {
+            let cache = self.cache.lock().expect("cache lock is not poisoned");
+            if let Some(response) = cache.get(&(root.clone(), child.clone()), now) {
+                return response;
+            }
+            cache.head
+        };
+
+        let head = match cached_head {
+            Some(head) => Ok(head),
+            None => self.node.finalized_head().await,
+        };
+        let status = match head {
+            Ok(head) => self
+                .node
+                .child_certificate_status(&root, &child, head.hash)
+                .await
+                .map(|result| (head, result)),
+            Err(e) => Err(e),
+        };
+
+        match status {
+            Ok((head, result)) => {
+                let response = StatusResponse {
+                    root: root.clone(),
+                    child: child.clone(),
+                    status: CertificateStatus::from(&result),
+                    block_hash: Some(head.hash),
+                    block_number: Some(head.number),
+                    this_update: now,
+                    next_update: now + self.validity,
+                }
+                .sign(&self.key);
+
+                let mut cache = self.cache.lock().expect("cache lock is not poisoned");
+                // The head may have moved while we were querying the node
+                if cache.head.is_none() || cache.head == Some(head) {
+                    cache.set_head(head);
+                    cache.statuses.insert((root, child), response.clone());
+                }
+
+                response
+            }
+            Err(e) => {
+                warn!("failed to query the node: {}", e);
+
+                // Not cached, the node may be reachable again on the next request
+                StatusResponse {
+                    root,
+                    child,
+                    status: CertificateStatus::Unknown,
+                    block_hash: None,
+                    block_number: None,
+                    this_update: now,
+                    next_update: now,
+                }
+                .sign(&self.key)
+            }
+        }
+    }
+
+    async fn handle(self: Arc<Self>, request: Request<Body>) -> Response<Body> {
+        if request.method() != Method::GET {
+            return reply(StatusCode::METHOD_NOT_ALLOWED, "only GET is supported");
+        }
+
+        let (root, child) = match parse_path(request.uri().path()) {
+            Some(ids) => ids,
+            None => return reply(StatusCode::NOT_FOUND, "expected /status/<root>/<child>"),
+        };
+
+        let response = self.status(root, child).await;
+        match serde_json::to_string(&response) {
+            Ok(body) => Response::builder()
+                .header("content-type", "application/json")
+                .body(Body::from(body))
+                .expect("response is well formed"),
+            Err(_) => reply(StatusCode::INTERNAL_SERVER_ERROR, "failed to encode"),
+        }
+    }
+}
+
+fn reply(status: StatusCode, message: &'static str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::from(message))
+        .expect("response is well formed")
+}
+
+/// Extract the root and child certificates of a `/status/<root>/<child>` path
+fn parse_path(path: &str) -> Option<(CertificateKey, CertificateKey)> {
+    let mut segments = path.trim_matches('/').split('/');
+    if segments.next() != Some("status") {
+        return None;
+    }
+
+    let root = segments.next()?.parse().ok()?;
+    let child = segments.next()?.parse().ok()?;
+    if segments.next().is_some() {
+        return None;
+    }
+
+    Some((root, child))
+}
+
+/// Keep track of the finalized head so that cached statuses are dropped when a new
+/// block is finalized
+async fn follow_finality(responder: Arc<Responder>, interval: Duration) {
+    let mut interval = tokio::time::interval(interval);
+    loop {
+        interval.tick().await;
+        match responder.node.finalized_head().await {
+            Ok(head) => responder
+                .cache
+                .lock()
+                .expect("cache lock is not poisoned")
+                .set_head(head),
+            Err(e) => warn!("failed to fetch the finalized head: {}", e),
+        }
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    env_logger::init();
+    let opt = Opt::from_args();
+
+    let key = Pair::from_string(&opt.key, None).map_err(|_| "invalid responder key")?;
+    info!(
+        "signing responses with {}",
+        AccountId32::from(key.public()).to_ss58check()
+    );
+
+    let responder = Arc::new(Responder {
+        node: NodeClient::new(opt.rpc_url),
+        key,
+        validity: opt.validity,
+        cache: Mutex::new(Cache::default()),
+    });
+    tokio::spawn(follow_finality(
+        responder.clone(),
+        Duration::from_millis(opt.poll_interval),
+    ));
+
+    let service = make_service_fn(move |_| {
+        let responder = responder.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let responder = responder.clone();
+                async move { Ok::<_, Infallible>(responder.handle(request).await) }
+            }))
+        }
+    });
+
+    info!("listening on {}", opt.listen);
+    Server::bind(&opt.listen).serve(service).await?;
+
+    Ok(())
+}
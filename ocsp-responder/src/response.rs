@@ -0,0 +1,99 @@
+//! Signed status responses returned to the clients.
+
+use crate::rpc::BlockNumber;
+use codec::Encode;
+use pki_certificate::{CertificateKey, VerificationResult};
+use serde::Serialize;
+use sp_core::{
+    crypto::AccountId32,
+    sr25519::{Pair, Public, Signature},
+    Bytes, Pair as PairT, H256,
+};
+
+/// Status of a certificate, named after their OCSP counterparts
+#[derive(Encode, Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum CertificateStatus {
+    /// The certificate is valid
+    Good,
+    /// The certificate, one of its issuers or its root is not valid anymore
+    Revoked,
+    /// The certificate does not chain up to a root we know of, or the node could not
+    /// be queried
+    Unknown,
+}
+
+impl From<&VerificationResult<CertificateKey>> for CertificateStatus {
+    fn from(result: &VerificationResult<CertificateKey>) -> Self {
+        match result {
+            VerificationResult::Valid => CertificateStatus::Good,
+            // A suspension is reported like an OCSP `certificateHold` revocation
+            VerificationResult::Revoked(_)
+            | VerificationResult::Suspended(_)
+            | VerificationResult::Expired(_)
+            | VerificationResult::RootNotAMember(_)
+            | VerificationResult::Superseded(_)
+            | VerificationResult::BadSignature(_) => CertificateStatus::Revoked,
+            // We can not vouch for certificates we never saw issued
+            VerificationResult::UnknownRoot(_) | VerificationResult::InvalidChain => {
+                CertificateStatus::Unknown
+            }
+        }
+    }
+}
+
+/// Content covered by the responder signature, the signature is computed over its
+/// SCALE encoding
+#[derive(Encode, Serialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusResponse {
+    pub root: CertificateKey,
+    pub child: CertificateKey,
+    pub status: CertificateStatus,
+    /// Finalized block at which the status was computed
+    pub block_hash: Option<H256>,
+    pub block_number: Option<BlockNumber>,
+    /// Unix time, in seconds, at which the response was produced
+    pub this_update: u64,
+    /// Unix time, in seconds, after which the response should not be relied upon
+    pub next_update: u64,
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedResponse {
+    #[serde(flatten)]
+    pub response: StatusResponse,
+    pub responder: AccountId32,
+    pub signature: Bytes,
+}
+
+impl StatusResponse {
+    pub fn sign(self, key: &Pair) -> SignedResponse {
+        let signature = key.sign(&self.encode());
+
+        SignedResponse {
+            response: self,
+            responder: key.public().into(),
+            signature: Bytes(signature.0.to_vec()),
+        }
+    }
+}
+
+impl SignedResponse {
+    pub fn verify(&self) -> bool {
+        if self.signature.len() != 64 {
+            return false;
+        }
+
+        let mut signature = [0u8; 64];
+        signature.copy_from_slice(&self.signature);
+        let responder: &[u8; 32] = self.responder.as_ref();
+
+        Pair::verify(
+            &Signature(signature),
+            &self.response.encode(),
+            &Public(*responder),
+        )
+    }
+}
@@ -0,0 +1,136 @@
+//! Minimal JSON-RPC client for the calls the responder needs from the node.
+
+use hyper::{client::HttpConnector, Body, Client, Request, Uri};
+use pki_certificate::{CertificateKey, VerificationResult};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use sp_core::H256;
+use std::fmt;
+
+/// Block number type of the runtime
+pub type BlockNumber = u32;
+
+#[derive(Debug)]
+pub enum Error {
+    Http(hyper::Error),
+    Request(hyper::http::Error),
+    Json(serde_json::Error),
+    /// The node answered with an error
+    Rpc(Value),
+    /// The node returned a block number we could not parse
+    BadBlockNumber(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Http(e) => write!(f, "http error: {}", e),
+            Error::Request(e) => write!(f, "invalid request: {}", e),
+            Error::Json(e) => write!(f, "invalid json: {}", e),
+            Error::Rpc(e) => write!(f, "rpc error: {}", e),
+            Error::BadBlockNumber(n) => write!(f, "invalid block number: {}", n),
+        }
+    }
+}
+
+impl From<hyper::Error> for Error {
+    fn from(e: hyper::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+impl From<hyper::http::Error> for Error {
+    fn from(e: hyper::http::Error) -> Self {
+        Error::Request(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Json(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct Reply<T> {
+    result: Option<T>,
+    error: Option<Value>,
+}
+
+/// The fields we need from a block header, numbers are hex encoded
+#[derive(Deserialize)]
+struct Header {
+    number: String,
+}
+
+/// Last finalized block of the node
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FinalizedHead {
+    pub hash: H256,
+    pub number: BlockNumber,
+}
+
+#[derive(Clone)]
+pub struct NodeClient {
+    client: Client<HttpConnector>,
+    url: Uri,
+}
+
+impl NodeClient {
+    pub fn new(url: Uri) -> Self {
+        NodeClient {
+            client: Client::new(),
+            url,
+        }
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, Error> {
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": method,
+            "params": params,
+        });
+        let request = Request::post(self.url.clone())
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))?;
+
+        let response = self.client.request(request).await?;
+        let bytes = hyper::body::to_bytes(response.into_body()).await?;
+        let reply: Reply<T> = serde_json::from_slice(&bytes)?;
+
+        match (reply.result, reply.error) {
+            (Some(result), _) => Ok(result),
+            (None, error) => Err(Error::Rpc(error.unwrap_or(Value::Null))),
+        }
+    }
+
+    pub async fn finalized_head(&self) -> Result<FinalizedHead, Error> {
+        let hash: H256 = self.call("chain_getFinalizedHead", json!([])).await?;
+        let header: Header = self.call("chain_getHeader", json!([hash])).await?;
+
+        Ok(FinalizedHead {
+            hash,
+            number: parse_block_number(&header.number)?,
+        })
+    }
+
+    pub async fn child_certificate_status(
+        &self,
+        root: &CertificateKey,
+        child: &CertificateKey,
+        at: H256,
+    ) -> Result<VerificationResult<CertificateKey>, Error> {
+        self.call(
+            "rootOfTrust_childCertificateStatus",
+            json!([root, child, at]),
+        )
+        .await
+    }
+}
+
+/// Parse the `0x` prefixed hex block numbers found in the headers
+pub fn parse_block_number(number: &str) -> Result<BlockNumber, Error> {
+    let digits = number.trim_start_matches("0x");
+    BlockNumber::from_str_radix(digits, 16).map_err(|_| Error::BadBlockNumber(number.into()))
+}
@@ -0,0 +1,156 @@
+use super::*;
+use pki_certificate::VerificationResult;
+use rpc::parse_block_number;
+use sp_core::H256;
+
+fn account(seed: &str) -> AccountId32 {
+    Pair::from_string(seed, None)
+        .expect("valid seed")
+        .public()
+        .into()
+}
+
+fn certificate(seed: &str) -> CertificateKey {
+    Pair::from_string(seed, None)
+        .expect("valid seed")
+        .public()
+        .into()
+}
+
+fn response() -> StatusResponse {
+    StatusResponse {
+        root: certificate("//Alice"),
+        child: certificate("//Bob"),
+        status: CertificateStatus::Good,
+        block_hash: Some(H256::repeat_byte(1)),
+        block_number: Some(1),
+        this_update: 100,
+        next_update: 400,
+    }
+}
+
+fn head(byte: u8) -> FinalizedHead {
+    FinalizedHead {
+        hash: H256::repeat_byte(byte),
+        number: byte.into(),
+    }
+}
+
+#[test]
+fn parses_status_paths() {
+    let root = certificate("//Alice");
+    let child = certificate("//Bob");
+    let path = format!("/status/{}/{}", root, child);
+
+    assert_eq!(parse_path(&path), Some((root, child)));
+}
+
+#[test]
+fn rejects_malformed_paths() {
+    let root = certificate("//Alice").to_string();
+    let child = certificate("//Bob").to_string();
+
+    assert_eq!(parse_path("/"), None);
+    assert_eq!(parse_path(&format!("/status/{}", root)), None);
+    assert_eq!(parse_path(&format!("/status/{}/not_an_id", root)), None);
+    assert_eq!(parse_path(&format!("/other/{}/{}", root, child)), None);
+    assert_eq!(
+        parse_path(&format!("/status/{}/{}/{}", root, child, child)),
+        None
+    );
+}
+
+#[test]
+fn responses_are_signed_by_the_responder() {
+    let key = Pair::from_string("//Responder", None).expect("valid seed");
+    let signed = response().sign(&key);
+
+    assert_eq!(signed.responder, account("//Responder"));
+    assert_eq!(signed.verify(), true);
+}
+
+#[test]
+fn tampered_responses_do_not_verify() {
+    let key = Pair::from_string("//Responder", None).expect("valid seed");
+    let mut signed = response().sign(&key);
+    signed.response.status = CertificateStatus::Revoked;
+
+    assert_eq!(signed.verify(), false);
+}
+
+#[test]
+fn validity_window_is_signed() {
+    let key = Pair::from_string("//Responder", None).expect("valid seed");
+
+    let mut signed = response().sign(&key);
+    signed.response.next_update = 1_000;
+    assert_eq!(signed.verify(), false);
+
+    let mut signed = response().sign(&key);
+    signed.response.block_number = Some(2);
+    assert_eq!(signed.verify(), false);
+}
+
+#[test]
+fn statuses_follow_the_verification_result() {
+    let child = certificate("//Bob");
+    let status = |result: VerificationResult<CertificateKey>| CertificateStatus::from(&result);
+
+    assert_eq!(status(VerificationResult::Valid), CertificateStatus::Good);
+    assert_eq!(
+        status(VerificationResult::Revoked(child.clone())),
+        CertificateStatus::Revoked
+    );
+    assert_eq!(
+        status(VerificationResult::Suspended(child.clone())),
+        CertificateStatus::Revoked
+    );
+    assert_eq!(
+        status(VerificationResult::Expired(child.clone())),
+        CertificateStatus::Revoked
+    );
+    assert_eq!(
+        status(VerificationResult::UnknownRoot(child)),
+        CertificateStatus::Unknown
+    );
+    assert_eq!(
+        status(VerificationResult::InvalidChain),
+        CertificateStatus::Unknown
+    );
+}
+
+#[test]
+fn parses_header_block_numbers() {
+    assert_eq!(parse_block_number("0x0").ok(), Some(0));
+    assert_eq!(parse_block_number("0x1a").ok(), Some(26));
+    assert_eq!(parse_block_number("0xzz").is_err(), true);
+}
+
+#[test]
+fn cache_is_dropped_on_new_finalized_blocks() {
+    let key = Pair::from_string("//Responder", None).expect("valid seed");
+    let mut cache = Cache::default();
+    cache.set_head(head(1));
+    cache.statuses.insert(
+        (certificate("//Alice"), certificate("//Bob")),
+        response().sign(&key),
+    );
+
+    cache.set_head(head(1));
+    assert_eq!(cache.statuses.len(), 1);
+
+    cache.set_head(head(2));
+    assert_eq!(cache.statuses.is_empty(), true);
+}
+
+#[test]
+fn expired_responses_are_not_served_from_the_cache() {
+    let key = Pair::from_string("//Responder", None).expect("valid seed");
+    let ids = (certificate("//Alice"), certificate("//Bob"));
+    let mut cache = Cache::default();
+    cache.set_head(head(1));
+    cache.statuses.insert(ids.clone(), response().sign(&key));
+
+    assert_eq!(cache.get(&ids, 399).is_some(), true);
+    assert_eq!(cache.get(&ids, 400), None);
+}
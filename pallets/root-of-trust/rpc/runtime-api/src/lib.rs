@@ -1,10 +1,20 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
+use pki_certificate::{CertificateStatus, Revocation, VerificationResult};
+use sp_std::prelude::Vec;
+
 sp_api::decl_runtime_apis! {
-    pub trait RootOfTrustApi<CertificateId> where
-        CertificateId: codec::Codec
+    pub trait RootOfTrustApi<CertificateId, Certificate, BlockNumber> where
+        CertificateId: codec::Codec,
+        Certificate: codec::Codec,
+        BlockNumber: codec::Codec,
     {
         fn is_root_certificate_valid(cert: &CertificateId) -> bool;
         fn is_child_certificate_valid(root: &CertificateId, child: &CertificateId) -> bool;
+        fn root_certificate_status(cert: &CertificateId) -> CertificateStatus;
+        fn child_certificate_status(root: &CertificateId, child: &CertificateId) -> VerificationResult<CertificateId>;
+        fn verify_certificate_chain(chain: Vec<Certificate>) -> VerificationResult<CertificateId>;
+        fn root_certificate_revocation(cert: &CertificateId) -> Option<Revocation<BlockNumber>>;
+        fn child_certificate_revocation(root: &CertificateId, child: &CertificateId) -> Option<Revocation<BlockNumber>>;
     }
 }
@@ -0,0 +1,124 @@
+//! Export of the revocations made under a root certificate, along with a storage
+//! proof so that offline relying parties can check them against a state root they
+//! trust instead of trusting the RPC node.
+
+use crate::{error::internal_error, reader::RecordingReader};
+use codec::{Codec, Decode};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use pki_certificate::{CertificateStatus, Revocation};
+use pki_light_verifier::Verifier;
+use sc_client_api::{backend::Backend, ProofProvider, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{
+    generic::BlockId,
+    traits::{Block as BlockT, Header as HeaderT, NumberFor},
+};
+use std::sync::Arc;
+
+/// Revocations, suspensions and revocation cutoffs in effect for every certificate
+/// chaining up to `root` at a given block, see `pki_light_verifier::RevocationList`
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationList<Hash, BlockNumber, CertificateId> {
+    pub root: CertificateId,
+    pub block_hash: Hash,
+    pub block_number: BlockNumber,
+    pub state_root: Hash,
+    /// Status of `root` itself, none of its children are valid unless it is valid
+    pub root_status: CertificateStatus,
+    /// When and why `root` was revoked, if it was
+    pub root_revocation: Option<Revocation<BlockNumber>>,
+    /// `root`, the root certificates it replaced and its intermediates
+    pub issuers: Vec<CertificateId>,
+    /// Issuer, child and revocation details
+    pub revocations: Vec<(CertificateId, CertificateId, Revocation<BlockNumber>)>,
+    /// Issuer, child and the block at which it was suspended
+    pub suspensions: Vec<(CertificateId, CertificateId, BlockNumber)>,
+    /// Children issued before the given block by the issuer are revoked
+    pub revoked_before: Vec<(CertificateId, BlockNumber)>,
+    /// Trie nodes proving every value above against `state_root`, including every
+    /// entry under the prefixes of the issuers. `pki_light_verifier::verify_revocation_list`
+    /// rebuilds the list from them so that omitted entries are detected.
+    pub proof: Vec<Bytes>,
+}
+
+#[rpc]
+pub trait RevocationListApi<BlockHash, BlockNumber, CertificateId> {
+    #[rpc(name = "rootOfTrust_revocationList")]
+    fn revocation_list(
+        &self,
+        root: CertificateId,
+        at: Option<BlockHash>,
+    ) -> Result<RevocationList<BlockHash, BlockNumber, CertificateId>>;
+}
+
+pub struct RevocationLists<C, B, BE, AccountId> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<(B, BE, AccountId)>,
+}
+
+impl<C, B, BE, AccountId> RevocationLists<C, B, BE, AccountId> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, BE, AccountId, CertificateId>
+    RevocationListApi<<Block as BlockT>::Hash, NumberFor<Block>, CertificateId>
+    for RevocationLists<C, Block, BE, AccountId>
+where
+    Block: BlockT,
+    BE: Backend<Block> + Send + Sync + 'static,
+    C: Send + Sync + 'static,
+    C: HeaderBackend<Block> + StorageProvider<Block, BE> + ProofProvider<Block>,
+    AccountId: Decode + PartialEq + Send + Sync + 'static,
+    CertificateId: Codec + Clone + PartialEq + Send + Sync + 'static,
+{
+    fn revocation_list(
+        &self,
+        root: CertificateId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<RevocationList<<Block as BlockT>::Hash, NumberFor<Block>, CertificateId>> {
+        // If the block hash is not supplied assume the best block.
+        let block_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+        let at = BlockId::hash(block_hash);
+        let header = self
+            .client
+            .header(at)
+            .map_err(internal_error)?
+            .ok_or_else(|| internal_error("Unknown block"))?;
+
+        // Build the list the same way as the light verifier so that the proof covers
+        // exactly the entries it will need
+        let storage = RecordingReader::<_, _, BE>::new(&*self.client, at);
+        let list = Verifier::<_, AccountId, _, _>::new(&storage, *header.number())
+            .revocation_list(&root)
+            .map_err(internal_error)?;
+        let entries = storage.entries.into_inner();
+
+        let proof = self
+            .client
+            .read_proof(&at, &mut entries.iter().map(|(key, _)| &key[..]))
+            .map_err(internal_error)?;
+
+        Ok(RevocationList {
+            root,
+            block_hash,
+            block_number: *header.number(),
+            state_root: *header.state_root(),
+            root_status: list.root_status,
+            root_revocation: list.root_revocation,
+            issuers: list.issuers,
+            revocations: list.revocations,
+            suspensions: list.suspensions,
+            revoked_before: list.revoked_before,
+            proof: proof.iter_nodes().map(Bytes).collect(),
+        })
+    }
+}
@@ -0,0 +1,12 @@
+//! Errors returned by the root of trust RPCs.
+
+use jsonrpc_core::{Error as RpcError, ErrorCode};
+
+/// Report a failure of the runtime API or of the client storage as an RPC error
+pub(crate) fn internal_error<E: std::fmt::Debug>(e: E) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(9876), // No real reason for this value
+        message: "Something wrong".into(),
+        data: Some(format!("{:?}", e).into()),
+    }
+}
@@ -1,16 +1,28 @@
 use codec::Codec;
-use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use error::internal_error;
+use jsonrpc_core::Result;
 use jsonrpc_derive::rpc;
 use pallet_root_of_trust_runtime_api::RootOfTrustApi as RootOfTrustRuntimeApi;
+use pki_certificate::{CertificateStatus, Revocation, VerificationResult};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_runtime::{generic::BlockId, traits::Block as BlockT};
 use std::sync::Arc;
 
+mod crl;
+mod error;
+mod proof;
+mod reader;
+
+pub use crl::{RevocationList, RevocationListApi, RevocationLists};
+pub use proof::{CertificateProof, CertificateProofApi, CertificateProofs};
+
 #[rpc]
-pub trait RootOfTrustApi<BlockHash, CertificateId>
+pub trait RootOfTrustApi<BlockHash, CertificateId, Certificate, BlockNumber>
 where
     CertificateId: Codec,
+    Certificate: Codec,
+    BlockNumber: Codec,
 {
     #[rpc(name = "rootOfTrust_isRootCertificateValid")]
     fn is_root_certificate_valid(&self, cert: CertificateId, at: Option<BlockHash>)
@@ -22,6 +34,38 @@ where
         child: CertificateId,
         at: Option<BlockHash>,
     ) -> Result<bool>;
+    #[rpc(name = "rootOfTrust_rootCertificateStatus")]
+    fn root_certificate_status(
+        &self,
+        cert: CertificateId,
+        at: Option<BlockHash>,
+    ) -> Result<CertificateStatus>;
+    #[rpc(name = "rootOfTrust_childCertificateStatus")]
+    fn child_certificate_status(
+        &self,
+        root: CertificateId,
+        child: CertificateId,
+        at: Option<BlockHash>,
+    ) -> Result<VerificationResult<CertificateId>>;
+    #[rpc(name = "rootOfTrust_verifyChain")]
+    fn verify_chain(
+        &self,
+        chain: Vec<Certificate>,
+        at: Option<BlockHash>,
+    ) -> Result<VerificationResult<CertificateId>>;
+    #[rpc(name = "rootOfTrust_rootCertificateRevocation")]
+    fn root_certificate_revocation(
+        &self,
+        cert: CertificateId,
+        at: Option<BlockHash>,
+    ) -> Result<Option<Revocation<BlockNumber>>>;
+    #[rpc(name = "rootOfTrust_childCertificateRevocation")]
+    fn child_certificate_revocation(
+        &self,
+        root: CertificateId,
+        child: CertificateId,
+        at: Option<BlockHash>,
+    ) -> Result<Option<Revocation<BlockNumber>>>;
 }
 
 pub struct RootOfTrust<C, M> {
@@ -38,15 +82,18 @@ impl<C, M> RootOfTrust<C, M> {
     }
 }
 
-impl<C, Block, CertificateId> RootOfTrustApi<<Block as BlockT>::Hash, CertificateId>
+impl<C, Block, CertificateId, Certificate, BlockNumber>
+    RootOfTrustApi<<Block as BlockT>::Hash, CertificateId, Certificate, BlockNumber>
     for RootOfTrust<C, Block>
 where
     CertificateId: Codec,
+    Certificate: Codec,
+    BlockNumber: Codec,
     Block: BlockT,
     C: Send + Sync + 'static,
     C: ProvideRuntimeApi<Block>,
     C: HeaderBackend<Block>,
-    C::Api: RootOfTrustRuntimeApi<Block, CertificateId>,
+    C::Api: RootOfTrustRuntimeApi<Block, CertificateId, Certificate, BlockNumber>,
 {
     fn is_root_certificate_valid(
         &self,
@@ -59,11 +106,7 @@ where
 			self.client.info().best_hash));
 
         api.is_root_certificate_valid(&at, &cert)
-            .map_err(|e| RpcError {
-                code: ErrorCode::ServerError(9876), // No real reason for this value
-                message: "Something wrong".into(),
-                data: Some(format!("{:?}", e).into()),
-            })
+            .map_err(internal_error)
     }
 
     fn is_child_certificate_valid(
@@ -78,10 +121,78 @@ where
 			self.client.info().best_hash));
 
         api.is_child_certificate_valid(&at, &root, &child)
-            .map_err(|e| RpcError {
-                code: ErrorCode::ServerError(9876), // No real reason for this value
-                message: "Something wrong".into(),
-                data: Some(format!("{:?}", e).into()),
-            })
+            .map_err(internal_error)
+    }
+
+    fn root_certificate_status(
+        &self,
+        cert: CertificateId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<CertificateStatus> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+        api.root_certificate_status(&at, &cert)
+            .map_err(internal_error)
+    }
+
+    fn child_certificate_status(
+        &self,
+        root: CertificateId,
+        child: CertificateId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<VerificationResult<CertificateId>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+        api.child_certificate_status(&at, &root, &child)
+            .map_err(internal_error)
+    }
+
+    fn verify_chain(
+        &self,
+        chain: Vec<Certificate>,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<VerificationResult<CertificateId>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+        api.verify_certificate_chain(&at, chain)
+            .map_err(internal_error)
+    }
+
+    fn root_certificate_revocation(
+        &self,
+        cert: CertificateId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Revocation<BlockNumber>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+        api.root_certificate_revocation(&at, &cert)
+            .map_err(internal_error)
+    }
+
+    fn child_certificate_revocation(
+        &self,
+        root: CertificateId,
+        child: CertificateId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<Option<Revocation<BlockNumber>>> {
+        let api = self.client.runtime_api();
+        let at = BlockId::hash(at.unwrap_or_else(||
+			// If the block hash is not supplied assume the best block.
+			self.client.info().best_hash));
+
+        api.child_certificate_revocation(&at, &root, &child)
+            .map_err(internal_error)
     }
 }
@@ -0,0 +1,117 @@
+//! Proofs of the storage entries backing the validity of a certificate, so that
+//! light clients can verify certificates against a header they trust using the
+//! `pki-light-verifier` crate instead of trusting the RPC node.
+
+use crate::{error::internal_error, reader::RecordingReader};
+use codec::{Codec, Decode};
+use jsonrpc_core::Result;
+use jsonrpc_derive::rpc;
+use pki_certificate::VerificationResult;
+use pki_light_verifier::Verifier;
+use sc_client_api::{backend::Backend, ProofProvider, StorageProvider};
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+use sp_runtime::{
+    generic::BlockId,
+    traits::{Block as BlockT, Header as HeaderT, NumberFor},
+};
+use std::sync::Arc;
+
+/// Storage entries read to verify a child certificate at a given block
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateProof<Hash, BlockNumber, CertificateId> {
+    pub root: CertificateId,
+    pub child: CertificateId,
+    pub block_hash: Hash,
+    pub block_number: BlockNumber,
+    pub state_root: Hash,
+    /// Status of the certificate according to the node, to be checked against the proof
+    pub status: VerificationResult<CertificateId>,
+    /// Storage keys and values read during the verification, absent values are proven
+    /// too
+    pub entries: Vec<(Bytes, Option<Bytes>)>,
+    /// Trie nodes proving `entries` against `state_root`
+    pub proof: Vec<Bytes>,
+}
+
+#[rpc]
+pub trait CertificateProofApi<BlockHash, BlockNumber, CertificateId> {
+    #[rpc(name = "rootOfTrust_proveCertificate")]
+    fn prove_certificate(
+        &self,
+        root: CertificateId,
+        child: CertificateId,
+        at: Option<BlockHash>,
+    ) -> Result<CertificateProof<BlockHash, BlockNumber, CertificateId>>;
+}
+
+pub struct CertificateProofs<C, B, BE, AccountId> {
+    client: Arc<C>,
+    _marker: std::marker::PhantomData<(B, BE, AccountId)>,
+}
+
+impl<C, B, BE, AccountId> CertificateProofs<C, B, BE, AccountId> {
+    pub fn new(client: Arc<C>) -> Self {
+        Self {
+            client,
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<C, Block, BE, AccountId, CertificateId>
+    CertificateProofApi<<Block as BlockT>::Hash, NumberFor<Block>, CertificateId>
+    for CertificateProofs<C, Block, BE, AccountId>
+where
+    Block: BlockT,
+    BE: Backend<Block> + Send + Sync + 'static,
+    C: Send + Sync + 'static,
+    C: HeaderBackend<Block> + StorageProvider<Block, BE> + ProofProvider<Block>,
+    AccountId: Decode + PartialEq + Send + Sync + 'static,
+    CertificateId: Codec + Clone + PartialEq + Send + Sync + 'static,
+{
+    fn prove_certificate(
+        &self,
+        root: CertificateId,
+        child: CertificateId,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> Result<CertificateProof<<Block as BlockT>::Hash, NumberFor<Block>, CertificateId>> {
+        // If the block hash is not supplied assume the best block.
+        let block_hash = at.unwrap_or_else(|| self.client.info().best_hash);
+        let at = BlockId::hash(block_hash);
+        let header = self
+            .client
+            .header(at)
+            .map_err(internal_error)?
+            .ok_or_else(|| internal_error("Unknown block"))?;
+
+        // Run the same checks as the light verifier so that the proof covers exactly
+        // the entries it will need
+        let storage = RecordingReader::<_, _, BE>::new(&*self.client, at);
+        let status = Verifier::<_, AccountId, _, _>::new(&storage, *header.number())
+            .child_certificate_status(&root, &child)
+            .map_err(internal_error)?;
+        let entries = storage.entries.into_inner();
+
+        let proof = self
+            .client
+            .read_proof(&at, &mut entries.iter().map(|(key, _)| &key[..]))
+            .map_err(internal_error)?;
+
+        Ok(CertificateProof {
+            root,
+            child,
+            block_hash,
+            block_number: *header.number(),
+            state_root: *header.state_root(),
+            status,
+            entries: entries
+                .into_iter()
+                .map(|(key, value)| (Bytes(key), value.map(Bytes)))
+                .collect(),
+            proof: proof.iter_nodes().map(Bytes).collect(),
+        })
+    }
+}
@@ -0,0 +1,69 @@
+//! Client storage access shared by the RPCs returning storage proofs.
+
+use pki_light_verifier::{Error as VerifierError, StorageReader};
+use sc_client_api::{backend::Backend, StorageProvider};
+use sp_core::storage::StorageKey;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+use std::cell::RefCell;
+
+/// Client storage remembering every entry read through it
+pub(crate) struct RecordingReader<'a, C, Block: BlockT, BE> {
+    client: &'a C,
+    at: BlockId<Block>,
+    /// Keys read so far, along with their value
+    pub entries: RefCell<Vec<(Vec<u8>, Option<Vec<u8>>)>>,
+    _marker: std::marker::PhantomData<BE>,
+}
+
+impl<'a, C, Block: BlockT, BE> RecordingReader<'a, C, Block, BE> {
+    pub fn new(client: &'a C, at: BlockId<Block>) -> Self {
+        RecordingReader {
+            client,
+            at,
+            entries: RefCell::new(Vec::new()),
+            _marker: Default::default(),
+        }
+    }
+}
+
+impl<'a, C, Block, BE> StorageReader for RecordingReader<'a, C, Block, BE>
+where
+    Block: BlockT,
+    BE: Backend<Block>,
+    C: StorageProvider<Block, BE>,
+{
+    fn read(&self, key: &[u8]) -> std::result::Result<Option<Vec<u8>>, VerifierError> {
+        let value = self
+            .client
+            .storage(&self.at, &StorageKey(key.to_vec()))
+            // The verifier has no better way to report a failing backend
+            .map_err(|_| VerifierError::IncompleteProof)?
+            .map(|data| data.0);
+        self.entries
+            .borrow_mut()
+            .push((key.to_vec(), value.clone()));
+
+        Ok(value)
+    }
+
+    fn read_prefix(
+        &self,
+        prefix: &[u8],
+    ) -> std::result::Result<Vec<(Vec<u8>, Vec<u8>)>, VerifierError> {
+        // Proving the prefix itself covers the path to its entries, even if there is none
+        self.read(prefix)?;
+
+        let mut entries = Vec::new();
+        for key in self
+            .client
+            .storage_keys(&self.at, &StorageKey(prefix.to_vec()))
+            .map_err(|_| VerifierError::IncompleteProof)?
+        {
+            if let Some(value) = self.read(&key.0)? {
+                entries.push((key.0, value));
+            }
+        }
+
+        Ok(entries)
+    }
+}
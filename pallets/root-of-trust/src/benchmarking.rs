@@ -0,0 +1,293 @@
+//! Benchmarks of the root of trust module extrinsics and of the slot maintenance done
+//! in `on_initialize`, their results are used to implement `WeightInfo` in the runtime. Certificates are generated as sr25519
+//! `CertificateKey`s, the benchmarks thus expect `CertificateId` and
+//! `CertificateSignature` to be encoded like `CertificateKey` and
+//! `CertificateSignature`, as they are in the runtime.
+
+use super::*;
+use frame_benchmarking::benchmarks;
+use frame_system::RawOrigin;
+use pki_certificate::{CertificateKey, CertificateSignature};
+use sp_core::sr25519;
+use sp_io::hashing::blake2_256;
+use sp_runtime::{
+    traits::{Bounded, OnInitialize},
+    KeyTypeId,
+};
+
+const MAX_MEMBERS: u32 = 1_000;
+const MAX_SLOTS: u32 = 100;
+const MAX_INTERMEDIATES: u32 = 100;
+/// Keys of the booked slots and intermediates, they need to be held to sign the key
+/// possession proofs
+const KEY_TYPE: KeyTypeId = KeyTypeId(*b"pkib");
+
+fn account<T: Trait>(name: &'static str, index: u32) -> T::AccountId {
+    let entropy = (name, index).using_encoded(blake2_256);
+    T::AccountId::decode(&mut &entropy[..]).unwrap_or_default()
+}
+
+/// An account with enough funds to book and renew slots
+fn funded<T: Trait>(name: &'static str, index: u32) -> T::AccountId {
+    let who = account::<T>(name, index);
+    T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 2.into());
+    who
+}
+
+/// Make `owners` and enough other accounts to reach `m` members, `owners` are put last
+/// so they are the slowest to find. Storage migrations are marked as done since slots
+/// can not be booked or updated while they run, nor be maintained in `on_initialize`.
+fn members<T: Trait>(m: u32, owners: &[T::AccountId]) {
+    CertificateIdsMigrated::put(true);
+    RevocationsMigrated::put(true);
+    SlotsIndexed::put(true);
+
+    let others = m.saturating_sub(owners.len() as u32);
+    let mut members = (0..others)
+        .map(|index| account::<T>("member", index))
+        .collect::<Vec<_>>();
+    members.extend_from_slice(owners);
+    <Members<T>>::put(members);
+}
+
+/// A certificate id we do not hold the key of, enough for children
+fn certificate_id<T: Trait>(
+    name: &'static str,
+    index: u32,
+) -> Result<T::CertificateId, &'static str> {
+    let key = sr25519::Public::from_raw((name, index).using_encoded(blake2_256));
+    T::CertificateId::decode(&mut &CertificateKey::from(key).encode()[..])
+        .map_err(|_| "T::CertificateId is not encoded like CertificateKey")
+}
+
+/// Generate a certificate key in the keystore and sign the key possession proof of
+/// `owner` with it, for a slot or an intermediate
+fn slot_key<T: Trait>(
+    owner: &T::AccountId,
+) -> Result<(T::CertificateId, T::CertificateSignature), &'static str> {
+    let key = sp_io::crypto::sr25519_generate(KEY_TYPE, None);
+    let certificate = T::CertificateId::decode(&mut &CertificateKey::from(key).encode()[..])
+        .map_err(|_| "T::CertificateId is not encoded like CertificateKey")?;
+
+    let payload = Module::<T>::key_possession_payload(owner, &certificate);
+    let signature = sp_io::crypto::sr25519_sign(KEY_TYPE, &key, &payload)
+        .ok_or("the key is not in the keystore")?;
+    let proof =
+        T::CertificateSignature::decode(&mut &CertificateSignature::from(signature).encode()[..])
+            .map_err(|_| "T::CertificateSignature is not encoded like CertificateSignature")?;
+
+    Ok((certificate, proof))
+}
+
+/// Book a slot for `owner`
+fn book<T: Trait>(owner: &T::AccountId) -> Result<T::CertificateId, &'static str> {
+    let (certificate, proof) = slot_key::<T>(owner)?;
+    Module::<T>::book_slot(
+        RawOrigin::Signed(owner.clone()).into(),
+        certificate.clone(),
+        T::MinSlotValidity::get(),
+        proof,
+    )?;
+    Ok(certificate)
+}
+
+/// Book a slot for `owner` and chain `depth` intermediates below it, returns the last
+/// one. Checking the validity of the last intermediate walks the whole chain.
+fn chain<T: Trait>(owner: &T::AccountId, depth: u32) -> Result<T::CertificateId, &'static str> {
+    let mut parent = book::<T>(owner)?;
+    let expires = <system::Module<T>>::block_number() + T::MinSlotValidity::get();
+
+    for index in 0..depth {
+        let (intermediate, proof) = slot_key::<T>(owner)?;
+        Module::<T>::add_intermediate(
+            RawOrigin::Signed(owner.clone()).into(),
+            parent,
+            intermediate.clone(),
+            T::MaxPathLength::get() - index - 1,
+            expires,
+            proof,
+        )?;
+        parent = intermediate;
+    }
+
+    Ok(parent)
+}
+
+benchmarks! {
+    _ {
+        let m in 1 .. MAX_MEMBERS => ();
+        let c in 1 .. T::MaxRevocationsPerCall::get() => ();
+        let s in 1 .. MAX_SLOTS => ();
+        let i in 0 .. MAX_INTERMEDIATES => ();
+    }
+
+    book_slot {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let (certificate, proof) = slot_key::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller), certificate, T::MinSlotValidity::get(), proof)
+
+    renew_slot {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let slot = book::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller), slot, T::MinSlotValidity::get())
+
+    revoke_slot {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let slot = book::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller), slot, RevocationReason::Unspecified)
+
+    // Revoking from the deepest intermediate walks the longest chain
+    revoke_child {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let issuer = chain::<T>(&caller, T::MaxPathLength::get())?;
+        let child = certificate_id::<T>("child", 0)?;
+    }: _(RawOrigin::Signed(caller), issuer, child, RevocationReason::Unspecified)
+
+    revoke_children {
+        let m in ...;
+        let c in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let issuer = chain::<T>(&caller, T::MaxPathLength::get())?;
+        let children = (0..c)
+            .map(|index| certificate_id::<T>("child", index))
+            .collect::<Result<Vec<_>, _>>()?;
+    }: _(RawOrigin::Signed(caller), issuer, children, RevocationReason::Unspecified)
+
+    suspend_child {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let issuer = chain::<T>(&caller, T::MaxPathLength::get())?;
+        let child = certificate_id::<T>("child", 0)?;
+    }: _(RawOrigin::Signed(caller), issuer, child)
+
+    reinstate_child {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let issuer = chain::<T>(&caller, T::MaxPathLength::get())?;
+        let child = certificate_id::<T>("child", 0)?;
+        Module::<T>::suspend_child(RawOrigin::Signed(caller.clone()).into(), issuer.clone(), child.clone())?;
+    }: _(RawOrigin::Signed(caller), issuer, child)
+
+    revoke_children_issued_before {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let issuer = chain::<T>(&caller, T::MaxPathLength::get())?;
+    }: _(RawOrigin::Signed(caller), issuer, <system::Module<T>>::block_number())
+
+    transfer_slot {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        let recipient = funded::<T>("recipient", 0);
+        members::<T>(m, &[caller.clone(), recipient.clone()]);
+        let slot = book::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller), slot, recipient)
+
+    // The transferred slot is the last one of the `s` slots of its previous owner
+    accept_slot_transfer {
+        let m in ...;
+        let s in ...;
+        let owner = funded::<T>("owner", 0);
+        let caller = funded::<T>("recipient", 0);
+        members::<T>(m, &[owner.clone(), caller.clone()]);
+        let others = (1..s)
+            .map(|index| certificate_id::<T>("slot", index))
+            .collect::<Result<Vec<_>, _>>()?;
+        <SlotsOf<T>>::insert(&owner, others);
+        let slot = book::<T>(&owner)?;
+        Module::<T>::transfer_slot(RawOrigin::Signed(owner).into(), slot.clone(), caller.clone())?;
+    }: _(RawOrigin::Signed(caller), slot)
+
+    rotate_root {
+        let m in ...;
+        let i in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let old = book::<T>(&caller)?;
+        let expires = <system::Module<T>>::block_number() + T::MinSlotValidity::get();
+        for _ in 0..i {
+            let (intermediate, proof) = slot_key::<T>(&caller)?;
+            Module::<T>::add_intermediate(
+                RawOrigin::Signed(caller.clone()).into(),
+                old.clone(),
+                intermediate,
+                0,
+                expires,
+                proof,
+            )?;
+        }
+        let (new, proof) = slot_key::<T>(&caller)?;
+    }: _(RawOrigin::Signed(caller), old, new, RevocationReason::KeyCompromise, proof)
+
+    // The new intermediate is added at the end of the longest chain
+    add_intermediate {
+        let m in ...;
+        let caller = funded::<T>("owner", 0);
+        members::<T>(m, &[caller.clone()]);
+        let parent = chain::<T>(&caller, T::MaxPathLength::get().saturating_sub(1))?;
+        let (intermediate, proof) = slot_key::<T>(&caller)?;
+        let expires = <system::Module<T>>::block_number() + T::MinSlotValidity::get();
+    }: _(RawOrigin::Signed(caller), parent, intermediate, 0, expires, proof)
+
+    // Every slot booked for `MinSlotValidity` expires
+    expire_slots {
+        let s in ...;
+        let owner = funded::<T>("owner", 0);
+        members::<T>(1, &[owner.clone()]);
+        for _ in 0..s {
+            book::<T>(&owner)?;
+        }
+        let block = <system::Module<T>>::block_number() + T::MinSlotValidity::get();
+    }: {
+        <Module<T> as OnInitialize<T::BlockNumber>>::on_initialize(block);
+    }
+
+    // The pruned slot was revoked with `i` intermediates registered under it
+    prune_slot {
+        let i in ...;
+        let owner = funded::<T>("owner", 0);
+        members::<T>(1, &[owner.clone()]);
+        let slot = book::<T>(&owner)?;
+        let expires = <system::Module<T>>::block_number() + T::MinSlotValidity::get();
+        for _ in 0..i {
+            let (intermediate, proof) = slot_key::<T>(&owner)?;
+            Module::<T>::add_intermediate(
+                RawOrigin::Signed(owner.clone()).into(),
+                slot.clone(),
+                intermediate,
+                0,
+                expires,
+                proof,
+            )?;
+        }
+        Module::<T>::revoke_slot(RawOrigin::Signed(owner).into(), slot, RevocationReason::Unspecified)?;
+        let block = <system::Module<T>>::block_number() + T::SlotGracePeriod::get();
+    }: {
+        <Module<T> as OnInitialize<T::BlockNumber>>::on_initialize(block);
+    }
+
+    // Slots booked before `SlotsOf` was introduced are indexed
+    index_slots {
+        let s in ...;
+        let owner = funded::<T>("owner", 0);
+        members::<T>(1, &[owner.clone()]);
+        for _ in 0..s {
+            book::<T>(&owner)?;
+        }
+        <SlotsOf<T>>::remove(&owner);
+        SlotsIndexed::put(false);
+    }: {
+        migration::index_slots_by_owner::<T>(s);
+    }
+}
@@ -4,35 +4,50 @@
 //! can be used to let entities represented by their `AccountId` manage certificates
 //! and off-chain certificates in Public Key Infrastructure fashion (SSL / TLS like).
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod migration;
 #[cfg(test)]
 mod tests;
+mod weights;
 
 use codec::{Decode, Encode};
 use frame_support::{
     decl_error, decl_event, decl_module, decl_storage,
     dispatch::DispatchResult,
     ensure,
+    storage::StoragePrefixedMap,
     traits::{ChangeMembers, Currency, ExistenceRequirement, Get, OnUnbalanced, WithdrawReasons},
+    weights::{DispatchClass, FunctionOf, Weight},
     Parameter,
 };
 use frame_system::{self as system, ensure_signed};
-use sp_runtime::traits::{MaybeDisplay, MaybeSerializeDeserialize, Member};
+use pki_certificate::{
+    Certificate, CertificateStatus, Revocation, RevocationReason, VerificationResult,
+};
+pub use pki_certificate::{IntermediateCertificate, RootCertificate};
+use sp_runtime::{
+    traits::{
+        Convert, IdentifyAccount, MaybeDisplay, MaybeSerializeDeserialize, Member, One, Saturating,
+        Verify, Zero,
+    },
+    SaturatedConversion,
+};
 use sp_std::{fmt::Debug, prelude::Vec};
+pub use weights::WeightInfo;
 
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
 type NegativeImbalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::NegativeImbalance;
+/// Weight charged for every certificate or revocation migrated
+const REVOCATION_MIGRATION_WEIGHT: Weight = 50_000;
 
-#[derive(Encode, Decode, Default, Clone, PartialEq)]
-pub struct RootCertificate<AccountId, CertificateId, BlockNumber> {
-    owner: AccountId,
-    key: CertificateId,
-    created: BlockNumber,
-    renewed: BlockNumber,
-    revoked: bool,
-    validity: BlockNumber,
-    child_revocations: Vec<CertificateId>,
-}
+pub type CertificateOf<T> = Certificate<
+    <T as Trait>::CertificateId,
+    <T as system::Trait>::BlockNumber,
+    <T as Trait>::CertificateSignature,
+>;
+pub type RevocationOf<T> = Revocation<<T as system::Trait>::BlockNumber>;
 
 /// The module's configuration trait.
 pub trait Trait: system::Trait {
@@ -41,7 +56,8 @@ pub trait Trait: system::Trait {
     /// The currency used to represent the voting power
     type Currency: Currency<Self::AccountId>;
 
-    /// How a certificate public key is represented, typically `AccountId`
+    /// How a certificate public key is represented, typically `CertificateKey` which
+    /// keeps the raw public key and its algorithm
     type CertificateId: Member
         + Parameter
         + MaybeSerializeDeserialize
@@ -49,12 +65,44 @@ pub trait Trait: system::Trait {
         + MaybeDisplay
         + Ord
         + Default;
+    /// Signature used to sign off-chain certificates, typically `CertificateSignature`
+    /// which dispatches the verification on the algorithm of the signer
+    type CertificateSignature: Parameter + Verify<Signer = Self::CertificateSigner>;
+    /// Public key matching `CertificateSignature`, it needs to resolve to a `CertificateId`
+    type CertificateSigner: IdentifyAccount<AccountId = Self::CertificateId>;
+    /// How certificates were identified before `CertificateId`, typically `AccountId`
+    type LegacyCertificateId: Parameter;
+    /// Translate legacy certificate ids to `CertificateId`
+    type LegacyCertificateIds: Convert<Self::LegacyCertificateId, Self::CertificateId>;
     /// How much a new root certificate costs
     type SlotBookingCost: Get<BalanceOf<Self>>;
     /// How much renewing a root certificate costs
     type SlotRenewingCost: Get<BalanceOf<Self>>;
-    /// How long a certificate is considered valid
+    /// Reference validity period, booking or renewing a slot for `SlotValidity`
+    /// blocks costs exactly `SlotBookingCost` or `SlotRenewingCost`
     type SlotValidity: Get<Self::BlockNumber>;
+    /// Shortest validity period that can be requested for a slot
+    type MinSlotValidity: Get<Self::BlockNumber>;
+    /// Longest validity period that can be requested for a slot
+    type MaxSlotValidity: Get<Self::BlockNumber>;
+    /// How many intermediate certificates can be chained below a root certificate
+    type MaxPathLength: Get<u32>;
+    /// How long an expired or revoked slot is kept in storage before being pruned
+    type SlotGracePeriod: Get<Self::BlockNumber>;
+    /// Maximum number of slots pruned per block, remaining slots are pruned in the
+    /// following blocks
+    type MaxPrunedPerBlock: Get<u32>;
+    /// Maximum number of slot expiries processed per block, remaining expiries are
+    /// processed in the following blocks
+    type MaxExpiredPerBlock: Get<u32>;
+    /// Maximum number of children that can be revoked at once with `revoke_children`
+    type MaxRevocationsPerCall: Get<u32>;
+    /// Maximum number of storage entries rewritten per block by storage migrations,
+    /// remaining entries are migrated in the following blocks
+    type MaxMigratedPerBlock: Get<u32>;
+    /// Weights of the extrinsics and of the slot maintenance done in `on_initialize`,
+    /// typically generated from `benchmarking.rs`
+    type WeightInfo: WeightInfo;
     /// The module receiving funds paid by depositors, typically a company
     /// reserve
     type FundsCollector: OnUnbalanced<NegativeImbalanceOf<Self>>;
@@ -64,16 +112,47 @@ decl_event!(
     pub enum Event<T>
     where
         AccountId = <T as system::Trait>::AccountId,
+        BlockNumber = <T as system::Trait>::BlockNumber,
         CertificateId = <T as Trait>::CertificateId,
+        Revocation = RevocationOf<T>,
     {
         /// A new slot has been booked
         SlotTaken(AccountId, CertificateId),
         /// An exisitng slot has been renewed (its validity period was extended)
         SlotRenewed(CertificateId),
         /// A slot has been revoked by its owner
-        SlotRevoked(CertificateId),
-        /// A child certificate was revoked
-        ChildSlotRevoked(CertificateId, CertificateId),
+        SlotRevoked(CertificateId, Revocation),
+        /// A child certificate was revoked, fields are the issuer and the child
+        ChildSlotRevoked(CertificateId, CertificateId, Revocation),
+        /// An intermediate certificate was registered, fields are the root, the parent
+        /// and the intermediate itself
+        IntermediateAdded(CertificateId, CertificateId, CertificateId),
+        /// A slot reached the end of its validity period without being renewed
+        SlotExpired(CertificateId),
+        /// An expired or revoked slot and its intermediates were removed from storage
+        SlotPruned(CertificateId),
+        /// The owner of a slot offered it to another member, fields are the slot, the
+        /// current owner and the new owner
+        SlotTransferInitiated(CertificateId, AccountId, AccountId),
+        /// A slot transfer was accepted by the new owner
+        SlotTransferred(CertificateId, AccountId),
+        /// A root certificate key was rotated, fields are the old and new keys
+        SlotRotated(CertificateId, CertificateId),
+        /// A batch of child certificates was revoked, only the children that were not
+        /// already revoked are listed
+        ChildrenRevoked(CertificateId, Vec<CertificateId>, Revocation),
+        /// Every child certificate issued before the given block was revoked
+        ChildrenRevokedBefore(CertificateId, BlockNumber),
+        /// A child certificate was suspended, fields are the issuer and the child
+        ChildSuspended(CertificateId, CertificateId),
+        /// A suspended child certificate was reinstated, fields are the issuer and the child
+        ChildReinstated(CertificateId, CertificateId),
+        /// The owner lost its membership, its valid slots are suspended until it is
+        /// admitted again
+        SlotsSuspended(AccountId, Vec<CertificateId>),
+        /// The owner was admitted again, its slots that did not expire in the meantime
+        /// are valid again
+        SlotsRestored(AccountId, Vec<CertificateId>),
     }
 );
 
@@ -89,6 +168,36 @@ decl_error! {
         NoLongerValid,
         /// `origin` is not the slot owner
         NotTheOwner,
+        /// The issuer is neither a root nor an intermediate certificate
+        IssuerNotFound,
+        /// The certificate chain would be longer than allowed
+        PathTooLong,
+        /// The expiry is already in the past
+        AlreadyExpired,
+        /// The requested validity is out of the `MinSlotValidity` and `MaxSlotValidity`
+        /// bounds
+        ValidityOutOfBounds,
+        /// No transfer was initiated for this slot
+        NoPendingTransfer,
+        /// `origin` is not the account the slot is being transferred to
+        NotTheRecipient,
+        /// More children than `MaxRevocationsPerCall` were submitted
+        TooManyRevocations,
+        /// Children can not be revoked ahead of time
+        CutoffInTheFuture,
+        /// The child certificate is already suspended
+        AlreadySuspended,
+        /// The child certificate is not suspended
+        NotSuspended,
+        /// The proof was not signed by the certificate key over the expected payload
+        BadKeyPossessionProof,
+        /// The certificate key was revoked, it can not be used again
+        KeyRevoked,
+        /// Revocations are permanent, certificates are put on hold with `suspend_child`
+        HoldIsNotARevocation,
+        /// Certificates and their revocations are being migrated, slots can not be
+        /// modified until the migration is over
+        MigrationInProgress,
     }
 }
 
@@ -96,6 +205,51 @@ decl_storage! {
     trait Store for Module<T: Trait> as RootOfTrustModule {
         Members get(members): Vec<T::AccountId>;
         Slots get(slots): map hasher(blake2_256) T::CertificateId => RootCertificate<T::AccountId, T::CertificateId, T::BlockNumber>;
+        Intermediates get(intermediates): map hasher(blake2_256) T::CertificateId => IntermediateCertificate<T::CertificateId, T::BlockNumber>;
+        /// Intermediates registered under a given root certificate
+        IntermediatesOf get(intermediates_of): map hasher(blake2_256) T::CertificateId => Vec<T::CertificateId>;
+        /// Slots reaching their expiry at a given block, entries are not removed when
+        /// a slot is renewed so they need to be checked against the slot itself
+        ExpiringSlots get(expiring_slots): map hasher(blake2_256) T::BlockNumber => Vec<T::CertificateId>;
+        /// Slots to be pruned at a given block
+        PruningQueue get(pruning_queue): map hasher(blake2_256) T::BlockNumber => Vec<T::CertificateId>;
+        /// First block whose `ExpiringSlots` entries were not all processed yet
+        ExpiryCursor get(expiry_cursor): Option<T::BlockNumber>;
+        /// Slots offered by their owner to another member, waiting for them to accept
+        PendingTransfers get(pending_transfers): map hasher(blake2_256) T::CertificateId => Option<T::AccountId>;
+        /// Rotated root certificates, mapped to their replacement and the block at which
+        /// the rotation happened
+        SupersededBy get(superseded_by): map hasher(blake2_256) T::CertificateId => Option<(T::CertificateId, T::BlockNumber)>;
+        /// Reverse of `SupersededBy`, rotated slots are pruned along with their replacement
+        Predecessor get(predecessor): map hasher(blake2_256) T::CertificateId => Option<T::CertificateId>;
+        /// Children revoked by a root or intermediate certificate, children ids are kept in
+        /// clear in the storage keys so that revocation lists can be built off-chain
+        ChildRevocations get(child_revocations): double_map hasher(blake2_256) T::CertificateId, hasher(blake2_128_concat) T::CertificateId => Option<RevocationOf<T>>;
+        /// Children temporarily suspended by a root or intermediate certificate, with the
+        /// block at which they were suspended
+        ChildSuspensions get(child_suspensions): double_map hasher(blake2_256) T::CertificateId, hasher(blake2_128_concat) T::CertificateId => Option<T::BlockNumber>;
+        /// When and why slots were revoked, including slots rotated because their key was
+        /// compromised. Entries are kept when revoked slots and intermediates are pruned
+        /// so that their keys can not be used again.
+        SlotRevocations get(slot_revocations): map hasher(blake2_256) T::CertificateId => Option<RevocationOf<T>>;
+        /// Children issued before the given block are considered revoked
+        RevokedBefore get(revoked_before): map hasher(blake2_256) T::CertificateId => Option<T::BlockNumber>;
+        /// Whether the certificates stored with their legacy id were stored again under
+        /// their `CertificateId`
+        CertificateIdsMigrated get(certificate_ids_migrated): bool;
+        /// Whether revocations were moved from the certificates to `ChildRevocations`
+        RevocationsMigrated get(revocations_migrated): bool;
+        /// Raw storage key of the last entry migrated by the storage migration in
+        /// progress, if any
+        MigrationCursor get(migration_cursor): Option<Vec<u8>>;
+        /// Bumped every time a key is registered as a root or intermediate certificate so
+        /// that key possession proofs can not be replayed, entries are kept when slots are
+        /// pruned
+        BookingNonces get(booking_nonce): map hasher(blake2_256) T::CertificateId => u64;
+        /// Slots owned by an account, including the ones it rotated
+        SlotsOf get(slots_of): map hasher(blake2_256) T::AccountId => Vec<T::CertificateId>;
+        /// Whether slots booked before `SlotsOf` was introduced were indexed
+        SlotsIndexed get(slots_indexed): bool;
     }
 }
 
@@ -104,53 +258,86 @@ decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
-        /// Book a certificate slot
-        fn book_slot(origin, certificate_id: T::CertificateId) -> DispatchResult {
+        /// Book a certificate slot valid for `validity` blocks, the fee is proportional
+        /// to the requested validity. `proof` is a signature by the certificate key of
+        /// `key_possession_payload`, showing that the sender holds the private key like
+        /// a certificate signing request would.
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &T::BlockNumber, &T::CertificateSignature)| T::WeightInfo::book_slot(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn book_slot(origin, certificate_id: T::CertificateId, validity: T::BlockNumber, proof: T::CertificateSignature) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(Self::is_member(&sender), Error::<T>::NotAMember);
-            ensure!(!<Slots<T>>::contains_key(&certificate_id), Error::<T>::SlotTaken);
+            Self::ensure_key_available(&certificate_id)?;
+            ensure!(Self::is_validity_allowed(validity), Error::<T>::ValidityOutOfBounds);
+            let payload = Self::key_possession_payload(&sender, &certificate_id);
+            ensure!(proof.verify(&payload[..], &certificate_id), Error::<T>::BadKeyPossessionProof);
 
-            match T::Currency::withdraw(&sender, T::SlotBookingCost::get(), WithdrawReasons::all(), ExistenceRequirement::AllowDeath) {
+            let fee = Self::fee_for(T::SlotBookingCost::get(), validity);
+            match T::Currency::withdraw(&sender, fee, WithdrawReasons::all(), ExistenceRequirement::AllowDeath) {
                 Ok(imbalance) => T::FundsCollector::on_unbalanced(imbalance),
                 Err(_) => Err(Error::<T>::NotEnoughFunds)?,
             };
 
             let now = <system::Module<T>>::block_number();
+            <BookingNonces<T>>::mutate(&certificate_id, |nonce| *nonce += 1);
+            <SlotsOf<T>>::mutate(&sender, |slots| slots.push(certificate_id.clone()));
+            <ExpiringSlots<T>>::mutate(now + validity, |slots| slots.push(certificate_id.clone()));
             <Slots<T>>::insert(&certificate_id, RootCertificate {
                 owner: sender.clone(),
                 key: certificate_id.clone(),
                 created: now,
                 renewed: now,
                 revoked: false,
-                validity: T::SlotValidity::get(),
-                child_revocations: Vec::new(),
+                validity: validity,
             });
 
             Self::deposit_event(RawEvent::SlotTaken(sender, certificate_id));
             Ok(())
         }
 
-        fn renew_slot(origin, certificate: T::CertificateId) -> DispatchResult {
+        /// Renew a slot for `validity` blocks starting from now, the fee is proportional
+        /// to the requested validity
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &T::BlockNumber)| T::WeightInfo::renew_slot(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn renew_slot(origin, certificate: T::CertificateId, validity: T::BlockNumber) -> DispatchResult {
             let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
 
             let mut slot = <Slots<T>>::get(&certificate);
             ensure!(Self::is_slot_valid(&slot), Error::<T>::NoLongerValid);
             ensure!(slot.owner == sender, Error::<T>::NotTheOwner);
+            ensure!(Self::is_validity_allowed(validity), Error::<T>::ValidityOutOfBounds);
 
-            match T::Currency::withdraw(&sender, T::SlotRenewingCost::get(), WithdrawReasons::all(), ExistenceRequirement::AllowDeath) {
+            let fee = Self::fee_for(T::SlotRenewingCost::get(), validity);
+            match T::Currency::withdraw(&sender, fee, WithdrawReasons::all(), ExistenceRequirement::AllowDeath) {
                 Ok(imbalance) => T::FundsCollector::on_unbalanced(imbalance),
                 Err(_) => Err(Error::<T>::NotEnoughFunds)?,
             };
 
             slot.renewed = <system::Module<T>>::block_number();
+            slot.validity = validity;
+            <ExpiringSlots<T>>::mutate(slot.renewed + slot.validity, |slots| slots.push(certificate.clone()));
             <Slots<T>>::insert(&certificate, slot);
 
             Self::deposit_event(RawEvent::SlotRenewed(certificate));
             Ok(())
         }
 
-        fn revoke_slot(origin, certificate: T::CertificateId) -> DispatchResult {
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &RevocationReason)| T::WeightInfo::revoke_slot(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn revoke_slot(origin, certificate: T::CertificateId, reason: RevocationReason) -> DispatchResult {
             let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+            Self::ensure_permanent(reason)?;
 
             let mut slot = <Slots<T>>::get(&certificate);
             ensure!(Self::is_slot_valid(&slot), Error::<T>::NoLongerValid);
@@ -159,24 +346,323 @@ decl_module! {
             slot.revoked = true;
             <Slots<T>>::insert(&certificate, slot);
 
-            Self::deposit_event(RawEvent::SlotRevoked(certificate));
+            let now = <system::Module<T>>::block_number();
+            let revocation = Revocation { revoked_at: now, reason };
+            <SlotRevocations<T>>::insert(&certificate, revocation.clone());
+            <PruningQueue<T>>::mutate(now + T::SlotGracePeriod::get(), |slots| slots.push(certificate.clone()));
+
+            Self::deposit_event(RawEvent::SlotRevoked(certificate, revocation));
+            Ok(())
+        }
+
+        /// Revoke a child certificate, `issuer` may be a root or an intermediate
+        /// certificate. Revocations are permanent, use `suspend_child` to put a child
+        /// on hold.
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &T::CertificateId, &RevocationReason)| T::WeightInfo::revoke_child(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn revoke_child(origin, issuer: T::CertificateId, child: T::CertificateId, reason: RevocationReason) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_permanent(reason)?;
+            Self::ensure_can_revoke(&sender, &issuer)?;
+            ensure!(!<ChildRevocations<T>>::contains_key(&issuer, &child), Error::<T>::NoLongerValid);
+
+            let revocation = Revocation {
+                revoked_at: <system::Module<T>>::block_number(),
+                reason,
+            };
+            <ChildRevocations<T>>::insert(&issuer, &child, revocation.clone());
+
+            Self::deposit_event(RawEvent::ChildSlotRevoked(issuer, child, revocation));
             Ok(())
         }
 
-        fn revoke_child(origin, root: T::CertificateId, child: T::CertificateId) -> DispatchResult {
+        /// Revoke up to `MaxRevocationsPerCall` children of `issuer` at once, children
+        /// that were already revoked are skipped
+        #[weight = FunctionOf(
+            |args: (&T::CertificateId, &Vec<T::CertificateId>, &RevocationReason)| {
+                T::WeightInfo::revoke_children(Module::<T>::member_count(), args.1.len() as u32)
+            },
+            DispatchClass::Normal,
+            true
+        )]
+        fn revoke_children(origin, issuer: T::CertificateId, children: Vec<T::CertificateId>, reason: RevocationReason) -> DispatchResult {
             let sender = ensure_signed(origin)?;
+            ensure!(children.len() <= T::MaxRevocationsPerCall::get() as usize, Error::<T>::TooManyRevocations);
+            Self::ensure_permanent(reason)?;
+            Self::ensure_can_revoke(&sender, &issuer)?;
+
+            let mut children = children;
+            children.sort();
+            children.dedup();
+            children.retain(|child| !<ChildRevocations<T>>::contains_key(&issuer, child));
+
+            let revocation = Revocation {
+                revoked_at: <system::Module<T>>::block_number(),
+                reason,
+            };
+            for child in children.iter() {
+                <ChildRevocations<T>>::insert(&issuer, child, revocation.clone());
+            }
+
+            Self::deposit_event(RawEvent::ChildrenRevoked(issuer, children, revocation));
+            Ok(())
+        }
+
+        /// Temporarily suspend a child certificate, for instance while a device is
+        /// quarantined. Unlike revocations, suspensions can be lifted with
+        /// `reinstate_child`.
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &T::CertificateId)| T::WeightInfo::suspend_child(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn suspend_child(origin, issuer: T::CertificateId, child: T::CertificateId) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_can_revoke(&sender, &issuer)?;
+            ensure!(!Self::is_suspended_by(&issuer, &child), Error::<T>::AlreadySuspended);
+
+            <ChildSuspensions<T>>::insert(&issuer, &child, <system::Module<T>>::block_number());
+
+            Self::deposit_event(RawEvent::ChildSuspended(issuer, child));
+            Ok(())
+        }
+
+        /// Lift the suspension of a child certificate, a revoked child stays revoked.
+        /// Suspensions made by the root certificates `issuer` replaced through
+        /// `rotate_root` are lifted too.
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &T::CertificateId)| T::WeightInfo::reinstate_child(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn reinstate_child(origin, issuer: T::CertificateId, child: T::CertificateId) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_can_revoke(&sender, &issuer)?;
+            ensure!(Self::is_suspended_by(&issuer, &child), Error::<T>::NotSuspended);
+
+            for issuer in Self::rotation_history(&issuer) {
+                <ChildSuspensions<T>>::remove(&issuer, &child);
+            }
 
-            let mut slot = <Slots<T>>::get(&root);
+            Self::deposit_event(RawEvent::ChildReinstated(issuer, child));
+            Ok(())
+        }
+
+        /// Revoke every child certificate issued by `issuer` before `block`. Since we only
+        /// know when a certificate was issued from the certificate itself, this is only
+        /// enforced when verifying signed certificates and intermediates.
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &T::BlockNumber)| T::WeightInfo::revoke_children_issued_before(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn revoke_children_issued_before(origin, issuer: T::CertificateId, block: T::BlockNumber) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_can_revoke(&sender, &issuer)?;
+            ensure!(block <= <system::Module<T>>::block_number(), Error::<T>::CutoffInTheFuture);
+
+            let cutoff = Self::revoked_before(&issuer).map_or(block, |previous| previous.max(block));
+            <RevokedBefore<T>>::insert(&issuer, cutoff);
+
+            Self::deposit_event(RawEvent::ChildrenRevokedBefore(issuer, cutoff));
+            Ok(())
+        }
+
+        /// Offer a slot to another member, the transfer is only effective once `new_owner`
+        /// calls `accept_slot_transfer`. Initiating a new transfer replaces the previous one.
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &T::AccountId)| T::WeightInfo::transfer_slot(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn transfer_slot(origin, certificate: T::CertificateId, new_owner: T::AccountId) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+
+            let slot = <Slots<T>>::get(&certificate);
             ensure!(Self::is_slot_valid(&slot), Error::<T>::NoLongerValid);
             ensure!(slot.owner == sender, Error::<T>::NotTheOwner);
-            ensure!(!slot.child_revocations.contains(&child), Error::<T>::NoLongerValid);
+            ensure!(Self::is_member(&new_owner), Error::<T>::NotAMember);
+
+            <PendingTransfers<T>>::insert(&certificate, new_owner.clone());
+
+            Self::deposit_event(RawEvent::SlotTransferInitiated(certificate, sender, new_owner));
+            Ok(())
+        }
 
-            slot.child_revocations.push(child.clone());
-            <Slots<T>>::insert(&root, slot);
+        /// Accept a slot transfer, the slot keeps its validity period, intermediates and
+        /// revocations
+        #[weight = FunctionOf(
+            |args: (&T::CertificateId,)| {
+                let owner = <Slots<T>>::get(args.0).owner;
+                let slots = <SlotsOf<T>>::decode_len(&owner).unwrap_or(0);
+                T::WeightInfo::accept_slot_transfer(Module::<T>::member_count(), slots as u32)
+            },
+            DispatchClass::Normal,
+            true
+        )]
+        fn accept_slot_transfer(origin, certificate: T::CertificateId) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
 
-            Self::deposit_event(RawEvent::ChildSlotRevoked(root, child));
+            let new_owner = <PendingTransfers<T>>::get(&certificate).ok_or(Error::<T>::NoPendingTransfer)?;
+            ensure!(new_owner == sender, Error::<T>::NotTheRecipient);
+            ensure!(Self::is_member(&sender), Error::<T>::NotAMember);
+
+            let mut slot = <Slots<T>>::get(&certificate);
+            ensure!(Self::is_slot_valid(&slot), Error::<T>::NoLongerValid);
+
+            Self::unindex_slot(&slot.owner, &certificate);
+            <SlotsOf<T>>::mutate(&sender, |slots| slots.push(certificate.clone()));
+            slot.owner = sender.clone();
+            <Slots<T>>::insert(&certificate, slot);
+            <PendingTransfers<T>>::remove(&certificate);
+
+            Self::deposit_event(RawEvent::SlotTransferred(certificate, sender));
             Ok(())
         }
+
+        /// Replace the key of a root certificate by `new`. `new` keeps the owner, expiry,
+        /// intermediates and revocations of `old`. Certificates signed by `old` before
+        /// the rotation stay valid and are checked against `new`, any certificate signed
+        /// later is rejected. Since the signer chooses the creation date of a certificate,
+        /// rotating with a `KeyCompromise` or `CACompromise` reason revokes `old`
+        /// instead, along with every certificate it signed. `proof` is a signature of
+        /// `key_possession_payload` by `new`, as when booking a slot.
+        #[weight = FunctionOf(
+            |args: (&T::CertificateId, &T::CertificateId, &RevocationReason, &T::CertificateSignature)| {
+                let intermediates = <IntermediatesOf<T>>::decode_len(args.0).unwrap_or(0);
+                T::WeightInfo::rotate_root(Module::<T>::member_count(), intermediates as u32)
+            },
+            DispatchClass::Normal,
+            true
+        )]
+        fn rotate_root(origin, old: T::CertificateId, new: T::CertificateId, reason: RevocationReason, proof: T::CertificateSignature) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+            Self::ensure_key_available(&new)?;
+
+            let slot = <Slots<T>>::get(&old);
+            ensure!(Self::is_slot_valid(&slot), Error::<T>::NoLongerValid);
+            ensure!(slot.owner == sender, Error::<T>::NotTheOwner);
+            let payload = Self::key_possession_payload(&sender, &new);
+            ensure!(proof.verify(&payload[..], &new), Error::<T>::BadKeyPossessionProof);
+
+            let intermediates = <IntermediatesOf<T>>::take(&old);
+            for intermediate in intermediates.iter() {
+                <Intermediates<T>>::mutate(intermediate, |intermediate| {
+                    intermediate.root = new.clone();
+                    if intermediate.parent == old {
+                        intermediate.parent = new.clone();
+                    }
+                });
+            }
+            <IntermediatesOf<T>>::insert(&new, intermediates);
+
+            let now = <system::Module<T>>::block_number();
+            <BookingNonces<T>>::mutate(&new, |nonce| *nonce += 1);
+            <ExpiringSlots<T>>::mutate(slot.renewed + slot.validity, |slots| slots.push(new.clone()));
+            <SlotsOf<T>>::mutate(&sender, |slots| slots.push(new.clone()));
+            <Slots<T>>::insert(&new, RootCertificate {
+                key: new.clone(),
+                created: now,
+                ..slot
+            });
+            <SupersededBy<T>>::insert(&old, (new.clone(), now));
+            <Predecessor<T>>::insert(&new, old.clone());
+            <PendingTransfers<T>>::remove(&old);
+
+            if reason.is_compromise() {
+                let revocation = Revocation { revoked_at: now, reason };
+                <SlotRevocations<T>>::insert(&old, revocation.clone());
+                Self::deposit_event(RawEvent::SlotRevoked(old.clone(), revocation));
+            }
+
+            Self::deposit_event(RawEvent::SlotRotated(old, new));
+            Ok(())
+        }
+
+        /// Register an intermediate certificate under `parent`, which may be a root or
+        /// another intermediate certificate. `path_length` is the number of intermediates
+        /// that can still be chained below the new one. `proof` is a signature of
+        /// `key_possession_payload` by `intermediate`. To revoke an intermediate
+        /// certificate, use `revoke_child` on its parent.
+        #[weight = FunctionOf(
+            |_: (&T::CertificateId, &T::CertificateId, &u32, &T::BlockNumber, &T::CertificateSignature)| T::WeightInfo::add_intermediate(Module::<T>::member_count()),
+            DispatchClass::Normal,
+            true
+        )]
+        fn add_intermediate(origin, parent: T::CertificateId, intermediate: T::CertificateId, path_length: u32, expires: T::BlockNumber, proof: T::CertificateSignature) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_key_available(&intermediate)?;
+
+            let (root, max_path_length) = if <Intermediates<T>>::contains_key(&parent) {
+                let parent_intermediate = <Intermediates<T>>::get(&parent);
+                (parent_intermediate.root, parent_intermediate.path_length)
+            } else if <Slots<T>>::contains_key(&parent) {
+                (parent.clone(), T::MaxPathLength::get())
+            } else {
+                Err(Error::<T>::IssuerNotFound)?
+            };
+
+            ensure!(Self::is_issuer_valid(&parent), Error::<T>::NoLongerValid);
+            ensure!(<Slots<T>>::get(&root).owner == sender, Error::<T>::NotTheOwner);
+            ensure!(path_length < max_path_length, Error::<T>::PathTooLong);
+
+            let now = <system::Module<T>>::block_number();
+            ensure!(expires > now, Error::<T>::AlreadyExpired);
+            let payload = Self::key_possession_payload(&sender, &intermediate);
+            ensure!(proof.verify(&payload[..], &intermediate), Error::<T>::BadKeyPossessionProof);
+
+            <BookingNonces<T>>::mutate(&intermediate, |nonce| *nonce += 1);
+            <IntermediatesOf<T>>::mutate(&root, |intermediates| intermediates.push(intermediate.clone()));
+            <Intermediates<T>>::insert(&intermediate, IntermediateCertificate {
+                key: intermediate.clone(),
+                parent: parent.clone(),
+                root: root.clone(),
+                path_length: path_length,
+                created: now,
+                expires: expires,
+            });
+
+            Self::deposit_event(RawEvent::IntermediateAdded(root, parent, intermediate));
+            Ok(())
+        }
+
+        /// Notify slot expiries and prune dead slots once their grace period is over.
+        /// Storage migrations rewrite at most `MaxMigratedPerBlock` entries per block,
+        /// slots are neither expired nor pruned until their revocations are migrated.
+        fn on_initialize(block: T::BlockNumber) -> Weight {
+            let budget = T::MaxMigratedPerBlock::get();
+            let mut migrated = 0;
+            if !Self::certificate_ids_migrated() {
+                let (used, done) = migration::migrate_certificate_ids::<T>(budget);
+                migrated = used;
+                CertificateIdsMigrated::put(done);
+            }
+            if Self::certificate_ids_migrated() && !Self::revocations_migrated() {
+                let (used, done) = migration::migrate_child_revocations::<T>(budget.saturating_sub(migrated));
+                migrated += used;
+                RevocationsMigrated::put(done);
+            }
+            let mut weight = REVOCATION_MIGRATION_WEIGHT * migrated as Weight;
+            if !Self::is_migrating() && !Self::slots_indexed() {
+                let (indexed, done) = migration::index_slots_by_owner::<T>(budget.saturating_sub(migrated));
+                weight += T::WeightInfo::index_slots(indexed);
+                SlotsIndexed::put(done);
+            }
+
+            if !Self::is_migrating() {
+                weight += T::WeightInfo::expire_slots(Self::expire_slots(block));
+                weight += Self::prune_slots(block);
+            } else {
+                Self::postpone_slot_maintenance(block);
+            }
+
+            weight
+        }
     }
 }
 
@@ -185,42 +671,574 @@ impl<T: Trait> Module<T> {
         Self::members().contains(who)
     }
 
+    /// Number of members, used to weight the extrinsics checking a membership since
+    /// `is_member` scans all of them
+    fn member_count() -> u32 {
+        <Members<T>>::decode_len().unwrap_or(0) as u32
+    }
+
+    /// Payload to be signed by `certificate_id` for `owner` to book its slot, rotate a
+    /// root to it or register it as an intermediate, the SCALE encoding of the owner,
+    /// the genesis hash and the booking nonce of the certificate
+    pub fn key_possession_payload(
+        owner: &T::AccountId,
+        certificate_id: &T::CertificateId,
+    ) -> Vec<u8> {
+        let genesis_hash = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+
+        (owner, genesis_hash, Self::booking_nonce(certificate_id)).encode()
+    }
+
+    /// Whether certificates or child revocations are being migrated, some of them are
+    /// then still stored with their legacy layout
+    fn is_migrating() -> bool {
+        !Self::certificate_ids_migrated() || !Self::revocations_migrated()
+    }
+
+    /// Make sure that `Slots` and `Intermediates` values are not being migrated, their
+    /// legacy values would otherwise be overwritten along with the revocations they hold
+    fn ensure_migrated() -> DispatchResult {
+        ensure!(!Self::is_migrating(), Error::<T>::MigrationInProgress);
+
+        Ok(())
+    }
+
+    /// Make sure that `certificate_id` can be used for a new root or intermediate
+    /// certificate
+    fn ensure_key_available(certificate_id: &T::CertificateId) -> DispatchResult {
+        // Certificates which were not migrated yet are stored under their legacy id
+        ensure!(
+            Self::certificate_ids_migrated(),
+            Error::<T>::MigrationInProgress
+        );
+        ensure!(
+            !<Slots<T>>::contains_key(certificate_id),
+            Error::<T>::SlotTaken
+        );
+        ensure!(
+            !<Intermediates<T>>::contains_key(certificate_id),
+            Error::<T>::SlotTaken
+        );
+        ensure!(
+            !<SlotRevocations<T>>::contains_key(certificate_id),
+            Error::<T>::KeyRevoked
+        );
+
+        Ok(())
+    }
+
+    /// Make sure that a revocation made for `reason` is meant to be permanent
+    fn ensure_permanent(reason: RevocationReason) -> DispatchResult {
+        ensure!(
+            reason != RevocationReason::CertificateHold,
+            Error::<T>::HoldIsNotARevocation
+        );
+
+        Ok(())
+    }
+
+    /// Make sure that `sender` can revoke children of `issuer`, which may be a root or an
+    /// intermediate certificate
+    fn ensure_can_revoke(sender: &T::AccountId, issuer: &T::CertificateId) -> DispatchResult {
+        if <Intermediates<T>>::contains_key(issuer) {
+            let intermediate = <Intermediates<T>>::get(issuer);
+            ensure!(Self::is_issuer_valid(issuer), Error::<T>::NoLongerValid);
+            ensure!(
+                <Slots<T>>::get(&intermediate.root).owner == *sender,
+                Error::<T>::NotTheOwner
+            );
+        } else {
+            let slot = <Slots<T>>::get(issuer);
+            ensure!(Self::is_slot_valid(&slot), Error::<T>::NoLongerValid);
+            ensure!(slot.owner == *sender, Error::<T>::NotTheOwner);
+        }
+
+        Ok(())
+    }
+
     fn is_slot_valid(
         slot: &RootCertificate<T::AccountId, T::CertificateId, T::BlockNumber>,
     ) -> bool {
+        Self::slot_status(slot).is_valid()
+    }
+
+    fn slot_status(
+        slot: &RootCertificate<T::AccountId, T::CertificateId, T::BlockNumber>,
+    ) -> CertificateStatus {
         let owner_is_member = Self::is_member(&slot.owner);
-        let revoked = slot.revoked;
+        // Slots rotated because their key was compromised are revoked too
+        let revoked = slot.revoked || <SlotRevocations<T>>::contains_key(&slot.key);
         let expired = slot.renewed + slot.validity <= <system::Module<T>>::block_number();
+        let superseded = <SupersededBy<T>>::contains_key(&slot.key);
 
-        owner_is_member && !revoked && !expired
+        if !owner_is_member {
+            CertificateStatus::OwnerNotAMember
+        } else if revoked {
+            CertificateStatus::Revoked
+        } else if superseded {
+            CertificateStatus::Superseded
+        } else if expired {
+            CertificateStatus::Expired
+        } else {
+            CertificateStatus::Valid
+        }
+    }
+
+    /// Status of the slot booked for `cert`, revoked slots stay revoked once pruned
+    pub fn root_certificate_status(cert: &T::CertificateId) -> CertificateStatus {
+        if !<Slots<T>>::contains_key(cert) {
+            return if <SlotRevocations<T>>::contains_key(cert) {
+                CertificateStatus::Revoked
+            } else {
+                CertificateStatus::NotFound
+            };
+        }
+
+        Self::slot_status(&<Slots<T>>::get(cert))
     }
 
     #[allow(dead_code)]
     pub fn is_root_certificate_valid(cert: &T::CertificateId) -> bool {
-        let exists = <Slots<T>>::contains_key(cert);
-        let slot = <Slots<T>>::get(cert);
+        Self::root_certificate_status(cert).is_valid()
+    }
+
+    /// When and why the slot booked for `cert` was revoked, slots revoked before
+    /// revocations were recorded have no revocation entry
+    pub fn root_certificate_revocation(cert: &T::CertificateId) -> Option<RevocationOf<T>> {
+        Self::slot_revocations(cert)
+    }
+
+    fn is_validity_allowed(validity: T::BlockNumber) -> bool {
+        validity >= T::MinSlotValidity::get() && validity <= T::MaxSlotValidity::get()
+    }
+
+    /// Scale `base_fee`, which is the price for `SlotValidity` blocks, to `validity`
+    /// blocks. The fee is rounded up so that short validities are never free.
+    pub fn fee_for(base_fee: BalanceOf<T>, validity: T::BlockNumber) -> BalanceOf<T> {
+        let validity: BalanceOf<T> = validity.saturated_into::<u128>().saturated_into();
+        let reference: BalanceOf<T> = T::SlotValidity::get()
+            .saturated_into::<u128>()
+            .saturated_into::<BalanceOf<T>>()
+            .max(One::one());
+
+        let scaled = base_fee.saturating_mul(validity);
+        let remainder = scaled % reference;
+        if remainder.is_zero() {
+            scaled / reference
+        } else {
+            scaled / reference + One::one()
+        }
+    }
+
+    /// Emit `SlotExpired` for the slots expiring at `block` and queue them for pruning.
+    /// At most `MaxExpiredPerBlock` entries of `ExpiringSlots` are processed, the
+    /// remaining ones are processed in the following blocks. Returns how many entries
+    /// were processed.
+    fn expire_slots(block: T::BlockNumber) -> u32 {
+        let mut current = Self::expiry_cursor().unwrap_or(block);
+        let mut budget = T::MaxExpiredPerBlock::get() as usize;
+        let mut processed = 0;
+
+        while current <= block {
+            let mut slots = <ExpiringSlots<T>>::take(current);
+            let postponed = slots.len() > budget;
+            if postponed {
+                <ExpiringSlots<T>>::insert(current, slots.split_off(budget));
+            }
+            budget -= slots.len();
+            processed += slots.len() as u32;
+
+            for certificate in slots {
+                Self::expire_slot(certificate, current, block);
+            }
+
+            if postponed {
+                break;
+            }
+            current += One::one();
+        }
+        <ExpiryCursor<T>>::put(current);
+
+        processed
+    }
+
+    /// Keep the slots expiring or queued for pruning at `block` for after the migration,
+    /// slots still holding revocations in their legacy value must not be pruned
+    fn postpone_slot_maintenance(block: T::BlockNumber) {
+        if Self::expiry_cursor().is_none() {
+            <ExpiryCursor<T>>::put(block);
+        }
+
+        // Queues may still hold legacy certificate ids
+        let queue = migration::take_pruning_queue::<T>(block);
+        if !queue.is_empty() {
+            let next = block + One::one();
+            let mut slots = migration::take_pruning_queue::<T>(next);
+            slots.extend(queue);
+            <PruningQueue<T>>::insert(next, slots);
+        }
+    }
+
+    /// Expire `certificate` if it was still set to expire at `expiry`, `block` being
+    /// the current block
+    fn expire_slot(certificate: T::CertificateId, expiry: T::BlockNumber, block: T::BlockNumber) {
+        if !<Slots<T>>::contains_key(&certificate) {
+            return;
+        }
+
+        // The slot may have been renewed, revoked or rotated in the meantime, rotated
+        // slots are pruned along with their replacement
+        let slot = <Slots<T>>::get(&certificate);
+        if slot.revoked || slot.renewed + slot.validity != expiry {
+            return;
+        }
+        if <SupersededBy<T>>::contains_key(&certificate) {
+            return;
+        }
+
+        <PruningQueue<T>>::mutate(block + T::SlotGracePeriod::get(), |slots| {
+            slots.push(certificate.clone())
+        });
+
+        Self::deposit_event(RawEvent::SlotExpired(certificate));
+    }
+
+    /// Remove at most `MaxPrunedPerBlock` slots queued for pruning at `block`, the
+    /// remaining ones are postponed to the next block. The slots a pruned slot replaced
+    /// through `rotate_root` are pruned with it. Returns the weight of the pruned slots.
+    fn prune_slots(block: T::BlockNumber) -> Weight {
+        let mut queue = <PruningQueue<T>>::take(block);
+        let budget = T::MaxPrunedPerBlock::get() as usize;
+        if queue.len() > budget {
+            let postponed = queue.split_off(budget);
+            <PruningQueue<T>>::mutate(block + One::one(), |slots| slots.extend(postponed));
+        }
+
+        let mut weight = 0;
+        for certificate in queue {
+            if !<Slots<T>>::contains_key(&certificate) {
+                continue;
+            }
+
+            let slot = <Slots<T>>::get(&certificate);
+            let expired = slot.renewed + slot.validity <= block;
+            if !slot.revoked && !expired {
+                continue;
+            }
+
+            let mut next = Some(certificate);
+            while let Some(certificate) = next {
+                let intermediates = <IntermediatesOf<T>>::decode_len(&certificate).unwrap_or(0);
+                weight += T::WeightInfo::prune_slot(intermediates as u32);
+                Self::remove_slot(&certificate);
+                next = <Predecessor<T>>::take(&certificate);
+
+                Self::deposit_event(RawEvent::SlotPruned(certificate));
+            }
+        }
+
+        weight
+    }
+
+    /// Remove a slot and everything attached to it from storage, revocations of the
+    /// slot and of its intermediates are kept so that their keys can not be used again
+    fn remove_slot(certificate: &T::CertificateId) {
+        let slot = <Slots<T>>::take(certificate);
+        for intermediate in <IntermediatesOf<T>>::take(certificate) {
+            let parent = <Intermediates<T>>::get(&intermediate).parent;
+            if let Some(revocation) = Self::child_certificate_revocation(&parent, &intermediate) {
+                <SlotRevocations<T>>::insert(&intermediate, revocation);
+            }
+            <ChildRevocations<T>>::remove_prefix(&intermediate);
+            <ChildSuspensions<T>>::remove_prefix(&intermediate);
+            <RevokedBefore<T>>::remove(&intermediate);
+            <Intermediates<T>>::remove(&intermediate);
+        }
+        <ChildRevocations<T>>::remove_prefix(certificate);
+        <ChildSuspensions<T>>::remove_prefix(certificate);
+        if slot.revoked && !<SlotRevocations<T>>::contains_key(certificate) {
+            // Slots revoked before revocations were recorded
+            <SlotRevocations<T>>::insert(
+                certificate,
+                Revocation {
+                    revoked_at: <system::Module<T>>::block_number(),
+                    reason: RevocationReason::Unspecified,
+                },
+            );
+        }
+        <RevokedBefore<T>>::remove(certificate);
+        <PendingTransfers<T>>::remove(certificate);
+        <SupersededBy<T>>::remove(certificate);
+        Self::unindex_slot(&slot.owner, certificate);
+    }
+
+    /// Remove `certificate` from the slots of `owner`
+    fn unindex_slot(owner: &T::AccountId, certificate: &T::CertificateId) {
+        let mut slots = Self::slots_of(owner);
+        slots.retain(|slot| slot != certificate);
+        if slots.is_empty() {
+            <SlotsOf<T>>::remove(owner);
+        } else {
+            <SlotsOf<T>>::insert(owner, slots);
+        }
+    }
+
+    /// Slots of `owner` which are currently valid
+    fn valid_slots_of(owner: &T::AccountId) -> Vec<T::CertificateId> {
+        Self::slots_of(owner)
+            .into_iter()
+            .filter(|certificate| Self::is_root_certificate_valid(certificate))
+            .collect()
+    }
+
+    /// Whether `issuer` is a valid root certificate or an intermediate certificate
+    /// that chains up to a valid root certificate without any revocation on the way.
+    fn is_issuer_valid(issuer: &T::CertificateId) -> bool {
+        Self::check_issuer(issuer).is_valid()
+    }
+
+    /// Walk up from `issuer` to its root certificate and return the first reason
+    /// making the chain invalid, if any.
+    fn check_issuer(issuer: &T::CertificateId) -> VerificationResult<T::CertificateId> {
+        let mut current = issuer.clone();
+
+        // A valid chain has at most `MaxPathLength` intermediates plus its root
+        for _ in 0..=T::MaxPathLength::get() {
+            if !<Intermediates<T>>::contains_key(&current) {
+                return match Self::root_certificate_status(&current) {
+                    CertificateStatus::Valid => VerificationResult::Valid,
+                    CertificateStatus::NotFound => VerificationResult::UnknownRoot(current),
+                    CertificateStatus::OwnerNotAMember => {
+                        VerificationResult::RootNotAMember(current)
+                    }
+                    CertificateStatus::Revoked => VerificationResult::Revoked(current),
+                    CertificateStatus::Expired => VerificationResult::Expired(current),
+                    CertificateStatus::Superseded => VerificationResult::Superseded(current),
+                };
+            }
+
+            let intermediate = <Intermediates<T>>::get(&current);
+            if intermediate.expires <= <system::Module<T>>::block_number() {
+                return VerificationResult::Expired(current);
+            }
+            if Self::is_revoked_by(&intermediate.parent, &current, Some(intermediate.created)) {
+                return VerificationResult::Revoked(current);
+            }
+            if Self::is_suspended_by(&intermediate.parent, &current) {
+                return VerificationResult::Suspended(current);
+            }
+
+            current = intermediate.parent;
+        }
+
+        VerificationResult::InvalidChain
+    }
+
+    /// Whether `issuer` (a root or intermediate certificate) revoked `child`, revocations
+    /// made by the root certificates `issuer` replaced through `rotate_root` apply too.
+    /// When known, `created` is checked against `revoke_children_issued_before` cutoffs.
+    fn is_revoked_by(
+        issuer: &T::CertificateId,
+        child: &T::CertificateId,
+        created: Option<T::BlockNumber>,
+    ) -> bool {
+        Self::rotation_history(issuer).iter().any(|issuer| {
+            let revoked = <ChildRevocations<T>>::contains_key(issuer, child)
+                || migration::is_revocation_pending::<T>(issuer, child);
+            let issued_before_cutoff = match (created, Self::revoked_before(issuer)) {
+                (Some(created), Some(cutoff)) => created < cutoff,
+                _ => false,
+            };
+
+            revoked || issued_before_cutoff
+        })
+    }
+
+    /// Whether `issuer`, or a root certificate it replaced, suspended `child`
+    fn is_suspended_by(issuer: &T::CertificateId, child: &T::CertificateId) -> bool {
+        Self::rotation_history(issuer)
+            .iter()
+            .any(|issuer| <ChildSuspensions<T>>::contains_key(issuer, child))
+    }
+
+    /// `issuer` followed by the root certificates it replaced through `rotate_root`,
+    /// most recent first
+    fn rotation_history(issuer: &T::CertificateId) -> Vec<T::CertificateId> {
+        let mut history = Vec::new();
+        let mut current = Some(issuer.clone());
+        while let Some(issuer) = current {
+            current = Self::predecessor(&issuer);
+            history.push(issuer);
+        }
+
+        history
+    }
+
+    /// Follow the rotations of `signer` which happened after `created`, a certificate
+    /// signed before a rotation is checked against the replacement root certificate.
+    /// `created` is chosen by the signer, so rotations of a compromised key are never
+    /// followed as whoever holds it could backdate the certificates it signs.
+    fn effective_issuer(signer: &T::CertificateId, created: T::BlockNumber) -> T::CertificateId {
+        let mut issuer = signer.clone();
+        while let Some((new, cutoff)) = Self::superseded_by(&issuer) {
+            if created >= cutoff || <SlotRevocations<T>>::contains_key(&issuer) {
+                break;
+            }
+            issuer = new;
+        }
+
+        issuer
+    }
+
+    /// Verify that `child` was not revoked by `issuer` and that `issuer` chains up to
+    /// a valid root certificate. `issuer` may be a root or intermediate certificate.
+    pub fn child_certificate_status(
+        issuer: &T::CertificateId,
+        child: &T::CertificateId,
+    ) -> VerificationResult<T::CertificateId> {
+        Self::child_status(issuer, child, None)
+    }
 
-        exists && Self::is_slot_valid(&slot)
+    fn child_status(
+        issuer: &T::CertificateId,
+        child: &T::CertificateId,
+        created: Option<T::BlockNumber>,
+    ) -> VerificationResult<T::CertificateId> {
+        if issuer == child {
+            return VerificationResult::InvalidChain;
+        }
+        if Self::is_revoked_by(issuer, child, created) {
+            return VerificationResult::Revoked(child.clone());
+        }
+        if Self::is_suspended_by(issuer, child) {
+            return VerificationResult::Suspended(child.clone());
+        }
+
+        Self::check_issuer(issuer)
     }
 
     #[allow(dead_code)]
-    pub fn is_child_certificate_valid(root: &T::CertificateId, child: &T::CertificateId) -> bool {
-        let equals = root == child;
-        let root_valid = Self::is_root_certificate_valid(root);
-        let revoked = <Slots<T>>::get(root).child_revocations.contains(child);
+    pub fn is_child_certificate_valid(issuer: &T::CertificateId, child: &T::CertificateId) -> bool {
+        Self::child_certificate_status(issuer, child).is_valid()
+    }
+
+    /// When and why `issuer`, or a root certificate it replaced, revoked `child`
+    pub fn child_certificate_revocation(
+        issuer: &T::CertificateId,
+        child: &T::CertificateId,
+    ) -> Option<RevocationOf<T>> {
+        Self::rotation_history(issuer)
+            .iter()
+            .find_map(|issuer| Self::child_revocations(issuer, child))
+    }
+
+    /// Verify that `certificate` was signed by its `signer`, has not expired and that
+    /// `signer` is a valid root for `device`. If `signer` was rotated after the
+    /// certificate was created, its replacement is checked instead.
+    #[allow(dead_code)]
+    pub fn is_child_certificate_signed_valid(certificate: &CertificateOf<T>) -> bool {
+        let expired = certificate.is_expired(<system::Module<T>>::block_number());
+        let signed = certificate.verify();
+        let issuer = Self::effective_issuer(&certificate.signer, certificate.created);
+
+        !expired
+            && signed
+            && Self::child_status(&issuer, &certificate.device, Some(certificate.created))
+                .is_valid()
+    }
+
+    /// Verify a chain of off-chain certificates, starting with the leaf certificate
+    /// and ending with the certificate signed by a root or a registered intermediate
+    /// certificate. Every certificate must be signed by the subject of the next one,
+    /// which has to be an intermediate registered under that signer so that only
+    /// certificate authorities known on chain can issue certificates. Certificates
+    /// signed by a root certificate before it was rotated are checked against its
+    /// replacement.
+    pub fn verify_certificate_chain(
+        chain: Vec<CertificateOf<T>>,
+    ) -> VerificationResult<T::CertificateId> {
+        if chain.is_empty() || chain.len() > T::MaxPathLength::get() as usize + 1 {
+            return VerificationResult::InvalidChain;
+        }
+
+        let now = <system::Module<T>>::block_number();
+        for (index, certificate) in chain.iter().enumerate() {
+            if certificate.signer == certificate.device {
+                return VerificationResult::InvalidChain;
+            }
+            if let Some(next) = chain.get(index + 1) {
+                if next.device != certificate.signer {
+                    return VerificationResult::InvalidChain;
+                }
+            }
+            let issuer = Self::effective_issuer(&certificate.signer, certificate.created);
+            if index > 0 && !Self::is_registered_under(&certificate.device, &issuer) {
+                return VerificationResult::InvalidChain;
+            }
+            if !certificate.verify() {
+                return VerificationResult::BadSignature(certificate.device.clone());
+            }
+            if certificate.is_expired(now) {
+                return VerificationResult::Expired(certificate.device.clone());
+            }
+            if Self::is_revoked_by(&issuer, &certificate.device, Some(certificate.created)) {
+                return VerificationResult::Revoked(certificate.device.clone());
+            }
+            if Self::is_suspended_by(&issuer, &certificate.device) {
+                return VerificationResult::Suspended(certificate.device.clone());
+            }
+        }
 
-        // TODO: let's support signature verification here
+        let last = &chain[chain.len() - 1];
+        let issuer = Self::effective_issuer(&last.signer, last.created);
+        if chain.len() - 1 > Self::remaining_path_length(&issuer) as usize {
+            return VerificationResult::InvalidChain;
+        }
 
-        !equals && root_valid && !revoked
+        Self::check_issuer(&issuer)
+    }
+
+    /// Whether `intermediate` is an intermediate certificate registered with `parent`
+    /// as its parent
+    fn is_registered_under(intermediate: &T::CertificateId, parent: &T::CertificateId) -> bool {
+        <Intermediates<T>>::contains_key(intermediate)
+            && <Intermediates<T>>::get(intermediate).parent == *parent
+    }
+
+    /// How many intermediates can still be chained below `issuer`
+    fn remaining_path_length(issuer: &T::CertificateId) -> u32 {
+        if <Intermediates<T>>::contains_key(issuer) {
+            <Intermediates<T>>::get(issuer).path_length
+        } else {
+            T::MaxPathLength::get()
+        }
     }
 }
 
 impl<T: Trait> ChangeMembers<T::AccountId> for Module<T> {
     fn change_members_sorted(
-        _incoming: &[T::AccountId],
-        _outgoing: &[T::AccountId],
+        incoming: &[T::AccountId],
+        outgoing: &[T::AccountId],
         new: &[T::AccountId],
     ) {
+        // Slots of outgoing members need to be collected while they are still valid
+        let suspended = outgoing
+            .iter()
+            .map(|owner| (owner.clone(), Self::valid_slots_of(owner)))
+            .collect::<Vec<_>>();
+
         <Members<T>>::put(new);
+
+        for (owner, slots) in suspended {
+            if !slots.is_empty() {
+                Self::deposit_event(RawEvent::SlotsSuspended(owner, slots));
+            }
+        }
+        for owner in incoming {
+            let restored = Self::valid_slots_of(owner);
+            if !restored.is_empty() {
+                Self::deposit_event(RawEvent::SlotsRestored(owner.clone(), restored));
+            }
+        }
     }
 }
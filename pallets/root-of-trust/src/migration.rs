@@ -0,0 +1,561 @@
+//! Storage migrations of the root of trust module.
+
+use super::*;
+use frame_support::storage::unhashed;
+
+/// Layout of `RootCertificate` before child revocations were moved to
+/// `ChildRevocations`
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct LegacyRootCertificate<AccountId, CertificateId, BlockNumber> {
+    pub owner: AccountId,
+    pub key: CertificateId,
+    pub created: BlockNumber,
+    pub renewed: BlockNumber,
+    pub revoked: bool,
+    pub validity: BlockNumber,
+    pub child_revocations: Vec<CertificateId>,
+}
+
+/// Layout of `IntermediateCertificate` before child revocations were moved to
+/// `ChildRevocations`
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct LegacyIntermediateCertificate<CertificateId, BlockNumber> {
+    pub key: CertificateId,
+    pub parent: CertificateId,
+    pub root: CertificateId,
+    pub path_length: u32,
+    pub created: BlockNumber,
+    pub expires: BlockNumber,
+    pub child_revocations: Vec<CertificateId>,
+}
+
+/// Translate a legacy certificate id
+pub fn from_legacy_id<T: Trait>(id: T::LegacyCertificateId) -> T::CertificateId {
+    <T::LegacyCertificateIds as Convert<_, _>>::convert(id)
+}
+
+/// Decode `value`, which must not be followed by anything else. Legacy and current
+/// certificate ids are encoded with different lengths, this tells them apart.
+fn decode_all<V: Decode>(mut value: &[u8]) -> Option<V> {
+    let decoded = V::decode(&mut value).ok()?;
+
+    if value.is_empty() {
+        Some(decoded)
+    } else {
+        None
+    }
+}
+
+/// Key of `id` in the map whose final prefix is `prefix`
+fn map_key<K: Encode>(prefix: &[u8; 32], id: &K) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    key.extend_from_slice(&id.using_encoded(sp_io::hashing::blake2_256));
+    key
+}
+
+/// Certificate ids of a `PruningQueue` or `ExpiringSlots` entry, translated if they
+/// are legacy ids
+fn queued_ids<T: Trait>(value: &[u8]) -> Vec<T::CertificateId> {
+    decode_all::<Vec<T::CertificateId>>(value)
+        .or_else(|| {
+            decode_all::<Vec<T::LegacyCertificateId>>(value)
+                .map(|ids| ids.into_iter().map(from_legacy_id::<T>).collect())
+        })
+        .unwrap_or_default()
+}
+
+/// Take the slots queued for pruning at `block`, the queue may not be migrated to
+/// `CertificateId` yet
+pub fn take_pruning_queue<T: Trait>(block: T::BlockNumber) -> Vec<T::CertificateId> {
+    let key = <PruningQueue<T>>::hashed_key_for(block);
+    let value = unhashed::get_raw(&key).unwrap_or_default();
+    unhashed::kill(&key);
+
+    queued_ids::<T>(&value)
+}
+
+/// Move at most `budget` entries of the double map whose final prefix is `prefix`
+/// from the `legacy` id of a certificate to its `certificate` id, translating the
+/// children kept in clear in the keys. Returns how many entries were moved and whether
+/// every entry was.
+fn move_children<T: Trait>(
+    prefix: &[u8; 32],
+    legacy: &T::LegacyCertificateId,
+    certificate: &T::CertificateId,
+    budget: u32,
+) -> (u32, bool) {
+    let legacy_prefix = map_key(prefix, legacy);
+    let certificate_prefix = map_key(prefix, certificate);
+    let mut moved = 0;
+
+    while let Some(key) =
+        sp_io::storage::next_key(&legacy_prefix).filter(|key| key.starts_with(&legacy_prefix))
+    {
+        if moved >= budget {
+            return (moved, false);
+        }
+
+        // With `blake2_128_concat` the child follows its 16 bytes hash
+        let child = key
+            .get(legacy_prefix.len() + 16..)
+            .and_then(decode_all::<T::LegacyCertificateId>);
+        if let Some(child) = child {
+            let child = from_legacy_id::<T>(child).encode();
+            let mut new_key = certificate_prefix.clone();
+            new_key.extend_from_slice(&sp_io::hashing::blake2_128(&child));
+            new_key.extend_from_slice(&child);
+
+            let value = unhashed::get_raw(&key).unwrap_or_default();
+            unhashed::put_raw(&new_key, &value);
+        }
+        unhashed::kill(&key);
+        moved += 1;
+    }
+
+    (moved, true)
+}
+
+/// Move the value stored for the `legacy` id of a certificate in the map whose final
+/// prefix is `prefix` to its `certificate` id, translating the value with `translate`.
+/// Returns how many values were moved.
+fn move_value<T: Trait, V: Decode, W: Encode>(
+    prefix: &[u8; 32],
+    legacy: &T::LegacyCertificateId,
+    certificate: &T::CertificateId,
+    translate: impl FnOnce(V) -> W,
+) -> u32 {
+    match unhashed::take::<V>(&map_key(prefix, legacy)) {
+        Some(value) => {
+            unhashed::put(&map_key(prefix, certificate), &translate(value));
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Move the entries stored under the `legacy` id of a root or intermediate certificate
+/// to its `certificate` id, spending at most `budget` on its children. Returns the
+/// budget used and whether every entry was moved.
+fn move_entries_of<T: Trait>(
+    legacy: &T::LegacyCertificateId,
+    certificate: &T::CertificateId,
+    budget: u32,
+) -> (u32, bool) {
+    let (revocations, done) = move_children::<T>(
+        &<ChildRevocations<T>>::final_prefix(),
+        legacy,
+        certificate,
+        budget,
+    );
+    if !done {
+        return (revocations, false);
+    }
+    let (suspensions, done) = move_children::<T>(
+        &<ChildSuspensions<T>>::final_prefix(),
+        legacy,
+        certificate,
+        budget.saturating_sub(revocations),
+    );
+    if !done {
+        return (revocations + suspensions, false);
+    }
+
+    let moved = move_value::<T, _, _>(
+        &<IntermediatesOf<T>>::final_prefix(),
+        legacy,
+        certificate,
+        |intermediates: Vec<T::LegacyCertificateId>| {
+            intermediates
+                .into_iter()
+                .map(from_legacy_id::<T>)
+                .collect::<Vec<_>>()
+        },
+    ) + move_value::<T, _, _>(
+        &<SupersededBy<T>>::final_prefix(),
+        legacy,
+        certificate,
+        |(new, block): (T::LegacyCertificateId, T::BlockNumber)| (from_legacy_id::<T>(new), block),
+    ) + move_value::<T, _, _>(
+        &<Predecessor<T>>::final_prefix(),
+        legacy,
+        certificate,
+        from_legacy_id::<T>,
+    ) + move_value::<T, _, _>(
+        &<PendingTransfers<T>>::final_prefix(),
+        legacy,
+        certificate,
+        |owner: T::AccountId| owner,
+    ) + move_value::<T, _, _>(
+        &<SlotRevocations<T>>::final_prefix(),
+        legacy,
+        certificate,
+        |revocation: RevocationOf<T>| revocation,
+    ) + move_value::<T, _, _>(
+        &<RevokedBefore<T>>::final_prefix(),
+        legacy,
+        certificate,
+        |cutoff: T::BlockNumber| cutoff,
+    );
+
+    (revocations + suspensions + moved, true)
+}
+
+/// Whether the entry stored under `key`, in the map whose final prefix is `prefix`,
+/// belongs to the `legacy` id
+fn is_stored_under<K: Encode>(prefix: &[u8; 32], key: &[u8], legacy: &K) -> bool {
+    key == &map_key(prefix, legacy)[..]
+}
+
+/// Store the certificates and their entries under `CertificateId` instead of the
+/// legacy ids they were stored under, at most `budget` entries at a time. Legacy
+/// values are kept with their layout, `migrate_child_revocations` then moves the
+/// revocations they hold. Certificates stored under their `CertificateId` are left as
+/// they are. Returns the budget used and whether every certificate was migrated.
+pub fn migrate_certificate_ids<T: Trait>(budget: u32) -> (u32, bool) {
+    let prefixes = [
+        <PruningQueue<T>>::final_prefix(),
+        <ExpiringSlots<T>>::final_prefix(),
+        <Slots<T>>::final_prefix(),
+        <Intermediates<T>>::final_prefix(),
+    ];
+
+    migrate_entries::<T, _>(&prefixes, budget, |key, value, budget| {
+        if key.starts_with(&prefixes[0]) || key.starts_with(&prefixes[1]) {
+            // Queues are keyed by block, only their values hold certificate ids
+            unhashed::put(key, &queued_ids::<T>(&value));
+            (1, true)
+        } else if key.starts_with(&prefixes[2]) {
+            let stored_under = |id: &T::LegacyCertificateId| is_stored_under(&prefixes[2], key, id);
+            // Slots may still hold their revocations
+            let (slot, revocations) =
+                match decode_all::<LegacySlot<T>>(&value).filter(|slot| stored_under(&slot.key)) {
+                    Some(slot) => (
+                        Slot::<T> {
+                            owner: slot.owner,
+                            key: slot.key,
+                            created: slot.created,
+                            renewed: slot.renewed,
+                            revoked: slot.revoked,
+                            validity: slot.validity,
+                        },
+                        Some(slot.child_revocations),
+                    ),
+                    None => {
+                        match decode_all::<Slot<T>>(&value).filter(|slot| stored_under(&slot.key)) {
+                            Some(slot) => (slot, None),
+                            None => return (1, true),
+                        }
+                    }
+                };
+
+            let certificate = from_legacy_id::<T>(slot.key.clone());
+            let (moved, done) = move_entries_of::<T>(&slot.key, &certificate, budget);
+            if !done {
+                return (moved, false);
+            }
+
+            let slot = RootCertificate {
+                owner: slot.owner,
+                key: certificate.clone(),
+                created: slot.created,
+                renewed: slot.renewed,
+                revoked: slot.revoked,
+                validity: slot.validity,
+            };
+            let new_key = <Slots<T>>::hashed_key_for(&certificate);
+            match revocations {
+                Some(revocations) => unhashed::put(
+                    &new_key,
+                    &LegacyRootCertificate {
+                        owner: slot.owner,
+                        key: slot.key,
+                        created: slot.created,
+                        renewed: slot.renewed,
+                        revoked: slot.revoked,
+                        validity: slot.validity,
+                        child_revocations: revocations
+                            .into_iter()
+                            .map(from_legacy_id::<T>)
+                            .collect(),
+                    },
+                ),
+                None => unhashed::put(&new_key, &slot),
+            }
+            unhashed::kill(key);
+
+            (moved + 1, true)
+        } else {
+            let stored_under = |id: &T::LegacyCertificateId| is_stored_under(&prefixes[3], key, id);
+            // Intermediates may still hold their revocations
+            let (intermediate, revocations) = match decode_all::<LegacyIntermediate<T>>(&value)
+                .filter(|intermediate| stored_under(&intermediate.key))
+            {
+                Some(intermediate) => (
+                    Intermediate::<T> {
+                        key: intermediate.key,
+                        parent: intermediate.parent,
+                        root: intermediate.root,
+                        path_length: intermediate.path_length,
+                        created: intermediate.created,
+                        expires: intermediate.expires,
+                    },
+                    Some(intermediate.child_revocations),
+                ),
+                None => match decode_all::<Intermediate<T>>(&value)
+                    .filter(|intermediate| stored_under(&intermediate.key))
+                {
+                    Some(intermediate) => (intermediate, None),
+                    None => return (1, true),
+                },
+            };
+
+            let certificate = from_legacy_id::<T>(intermediate.key.clone());
+            let (moved, done) = move_entries_of::<T>(&intermediate.key, &certificate, budget);
+            if !done {
+                return (moved, false);
+            }
+
+            let intermediate = IntermediateCertificate {
+                key: certificate.clone(),
+                parent: from_legacy_id::<T>(intermediate.parent),
+                root: from_legacy_id::<T>(intermediate.root),
+                path_length: intermediate.path_length,
+                created: intermediate.created,
+                expires: intermediate.expires,
+            };
+            let new_key = <Intermediates<T>>::hashed_key_for(&certificate);
+            match revocations {
+                Some(revocations) => unhashed::put(
+                    &new_key,
+                    &LegacyIntermediateCertificate {
+                        key: intermediate.key,
+                        parent: intermediate.parent,
+                        root: intermediate.root,
+                        path_length: intermediate.path_length,
+                        created: intermediate.created,
+                        expires: intermediate.expires,
+                        child_revocations: revocations
+                            .into_iter()
+                            .map(from_legacy_id::<T>)
+                            .collect(),
+                    },
+                ),
+                None => unhashed::put(&new_key, &intermediate),
+            }
+            unhashed::kill(key);
+
+            (moved + 1, true)
+        }
+    })
+}
+
+/// Slots and intermediates stored before `CertificateId`, with and without their
+/// revocations
+type LegacySlot<T> = LegacyRootCertificate<
+    <T as system::Trait>::AccountId,
+    <T as Trait>::LegacyCertificateId,
+    <T as system::Trait>::BlockNumber,
+>;
+type Slot<T> = RootCertificate<
+    <T as system::Trait>::AccountId,
+    <T as Trait>::LegacyCertificateId,
+    <T as system::Trait>::BlockNumber,
+>;
+type LegacyIntermediate<T> = LegacyIntermediateCertificate<
+    <T as Trait>::LegacyCertificateId,
+    <T as system::Trait>::BlockNumber,
+>;
+type Intermediate<T> =
+    IntermediateCertificate<<T as Trait>::LegacyCertificateId, <T as system::Trait>::BlockNumber>;
+
+/// Call `migrate` on the raw entries stored under `prefixes`, one prefix after the
+/// other, resuming after the key saved in `MigrationCursor`. `migrate` receives a key,
+/// its value and the remaining budget. It returns how much of the budget it used and
+/// whether the entry is done, entries which are not are resumed in the next call.
+/// Every entry uses at least one unit of `budget`. Returns the budget used and whether
+/// every entry was migrated, the cursor being cleared in that case.
+pub fn migrate_entries<T: Trait, F>(
+    prefixes: &[[u8; 32]],
+    budget: u32,
+    mut migrate: F,
+) -> (u32, bool)
+where
+    F: FnMut(&[u8], Vec<u8>, u32) -> (u32, bool),
+{
+    let cursor = MigrationCursor::get();
+    let mut stage = cursor
+        .as_ref()
+        .and_then(|key| prefixes.iter().position(|prefix| key.starts_with(prefix)))
+        .unwrap_or(0);
+    let mut key = cursor.unwrap_or_else(|| prefixes[stage].to_vec());
+    let mut used = 0;
+
+    while used < budget {
+        let next = sp_io::storage::next_key(&key).filter(|next| next.starts_with(&prefixes[stage]));
+        match next {
+            Some(next) => {
+                let value = sp_io::storage::get(&next).unwrap_or_default();
+                let (spent, done) = migrate(&next, value, budget - used);
+                used += spent.max(1);
+                if !done {
+                    break;
+                }
+                key = next;
+            }
+            None if stage + 1 < prefixes.len() => {
+                stage += 1;
+                key = prefixes[stage].to_vec();
+            }
+            None => {
+                MigrationCursor::kill();
+                return (used, true);
+            }
+        }
+    }
+    MigrationCursor::put(key);
+
+    (used, false)
+}
+
+/// Move at most `budget` of the revocations stored in `legacy` to `ChildRevocations`,
+/// starting from the last ones. Returns how many revocations were moved.
+fn move_revocations<T: Trait>(
+    issuer: &T::CertificateId,
+    legacy: &mut Vec<T::CertificateId>,
+    budget: u32,
+) -> u32 {
+    let now = <system::Module<T>>::block_number();
+    let children = legacy.split_off(legacy.len().saturating_sub(budget as usize));
+    for child in children.iter() {
+        <ChildRevocations<T>>::insert(
+            issuer,
+            child,
+            Revocation {
+                revoked_at: now,
+                reason: RevocationReason::Unspecified,
+            },
+        );
+    }
+
+    children.len() as u32
+}
+
+/// Move the revocations stored inside `Slots` and `Intermediates` values to
+/// `ChildRevocations`, at most `budget` revocations at a time. A certificate with
+/// more revocations than that is migrated over several calls, its remaining
+/// revocations are kept in its value meanwhile. We do not know when or why these
+/// revocations happened, so they are recorded as unspecified revocations that
+/// happened at the migration block. They are inserted with the current key layout of
+/// `ChildRevocations`, children in clear, so nothing is left to rewrite afterwards.
+/// Returns the budget used and whether every certificate was migrated.
+pub fn migrate_child_revocations<T: Trait>(budget: u32) -> (u32, bool) {
+    let prefixes = [
+        <Slots<T>>::final_prefix(),
+        <Intermediates<T>>::final_prefix(),
+    ];
+
+    migrate_entries::<T, _>(&prefixes, budget, |key, value, budget| {
+        if key.starts_with(&prefixes[0]) {
+            // Slots booked during the migration already have the new layout
+            let mut legacy = match LegacyRootCertificate::<
+                T::AccountId,
+                T::CertificateId,
+                T::BlockNumber,
+            >::decode(&mut &value[..])
+            {
+                Ok(legacy) => legacy,
+                Err(_) => return (1, true),
+            };
+            let moved = move_revocations::<T>(&legacy.key, &mut legacy.child_revocations, budget);
+            if !legacy.child_revocations.is_empty() {
+                unhashed::put(key, &legacy);
+                return (moved, false);
+            }
+
+            unhashed::put(
+                key,
+                &RootCertificate {
+                    owner: legacy.owner,
+                    key: legacy.key,
+                    created: legacy.created,
+                    renewed: legacy.renewed,
+                    revoked: legacy.revoked,
+                    validity: legacy.validity,
+                },
+            );
+            (moved + 1, true)
+        } else {
+            let mut legacy =
+                match LegacyIntermediateCertificate::<T::CertificateId, T::BlockNumber>::decode(
+                    &mut &value[..],
+                ) {
+                    Ok(legacy) => legacy,
+                    Err(_) => return (1, true),
+                };
+            let moved = move_revocations::<T>(&legacy.key, &mut legacy.child_revocations, budget);
+            if !legacy.child_revocations.is_empty() {
+                unhashed::put(key, &legacy);
+                return (moved, false);
+            }
+
+            unhashed::put(
+                key,
+                &IntermediateCertificate {
+                    key: legacy.key,
+                    parent: legacy.parent,
+                    root: legacy.root,
+                    path_length: legacy.path_length,
+                    created: legacy.created,
+                    expires: legacy.expires,
+                },
+            );
+            (moved + 1, true)
+        }
+    })
+}
+
+/// Whether `issuer` revoked `child` with a revocation which was not migrated yet
+pub fn is_revocation_pending<T: Trait>(
+    issuer: &T::CertificateId,
+    child: &T::CertificateId,
+) -> bool {
+    !<Module<T>>::revocations_migrated() && is_revoked_in_legacy_value::<T>(issuer, child)
+}
+
+/// Whether `issuer` revoked `child` in the revocations stored inside its value which
+/// were not moved to `ChildRevocations` yet
+fn is_revoked_in_legacy_value<T: Trait>(
+    issuer: &T::CertificateId,
+    child: &T::CertificateId,
+) -> bool {
+    let slot = unhashed::get::<LegacyRootCertificate<T::AccountId, T::CertificateId, T::BlockNumber>>(
+        &<Slots<T>>::hashed_key_for(issuer),
+    );
+    let intermediate = unhashed::get::<
+        LegacyIntermediateCertificate<T::CertificateId, T::BlockNumber>,
+    >(&<Intermediates<T>>::hashed_key_for(issuer));
+
+    slot.map_or(false, |slot| slot.child_revocations.contains(child))
+        || intermediate.map_or(false, |intermediate| {
+            intermediate.child_revocations.contains(child)
+        })
+}
+
+/// Fill `SlotsOf` with the slots booked before it was introduced, at most `budget`
+/// slots at a time. Returns the budget used and whether every slot was indexed.
+pub fn index_slots_by_owner<T: Trait>(budget: u32) -> (u32, bool) {
+    migrate_entries::<T, _>(&[<Slots<T>>::final_prefix()], budget, |_, value, _| {
+        // Keys of `Slots` are hashed, certificate ids are read from the slots themselves
+        if let Ok(slot) = RootCertificate::<T::AccountId, T::CertificateId, T::BlockNumber>::decode(
+            &mut &value[..],
+        ) {
+            <SlotsOf<T>>::mutate(&slot.owner, |slots| {
+                if !slots.contains(&slot.key) {
+                    slots.push(slot.key.clone());
+                }
+            });
+        }
+
+        (1, true)
+    })
+}
@@ -1,11 +1,13 @@
 use super::*;
 
 use frame_support::{
-    assert_noop, assert_ok, impl_outer_origin, parameter_types, traits::Imbalance, weights::Weight,
+    assert_noop, assert_ok, impl_outer_origin, parameter_types, storage::unhashed,
+    traits::Imbalance, weights::Weight,
 };
+use pki_certificate::Payload;
 use sp_core::H256;
 use sp_runtime::{
-    testing::Header,
+    testing::{Header, TestSignature, UintAuthorityId},
     traits::{BlakeTwo256, IdentityLookup},
     Perbill,
 };
@@ -62,7 +64,11 @@ parameter_types! {
     pub const MinimumChallengeAmount: u64 = 10000;
     pub const FinalizeApplicationPeriod: u64 = 100;
     pub const FinalizeChallengePeriod: u64 = 101; // Happens later to ease unit tests
+    pub const CommitPeriod: u64 = 50;
     pub const LoosersSlash: Perbill = Perbill::from_percent(50);
+    pub const UnrevealedSlash: Perbill = Perbill::from_percent(10);
+    pub const MaxVoters: u32 = 3;
+    pub const ExitCooldown: u64 = 10;
 }
 impl pallet_tcr::Trait for Test {
     type Event = ();
@@ -72,21 +78,55 @@ impl pallet_tcr::Trait for Test {
     type MinimumChallengeAmount = MinimumChallengeAmount;
     type FinalizeApplicationPeriod = FinalizeApplicationPeriod;
     type FinalizeChallengePeriod = FinalizeChallengePeriod;
+    type CommitPeriod = CommitPeriod;
     type LoosersSlash = LoosersSlash;
+    type UnrevealedSlash = UnrevealedSlash;
+    type ExitCooldown = ExitCooldown;
+    type MaxVoters = MaxVoters;
+    type MaxMigratedPerBlock = MaxMigratedPerBlock;
+    type WeightInfo = ();
     type ChangeMembers = TestModule;
 }
 parameter_types! {
     pub const SlotBookingCost: u64 = 1000;
     pub const SlotRenewingCost: u64 = 10000;
     pub const SlotValidity: u64 = 100000;
+    pub const MinSlotValidity: u64 = 10;
+    pub const MaxSlotValidity: u64 = 200000;
+    pub const MaxPathLength: u32 = 2;
+    pub const SlotGracePeriod: u64 = 10;
+    pub const MaxPrunedPerBlock: u32 = 1;
+    pub const MaxExpiredPerBlock: u32 = 2;
+    pub const MaxRevocationsPerCall: u32 = 3;
+    pub const MaxMigratedPerBlock: u32 = 2;
+}
+/// Certificates used to be identified by `u32`s
+pub struct LegacyCertificateIds;
+impl Convert<u32, u64> for LegacyCertificateIds {
+    fn convert(id: u32) -> u64 {
+        id.into()
+    }
 }
 impl Trait for Test {
     type Event = ();
     type Currency = pallet_balances::Module<Self>;
     type CertificateId = <Test as system::Trait>::AccountId;
+    type CertificateSignature = TestSignature;
+    type CertificateSigner = UintAuthorityId;
+    type LegacyCertificateId = u32;
+    type LegacyCertificateIds = LegacyCertificateIds;
     type SlotBookingCost = SlotBookingCost;
     type SlotRenewingCost = SlotRenewingCost;
     type SlotValidity = SlotValidity;
+    type MinSlotValidity = MinSlotValidity;
+    type MaxSlotValidity = MaxSlotValidity;
+    type MaxPathLength = MaxPathLength;
+    type SlotGracePeriod = SlotGracePeriod;
+    type MaxPrunedPerBlock = MaxPrunedPerBlock;
+    type MaxExpiredPerBlock = MaxExpiredPerBlock;
+    type MaxRevocationsPerCall = MaxRevocationsPerCall;
+    type MaxMigratedPerBlock = MaxMigratedPerBlock;
+    type WeightInfo = ();
     type FundsCollector = ();
 }
 
@@ -101,14 +141,24 @@ const ROOT_MANAGER: u64 = 1;
 const OFFCHAIN_CERTIFICATE_SIGNER_1: u64 = 2;
 const OFFCHAIN_CERTIFICATE_SIGNER_2: u64 = 3;
 const OFFCHAIN_CERTIFICATE_SIGNER_3: u64 = 4;
+const INTERMEDIATE_1: u64 = 5;
+const INTERMEDIATE_2: u64 = 6;
+const INTERMEDIATE_3: u64 = 7;
+const ROOT_MANAGER_2: u64 = 8;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
 fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
         .build_storage::<Test>()
         .unwrap()
-        .into()
+        .into();
+    ext.execute_with(|| {
+        CertificateIdsMigrated::put(true);
+        RevocationsMigrated::put(true);
+        SlotsIndexed::put(true);
+    });
+    ext
 }
 
 fn allocate_balances() {
@@ -126,7 +176,31 @@ fn do_register() {
         vec![],
         MinimumApplicationAmount::get(),
     ));
-    <TcrModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
+    <TcrModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
+}
+
+fn run_to_block(block: u64) {
+    <system::Module<Test>>::set_block_number(block);
+    <TestModule as sp_runtime::traits::OnInitialize<u64>>::on_initialize(block);
+}
+
+fn sign_certificate(signer: u64, device: u64, expires: u64) -> CertificateOf<Test> {
+    let payload = Payload::new(
+        device,
+        signer,
+        <system::Module<Test>>::block_number(),
+        expires,
+    );
+    let signature = TestSignature(signer, payload.encode());
+
+    payload.into_certificate(signature)
+}
+
+fn possession_proof(owner: u64, certificate_id: u64) -> TestSignature {
+    TestSignature(
+        certificate_id,
+        TestModule::key_possession_payload(&owner, &certificate_id),
+    )
 }
 
 #[test]
@@ -146,7 +220,12 @@ fn non_member_can_not_buy_slots() {
         allocate_balances();
 
         assert_noop!(
-            TestModule::book_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+            ),
             Error::<Test>::NotAMember
         );
     })
@@ -160,10 +239,17 @@ fn can_not_buy_slot_twice() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
         assert_noop!(
-            TestModule::book_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+            ),
             Error::<Test>::SlotTaken
         );
     })
@@ -177,13 +263,20 @@ fn can_not_buy_slot_if_not_enough_funds() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         BalancesModule::make_free_balance_be(&ROOT_MANAGER, 0);
 
         assert_noop!(
-            TestModule::book_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_2),
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_2)
+            ),
             Error::<Test>::NotEnoughFunds
         );
     })
@@ -197,7 +290,9 @@ fn member_can_buy_slots() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
         assert_eq!(
             TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).key,
@@ -224,14 +319,101 @@ fn member_can_buy_slots() {
             SlotValidity::get(),
         );
         assert_eq!(
-            TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).child_revocations,
-            vec![],
+            TestModule::child_revocations(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            None
         );
 
         assert_eq!(
             BalancesModule::free_balance(ROOT_MANAGER),
-            MinimumApplicationAmount::get() + SlotRenewingCost::get()
+            SlotRenewingCost::get()
         ); // Took SlotBookingCost
+           // The TCR stake stays reserved
+        assert_eq!(
+            BalancesModule::reserved_balance(ROOT_MANAGER),
+            MinimumApplicationAmount::get()
+        );
+    })
+}
+
+#[test]
+fn can_not_buy_slot_without_key_possession_proof() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        // Signed by another key
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_2)
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
+        // Signed for another owner
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER_2, OFFCHAIN_CERTIFICATE_SIGNER_1)
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
+        // Signed for another chain
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get(),
+                TestSignature(
+                    OFFCHAIN_CERTIFICATE_SIGNER_1,
+                    (ROOT_MANAGER, H256::repeat_byte(1), 0u64).encode()
+                )
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
+    })
+}
+
+#[test]
+fn key_possession_proof_can_not_be_replayed() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        let proof = possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1);
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            proof.clone()
+        ));
+        assert_eq!(TestModule::booking_nonce(OFFCHAIN_CERTIFICATE_SIGNER_1), 1);
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+
+        run_to_block(SlotGracePeriod::get());
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::NotFound
+        );
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get(),
+                proof
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
     })
 }
 
@@ -243,7 +425,9 @@ fn root_certificate_is_valid() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_eq!(
@@ -269,7 +453,6 @@ fn root_certificate_not_valid_if_revoked() {
                 renewed: now,
                 revoked: true,
                 validity: SlotValidity::get(),
-                child_revocations: vec![],
             },
         );
 
@@ -288,7 +471,9 @@ fn root_certificate_not_valid_if_expired() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         <system::Module<Test>>::set_block_number(SlotValidity::get() + 1);
@@ -313,7 +498,6 @@ fn root_certificate_not_valid_if_owner_is_no_longer_a_member() {
                 renewed: now,
                 revoked: false,
                 validity: SlotValidity::get(),
-                child_revocations: vec![],
             },
         );
 
@@ -342,7 +526,9 @@ fn child_certificate_still_valid_if_revoked_under_non_parent_certificate() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         let now = <system::Module<Test>>::block_number();
@@ -355,7 +541,14 @@ fn child_certificate_still_valid_if_revoked_under_non_parent_certificate() {
                 renewed: now,
                 revoked: false,
                 validity: SlotValidity::get(),
-                child_revocations: vec![OFFCHAIN_CERTIFICATE_SIGNER_2],
+            },
+        );
+        <ChildRevocations<Test>>::insert(
+            &OFFCHAIN_CERTIFICATE_SIGNER_3,
+            &OFFCHAIN_CERTIFICATE_SIGNER_2,
+            Revocation {
+                revoked_at: now,
+                reason: RevocationReason::KeyCompromise,
             },
         );
 
@@ -395,7 +588,14 @@ fn child_certificate_not_valid_if_revoked_in_root_certificate() {
                 renewed: now,
                 revoked: false,
                 validity: SlotValidity::get(),
-                child_revocations: vec![OFFCHAIN_CERTIFICATE_SIGNER_2],
+            },
+        );
+        <ChildRevocations<Test>>::insert(
+            &OFFCHAIN_CERTIFICATE_SIGNER_1,
+            &OFFCHAIN_CERTIFICATE_SIGNER_2,
+            Revocation {
+                revoked_at: now,
+                reason: RevocationReason::KeyCompromise,
             },
         );
 
@@ -440,7 +640,9 @@ fn child_certificate_is_valid() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_eq!(
@@ -461,7 +663,9 @@ fn child_invalid_if_equal_root() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_eq!(
@@ -482,21 +686,22 @@ fn renew_update_fields() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_ok!(TestModule::renew_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get()
         ));
         assert_eq!(
             TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).renewed,
             <system::Module<Test>>::block_number()
         );
-        assert_eq!(
-            BalancesModule::free_balance(ROOT_MANAGER),
-            MinimumApplicationAmount::get()
-        ); // Took SlotBookingCost + SlotRenewingCost
+        // Took SlotBookingCost + SlotRenewingCost
+        assert_eq!(BalancesModule::free_balance(ROOT_MANAGER), 0);
     })
 }
 
@@ -508,13 +713,16 @@ fn can_not_renew_if_not_owner() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_noop!(
             TestModule::renew_slot(
                 Origin::signed(OFFCHAIN_CERTIFICATE_SIGNER_1),
-                OFFCHAIN_CERTIFICATE_SIGNER_1
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get()
             ),
             Error::<Test>::NotTheOwner
         );
@@ -529,13 +737,19 @@ fn can_not_renew_if_invalid() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         <system::Module<Test>>::set_block_number(SlotValidity::get() + 1);
 
         assert_noop!(
-            TestModule::renew_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            TestModule::renew_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get()
+            ),
             Error::<Test>::NoLongerValid
         );
     })
@@ -549,13 +763,19 @@ fn can_not_renew_if_not_enough_funds() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         BalancesModule::make_free_balance_be(&ROOT_MANAGER, 0);
 
         assert_noop!(
-            TestModule::renew_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            TestModule::renew_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get()
+            ),
             Error::<Test>::NotEnoughFunds
         );
     })
@@ -569,12 +789,15 @@ fn revoke_slot_works() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_ok!(TestModule::revoke_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
         ));
 
         assert_eq!(
@@ -597,13 +820,16 @@ fn can_not_revoke_slot_if_not_owner() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_noop!(
             TestModule::revoke_slot(
                 Origin::signed(OFFCHAIN_CERTIFICATE_SIGNER_1),
-                OFFCHAIN_CERTIFICATE_SIGNER_1
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                RevocationReason::CessationOfOperation
             ),
             Error::<Test>::NotTheOwner
         );
@@ -618,17 +844,24 @@ fn can_not_revoke_slot_if_not_valid_anymore() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         // Best to way to make it invalid would be to revoke it once already!
         assert_ok!(TestModule::revoke_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
         ));
 
         assert_noop!(
-            TestModule::revoke_slot(Origin::signed(ROOT_MANAGER), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            TestModule::revoke_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                RevocationReason::CessationOfOperation
+            ),
             Error::<Test>::NoLongerValid
         );
     })
@@ -642,13 +875,16 @@ fn revoke_child_works() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_ok!(TestModule::revoke_child(
             Origin::signed(ROOT_MANAGER),
             OFFCHAIN_CERTIFICATE_SIGNER_1,
-            OFFCHAIN_CERTIFICATE_SIGNER_2
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
         ));
 
         assert_eq!(
@@ -663,10 +899,14 @@ fn revoke_child_works() {
             false
         );
         assert_eq!(
-            <Slots<Test>>::get(&OFFCHAIN_CERTIFICATE_SIGNER_1)
-                .child_revocations
-                .contains(&OFFCHAIN_CERTIFICATE_SIGNER_2),
-            true
+            TestModule::child_revocations(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            Some(Revocation {
+                revoked_at: 0,
+                reason: RevocationReason::KeyCompromise,
+            })
         );
     })
 }
@@ -679,14 +919,17 @@ fn can_not_revoke_child_if_not_owner() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_noop!(
             TestModule::revoke_child(
                 Origin::signed(OFFCHAIN_CERTIFICATE_SIGNER_1),
                 OFFCHAIN_CERTIFICATE_SIGNER_1,
-                OFFCHAIN_CERTIFICATE_SIGNER_2
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                RevocationReason::KeyCompromise
             ),
             Error::<Test>::NotTheOwner
         );
@@ -701,19 +944,23 @@ fn can_not_revoke_child_if_root_not_valid_anymore() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_ok!(TestModule::revoke_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
         ));
 
         assert_noop!(
             TestModule::revoke_child(
                 Origin::signed(ROOT_MANAGER),
                 OFFCHAIN_CERTIFICATE_SIGNER_1,
-                OFFCHAIN_CERTIFICATE_SIGNER_2
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                RevocationReason::KeyCompromise
             ),
             Error::<Test>::NoLongerValid
         );
@@ -728,22 +975,3313 @@ fn can_not_revoke_child_if_not_valid_anymore() {
 
         assert_ok!(TestModule::book_slot(
             Origin::signed(ROOT_MANAGER),
-            OFFCHAIN_CERTIFICATE_SIGNER_1
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
         ));
 
         assert_ok!(TestModule::revoke_child(
             Origin::signed(ROOT_MANAGER),
             OFFCHAIN_CERTIFICATE_SIGNER_1,
-            OFFCHAIN_CERTIFICATE_SIGNER_2
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
         ));
 
         assert_noop!(
             TestModule::revoke_child(
                 Origin::signed(ROOT_MANAGER),
                 OFFCHAIN_CERTIFICATE_SIGNER_1,
-                OFFCHAIN_CERTIFICATE_SIGNER_2
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                RevocationReason::KeyCompromise
             ),
             Error::<Test>::NoLongerValid
         );
     })
 }
+
+#[test]
+fn signed_child_certificate_is_valid() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        let certificate = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            true
+        );
+    })
+}
+
+#[test]
+fn signed_child_certificate_not_valid_if_bad_signature() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        let mut certificate = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+        // Signed by somebody else than the announced signer
+        certificate.signature = TestSignature(OFFCHAIN_CERTIFICATE_SIGNER_3, certificate.message());
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            false
+        );
+    })
+}
+
+#[test]
+fn signed_child_certificate_not_valid_if_expired() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        let certificate = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            10,
+        );
+
+        <system::Module<Test>>::set_block_number(10);
+
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            false
+        );
+    })
+}
+
+#[test]
+fn signed_child_certificate_not_valid_if_signer_is_not_a_root() {
+    new_test_ext().execute_with(|| {
+        let certificate = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            false
+        );
+    })
+}
+
+#[test]
+fn signed_child_certificate_not_valid_if_revoked() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+
+        let certificate = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            false
+        );
+    })
+}
+
+#[test]
+fn signed_child_certificate_not_valid_if_tampered() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        let mut certificate = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            10,
+        );
+        certificate.expires = SlotValidity::get();
+
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            false
+        );
+    })
+}
+
+fn do_book_slot_and_intermediates() {
+    assert_ok!(TestModule::book_slot(
+        Origin::signed(ROOT_MANAGER),
+        OFFCHAIN_CERTIFICATE_SIGNER_1,
+        SlotValidity::get(),
+        possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+    ));
+    assert_ok!(TestModule::add_intermediate(
+        Origin::signed(ROOT_MANAGER),
+        OFFCHAIN_CERTIFICATE_SIGNER_1,
+        INTERMEDIATE_1,
+        1,
+        SlotValidity::get(),
+        possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+    ));
+    assert_ok!(TestModule::add_intermediate(
+        Origin::signed(ROOT_MANAGER),
+        INTERMEDIATE_1,
+        INTERMEDIATE_2,
+        0,
+        SlotValidity::get(),
+        possession_proof(ROOT_MANAGER, INTERMEDIATE_2)
+    ));
+}
+
+#[test]
+fn add_intermediate_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_eq!(
+            TestModule::intermediates(INTERMEDIATE_2).root,
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        );
+        assert_eq!(
+            TestModule::intermediates(INTERMEDIATE_2).parent,
+            INTERMEDIATE_1
+        );
+        assert_eq!(TestModule::intermediates(INTERMEDIATE_2).path_length, 0);
+
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&OFFCHAIN_CERTIFICATE_SIGNER_1, &INTERMEDIATE_1),
+            true
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            true
+        );
+        // Intermediates are not root certificates
+        assert_eq!(
+            TestModule::is_root_certificate_valid(&INTERMEDIATE_1),
+            false
+        );
+    })
+}
+
+#[test]
+fn can_not_add_intermediate_if_not_owner() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(OFFCHAIN_CERTIFICATE_SIGNER_1),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_1,
+                0,
+                SlotValidity::get(),
+                possession_proof(OFFCHAIN_CERTIFICATE_SIGNER_1, INTERMEDIATE_1)
+            ),
+            Error::<Test>::NotTheOwner
+        );
+    })
+}
+
+#[test]
+fn can_not_add_intermediate_under_unknown_issuer() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_1,
+                0,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+            ),
+            Error::<Test>::IssuerNotFound
+        );
+    })
+}
+
+#[test]
+fn can_not_add_intermediate_past_max_path_length() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(ROOT_MANAGER),
+                INTERMEDIATE_2,
+                INTERMEDIATE_3,
+                0,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_3)
+            ),
+            Error::<Test>::PathTooLong
+        );
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_3,
+                MaxPathLength::get(),
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_3)
+            ),
+            Error::<Test>::PathTooLong
+        );
+    })
+}
+
+#[test]
+fn can_not_add_intermediate_twice_or_over_a_slot() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_1,
+                0,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+            ),
+            Error::<Test>::SlotTaken
+        );
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(ROOT_MANAGER),
+                INTERMEDIATE_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                0,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+            ),
+            Error::<Test>::SlotTaken
+        );
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                INTERMEDIATE_1,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+            ),
+            Error::<Test>::SlotTaken
+        );
+    })
+}
+
+#[test]
+fn can_not_add_already_expired_intermediate() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_1,
+                0,
+                <system::Module<Test>>::block_number(),
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+            ),
+            Error::<Test>::AlreadyExpired
+        );
+    })
+}
+
+#[test]
+fn can_not_add_intermediate_without_key_possession_proof() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        // Signed by another key
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_1,
+                0,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_2)
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
+        // Signed for another owner
+        assert_noop!(
+            TestModule::add_intermediate(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_1,
+                0,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER_2, INTERMEDIATE_1)
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
+
+        let proof = possession_proof(ROOT_MANAGER, INTERMEDIATE_1);
+        assert_ok!(TestModule::add_intermediate(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_1,
+            0,
+            SlotValidity::get(),
+            proof.clone()
+        ));
+        assert_eq!(TestModule::booking_nonce(INTERMEDIATE_1), 1);
+
+        // The proof is spent once the intermediate is gone
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+        run_to_block(SlotGracePeriod::get());
+        assert_eq!(<Intermediates<Test>>::contains_key(INTERMEDIATE_1), false);
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                INTERMEDIATE_1,
+                SlotValidity::get(),
+                proof
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
+    })
+}
+
+#[test]
+fn chain_not_valid_if_intermediate_revoked() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_1,
+            RevocationReason::KeyCompromise
+        ));
+
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_1, &INTERMEDIATE_2),
+            false
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            false
+        );
+    })
+}
+
+#[test]
+fn chain_not_valid_if_intermediate_expired() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::add_intermediate(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_1,
+            0,
+            10,
+            possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+        ));
+
+        <system::Module<Test>>::set_block_number(10);
+
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_1, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            false
+        );
+    })
+}
+
+#[test]
+fn chain_not_valid_if_root_revoked() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            false
+        );
+    })
+}
+
+#[test]
+fn revoke_child_of_intermediate_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            INTERMEDIATE_2,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            false
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_1, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            true
+        );
+        assert_noop!(
+            TestModule::revoke_child(
+                Origin::signed(ROOT_MANAGER),
+                INTERMEDIATE_2,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                RevocationReason::KeyCompromise
+            ),
+            Error::<Test>::NoLongerValid
+        );
+    })
+}
+
+#[test]
+fn can_not_revoke_child_of_intermediate_if_not_owner() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_noop!(
+            TestModule::revoke_child(
+                Origin::signed(OFFCHAIN_CERTIFICATE_SIGNER_1),
+                INTERMEDIATE_2,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                RevocationReason::KeyCompromise
+            ),
+            Error::<Test>::NotTheOwner
+        );
+    })
+}
+
+#[test]
+fn signed_child_certificate_of_intermediate_is_valid() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        let certificate = sign_certificate(
+            INTERMEDIATE_2,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            true
+        );
+    })
+}
+
+#[test]
+fn verify_certificate_chain_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        // Signed directly by the root certificate
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::Valid
+        );
+
+        // Signed by an on-chain intermediate
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                INTERMEDIATE_2,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::Valid
+        );
+
+        // Signed by an intermediate whose certificates are included in the chain
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![
+                sign_certificate(
+                    INTERMEDIATE_2,
+                    OFFCHAIN_CERTIFICATE_SIGNER_2,
+                    SlotValidity::get(),
+                ),
+                sign_certificate(INTERMEDIATE_1, INTERMEDIATE_2, SlotValidity::get()),
+                sign_certificate(
+                    OFFCHAIN_CERTIFICATE_SIGNER_1,
+                    INTERMEDIATE_1,
+                    SlotValidity::get(),
+                ),
+            ]),
+            VerificationResult::Valid
+        );
+    })
+}
+
+#[test]
+fn verify_certificate_chain_rejects_unregistered_intermediates() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        // `INTERMEDIATE_3` was never registered, any certificate could act as one
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![
+                sign_certificate(
+                    INTERMEDIATE_3,
+                    OFFCHAIN_CERTIFICATE_SIGNER_2,
+                    SlotValidity::get(),
+                ),
+                sign_certificate(
+                    OFFCHAIN_CERTIFICATE_SIGNER_1,
+                    INTERMEDIATE_3,
+                    SlotValidity::get(),
+                ),
+            ]),
+            VerificationResult::InvalidChain
+        );
+
+        // `INTERMEDIATE_2` is registered, but under `INTERMEDIATE_1`
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![
+                sign_certificate(
+                    INTERMEDIATE_2,
+                    OFFCHAIN_CERTIFICATE_SIGNER_2,
+                    SlotValidity::get(),
+                ),
+                sign_certificate(
+                    OFFCHAIN_CERTIFICATE_SIGNER_1,
+                    INTERMEDIATE_2,
+                    SlotValidity::get(),
+                ),
+            ]),
+            VerificationResult::InvalidChain
+        );
+    })
+}
+
+#[test]
+fn verify_certificate_chain_enforces_the_issuer_path_length() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        // Registered below `INTERMEDIATE_2` without going through `add_intermediate`,
+        // which would refuse it since `INTERMEDIATE_2` has no path length left
+        let now = <system::Module<Test>>::block_number();
+        <Intermediates<Test>>::insert(
+            &INTERMEDIATE_3,
+            IntermediateCertificate {
+                key: INTERMEDIATE_3,
+                parent: INTERMEDIATE_2,
+                root: OFFCHAIN_CERTIFICATE_SIGNER_1,
+                path_length: 0,
+                created: now,
+                expires: SlotValidity::get(),
+            },
+        );
+
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![
+                sign_certificate(
+                    INTERMEDIATE_3,
+                    OFFCHAIN_CERTIFICATE_SIGNER_2,
+                    SlotValidity::get(),
+                ),
+                sign_certificate(INTERMEDIATE_2, INTERMEDIATE_3, SlotValidity::get()),
+            ]),
+            VerificationResult::InvalidChain
+        );
+
+        // `INTERMEDIATE_1` can still have one intermediate below it
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![
+                sign_certificate(
+                    INTERMEDIATE_2,
+                    OFFCHAIN_CERTIFICATE_SIGNER_2,
+                    SlotValidity::get(),
+                ),
+                sign_certificate(INTERMEDIATE_1, INTERMEDIATE_2, SlotValidity::get()),
+            ]),
+            VerificationResult::Valid
+        );
+    })
+}
+
+#[test]
+fn verify_certificate_chain_rejects_malformed_chains() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![]),
+            VerificationResult::InvalidChain
+        );
+
+        // Second certificate does not certify the signer of the first one
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![
+                sign_certificate(
+                    INTERMEDIATE_3,
+                    OFFCHAIN_CERTIFICATE_SIGNER_2,
+                    SlotValidity::get(),
+                ),
+                sign_certificate(
+                    OFFCHAIN_CERTIFICATE_SIGNER_1,
+                    INTERMEDIATE_2,
+                    SlotValidity::get(),
+                ),
+            ]),
+            VerificationResult::InvalidChain
+        );
+
+        // Longer than `MaxPathLength` intermediates
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![
+                sign_certificate(INTERMEDIATE_3, OFFCHAIN_CERTIFICATE_SIGNER_2, 10),
+                sign_certificate(INTERMEDIATE_2, INTERMEDIATE_3, 10),
+                sign_certificate(INTERMEDIATE_1, INTERMEDIATE_2, 10),
+                sign_certificate(OFFCHAIN_CERTIFICATE_SIGNER_1, INTERMEDIATE_1, 10),
+            ]),
+            VerificationResult::InvalidChain
+        );
+    })
+}
+
+#[test]
+fn verify_certificate_chain_reports_reasons() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        let mut certificate = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+        certificate.signature = TestSignature(OFFCHAIN_CERTIFICATE_SIGNER_3, certificate.message());
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![certificate]),
+            VerificationResult::BadSignature(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                <system::Module<Test>>::block_number(),
+            )]),
+            VerificationResult::Expired(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::UnknownRoot(OFFCHAIN_CERTIFICATE_SIGNER_3)
+        );
+
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::Revoked(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::Revoked(OFFCHAIN_CERTIFICATE_SIGNER_1)
+        );
+    })
+}
+
+#[test]
+fn verify_certificate_chain_reports_root_not_a_member() {
+    new_test_ext().execute_with(|| {
+        let now = <system::Module<Test>>::block_number();
+        <Slots<Test>>::insert(
+            &OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RootCertificate {
+                owner: ROOT_MANAGER,
+                key: OFFCHAIN_CERTIFICATE_SIGNER_1,
+                created: now,
+                renewed: now,
+                revoked: false,
+                validity: SlotValidity::get(),
+            },
+        );
+
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::RootNotAMember(OFFCHAIN_CERTIFICATE_SIGNER_1)
+        );
+    })
+}
+
+#[test]
+fn root_certificate_status_reports_reasons() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::NotFound
+        );
+
+        let now = <system::Module<Test>>::block_number();
+        <Slots<Test>>::insert(
+            &OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RootCertificate {
+                owner: ROOT_MANAGER,
+                key: OFFCHAIN_CERTIFICATE_SIGNER_1,
+                created: now,
+                renewed: now,
+                revoked: false,
+                validity: SlotValidity::get(),
+            },
+        );
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::OwnerNotAMember
+        );
+
+        allocate_balances();
+        do_register();
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::Valid
+        );
+
+        <system::Module<Test>>::set_block_number(SlotValidity::get() + 1);
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::Expired
+        );
+
+        <system::Module<Test>>::set_block_number(now);
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::Revoked
+        );
+    })
+}
+
+#[test]
+fn child_certificate_status_reports_reasons() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            TestModule::child_certificate_status(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            VerificationResult::UnknownRoot(OFFCHAIN_CERTIFICATE_SIGNER_1)
+        );
+
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_eq!(
+            TestModule::child_certificate_status(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            VerificationResult::Valid
+        );
+        assert_eq!(
+            TestModule::child_certificate_status(&INTERMEDIATE_2, &INTERMEDIATE_2),
+            VerificationResult::InvalidChain
+        );
+
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            INTERMEDIATE_1,
+            INTERMEDIATE_2,
+            RevocationReason::KeyCompromise
+        ));
+        assert_eq!(
+            TestModule::child_certificate_status(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            VerificationResult::Revoked(INTERMEDIATE_2)
+        );
+    })
+}
+
+#[test]
+fn expired_slot_is_pruned_after_grace_period() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        let expiry = SlotValidity::get();
+        assert_eq!(
+            TestModule::expiring_slots(expiry),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1]
+        );
+
+        run_to_block(expiry);
+        assert_eq!(<ExpiringSlots<Test>>::contains_key(expiry), false);
+        assert_eq!(
+            TestModule::pruning_queue(expiry + SlotGracePeriod::get()),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1]
+        );
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            true
+        );
+
+        run_to_block(expiry + SlotGracePeriod::get());
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            false
+        );
+        assert_eq!(<Intermediates<Test>>::contains_key(&INTERMEDIATE_1), false);
+        assert_eq!(<Intermediates<Test>>::contains_key(&INTERMEDIATE_2), false);
+        assert_eq!(
+            <IntermediatesOf<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            false
+        );
+    })
+}
+
+#[test]
+fn renewed_slot_does_not_expire_at_previous_expiry() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        <system::Module<Test>>::set_block_number(10);
+        assert_ok!(TestModule::renew_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get()
+        ));
+
+        run_to_block(SlotValidity::get());
+        assert_eq!(
+            <PruningQueue<Test>>::contains_key(SlotValidity::get() + SlotGracePeriod::get()),
+            false
+        );
+        assert_eq!(
+            TestModule::is_root_certificate_valid(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            true
+        );
+        assert_eq!(
+            TestModule::expiring_slots(10 + SlotValidity::get()),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1]
+        );
+    })
+}
+
+#[test]
+fn revoked_slots_are_pruned_within_budget() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_2)
+        ));
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::CessationOfOperation
+        ));
+
+        // Only one slot can be pruned per block
+        run_to_block(SlotGracePeriod::get());
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            false
+        );
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_2),
+            true
+        );
+        assert_eq!(
+            TestModule::pruning_queue(SlotGracePeriod::get() + 1),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_2]
+        );
+
+        run_to_block(SlotGracePeriod::get() + 1);
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_2),
+            false
+        );
+    })
+}
+
+#[test]
+fn slot_expiries_are_processed_within_budget() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        for certificate in &[
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+        ] {
+            assert_ok!(TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                *certificate,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, *certificate)
+            ));
+        }
+
+        // Only two expiries can be processed per block
+        let expiry = SlotValidity::get();
+        run_to_block(expiry);
+        assert_eq!(
+            TestModule::pruning_queue(expiry + SlotGracePeriod::get()),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_2]
+        );
+        assert_eq!(
+            TestModule::expiring_slots(expiry),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_3]
+        );
+
+        run_to_block(expiry + 1);
+        assert_eq!(<ExpiringSlots<Test>>::contains_key(expiry), false);
+        assert_eq!(
+            TestModule::pruning_queue(expiry + 1 + SlotGracePeriod::get()),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_3]
+        );
+    })
+}
+
+#[test]
+fn pruned_slot_can_be_booked_again() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            MinSlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        run_to_block(MinSlotValidity::get());
+        run_to_block(MinSlotValidity::get() + SlotGracePeriod::get());
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::NotFound
+        );
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_eq!(
+            TestModule::is_root_certificate_valid(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            true
+        );
+    })
+}
+
+#[test]
+fn pruned_revoked_keys_stay_revoked() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_1,
+            RevocationReason::KeyCompromise
+        ));
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::KeyCompromise
+        ));
+
+        run_to_block(SlotGracePeriod::get());
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            false
+        );
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::Revoked
+        );
+        assert_eq!(
+            TestModule::root_certificate_status(&INTERMEDIATE_1),
+            CertificateStatus::Revoked
+        );
+        assert_eq!(
+            TestModule::root_certificate_status(&INTERMEDIATE_2),
+            CertificateStatus::NotFound
+        );
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::Revoked(OFFCHAIN_CERTIFICATE_SIGNER_1)
+        );
+
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+            ),
+            Error::<Test>::KeyRevoked
+        );
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                INTERMEDIATE_1,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+            ),
+            Error::<Test>::KeyRevoked
+        );
+    })
+}
+
+#[test]
+fn book_slot_with_custom_validity() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get() / 4,
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_eq!(
+            TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).validity,
+            SlotValidity::get() / 4
+        );
+        assert_eq!(
+            TestModule::expiring_slots(SlotValidity::get() / 4),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1]
+        );
+        assert_eq!(
+            BalancesModule::free_balance(ROOT_MANAGER),
+            SlotRenewingCost::get() + SlotBookingCost::get() - SlotBookingCost::get() / 4
+        );
+
+        <system::Module<Test>>::set_block_number(SlotValidity::get() / 4);
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::Expired
+        );
+    })
+}
+
+#[test]
+fn renew_slot_with_custom_validity() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get() / 2,
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        <system::Module<Test>>::set_block_number(10);
+        assert_ok!(TestModule::renew_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get() / 10
+        ));
+        assert_eq!(TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).renewed, 10);
+        assert_eq!(
+            TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).validity,
+            SlotValidity::get() / 10
+        );
+        assert_eq!(
+            BalancesModule::free_balance(ROOT_MANAGER),
+            SlotRenewingCost::get() + SlotBookingCost::get()
+                - SlotBookingCost::get() / 2
+                - SlotRenewingCost::get() / 10
+        );
+    })
+}
+
+#[test]
+fn can_not_book_or_renew_slot_with_out_of_bounds_validity() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                MinSlotValidity::get() - 1,
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+            ),
+            Error::<Test>::ValidityOutOfBounds
+        );
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                MaxSlotValidity::get() + 1,
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+            ),
+            Error::<Test>::ValidityOutOfBounds
+        );
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_noop!(
+            TestModule::renew_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                MaxSlotValidity::get() + 1
+            ),
+            Error::<Test>::ValidityOutOfBounds
+        );
+    })
+}
+
+#[test]
+fn fee_is_proportional_to_validity() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            TestModule::fee_for(SlotBookingCost::get(), SlotValidity::get()),
+            SlotBookingCost::get()
+        );
+        assert_eq!(
+            TestModule::fee_for(SlotBookingCost::get(), MaxSlotValidity::get()),
+            2 * SlotBookingCost::get()
+        );
+        assert_eq!(
+            TestModule::fee_for(SlotRenewingCost::get(), SlotValidity::get() / 100),
+            SlotRenewingCost::get() / 100
+        );
+    })
+}
+
+#[test]
+fn fee_is_rounded_up() {
+    new_test_ext().execute_with(|| {
+        // 1000 * 10 / 100000 = 0.1
+        assert_eq!(
+            TestModule::fee_for(SlotBookingCost::get(), MinSlotValidity::get()),
+            1
+        );
+        // 1000 * 150 / 100000 = 1.5
+        assert_eq!(TestModule::fee_for(SlotBookingCost::get(), 150), 2);
+        assert_eq!(TestModule::fee_for(0, MinSlotValidity::get()), 0);
+    })
+}
+
+#[test]
+fn transfer_slot_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        <Members<Test>>::put(vec![ROOT_MANAGER, ROOT_MANAGER_2]);
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+
+        assert_ok!(TestModule::transfer_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            ROOT_MANAGER_2
+        ));
+        assert_eq!(
+            TestModule::pending_transfers(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            Some(ROOT_MANAGER_2)
+        );
+
+        // The slot is not transferred until accepted
+        assert_eq!(
+            TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).owner,
+            ROOT_MANAGER
+        );
+
+        assert_ok!(TestModule::accept_slot_transfer(
+            Origin::signed(ROOT_MANAGER_2),
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        ));
+        assert_eq!(
+            TestModule::pending_transfers(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            None
+        );
+
+        let slot = TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1);
+        assert_eq!(slot.owner, ROOT_MANAGER_2);
+        assert_eq!(TestModule::slots_of(ROOT_MANAGER), vec![]);
+        assert_eq!(
+            TestModule::slots_of(ROOT_MANAGER_2),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1]
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            false
+        );
+
+        // Only the new owner can manage the slot now
+        assert_noop!(
+            TestModule::revoke_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                RevocationReason::CessationOfOperation
+            ),
+            Error::<Test>::NotTheOwner
+        );
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER_2),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+    })
+}
+
+#[test]
+fn can_not_transfer_slot_if_not_the_owner_or_to_non_member() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        assert_noop!(
+            TestModule::transfer_slot(
+                Origin::signed(ROOT_MANAGER_2),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                ROOT_MANAGER_2
+            ),
+            Error::<Test>::NotTheOwner
+        );
+        assert_noop!(
+            TestModule::transfer_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                ROOT_MANAGER_2
+            ),
+            Error::<Test>::NotAMember
+        );
+        assert_noop!(
+            TestModule::transfer_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                ROOT_MANAGER
+            ),
+            Error::<Test>::NoLongerValid
+        );
+    })
+}
+
+#[test]
+fn can_not_accept_slot_transfer_if_not_the_recipient() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        <Members<Test>>::put(vec![ROOT_MANAGER, ROOT_MANAGER_2]);
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        assert_noop!(
+            TestModule::accept_slot_transfer(
+                Origin::signed(ROOT_MANAGER_2),
+                OFFCHAIN_CERTIFICATE_SIGNER_1
+            ),
+            Error::<Test>::NoPendingTransfer
+        );
+
+        assert_ok!(TestModule::transfer_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            ROOT_MANAGER_2
+        ));
+        assert_noop!(
+            TestModule::accept_slot_transfer(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1
+            ),
+            Error::<Test>::NotTheRecipient
+        );
+
+        // The recipient left the registry before accepting
+        <Members<Test>>::put(vec![ROOT_MANAGER]);
+        assert_noop!(
+            TestModule::accept_slot_transfer(
+                Origin::signed(ROOT_MANAGER_2),
+                OFFCHAIN_CERTIFICATE_SIGNER_1
+            ),
+            Error::<Test>::NotAMember
+        );
+    })
+}
+
+#[test]
+fn can_not_accept_transfer_of_revoked_slot() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        <Members<Test>>::put(vec![ROOT_MANAGER, ROOT_MANAGER_2]);
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::transfer_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            ROOT_MANAGER_2
+        ));
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+
+        assert_noop!(
+            TestModule::accept_slot_transfer(
+                Origin::signed(ROOT_MANAGER_2),
+                OFFCHAIN_CERTIFICATE_SIGNER_1
+            ),
+            Error::<Test>::NoLongerValid
+        );
+
+        // Pending transfers are cleaned up with the slot
+        run_to_block(SlotGracePeriod::get());
+        assert_eq!(
+            TestModule::pending_transfers(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            None
+        );
+    })
+}
+
+#[test]
+fn rotate_root_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+
+        run_to_block(5);
+        assert_ok!(TestModule::rotate_root(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            RevocationReason::Superseded,
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_3)
+        ));
+
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::Superseded
+        );
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_3),
+            CertificateStatus::Valid
+        );
+        assert_eq!(
+            TestModule::superseded_by(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            Some((OFFCHAIN_CERTIFICATE_SIGNER_3, 5))
+        );
+
+        let slot = TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_3);
+        assert_eq!(slot.owner, ROOT_MANAGER);
+        assert_eq!(slot.created, 5);
+        assert_eq!(slot.renewed + slot.validity, SlotValidity::get());
+
+        // Revocations made under the old key still apply
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_3,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            false
+        );
+
+        // The old key can no longer be used
+        assert_noop!(
+            TestModule::revoke_child(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_1,
+                RevocationReason::KeyCompromise
+            ),
+            Error::<Test>::NoLongerValid
+        );
+        assert_noop!(
+            TestModule::renew_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get()
+            ),
+            Error::<Test>::NoLongerValid
+        );
+    })
+}
+
+#[test]
+fn can_not_rotate_root_if_not_the_owner_or_new_key_taken() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_noop!(
+            TestModule::rotate_root(
+                Origin::signed(ROOT_MANAGER_2),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                RevocationReason::Superseded,
+                possession_proof(ROOT_MANAGER_2, OFFCHAIN_CERTIFICATE_SIGNER_3)
+            ),
+            Error::<Test>::NotTheOwner
+        );
+        assert_noop!(
+            TestModule::rotate_root(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_1,
+                RevocationReason::Superseded,
+                possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+            ),
+            Error::<Test>::SlotTaken
+        );
+        assert_noop!(
+            TestModule::rotate_root(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                RevocationReason::Superseded,
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_3)
+            ),
+            Error::<Test>::NoLongerValid
+        );
+    })
+}
+
+#[test]
+fn can_not_rotate_root_without_key_possession_proof() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        // Signed by another key
+        assert_noop!(
+            TestModule::rotate_root(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                RevocationReason::Superseded,
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_2)
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
+        // Signed for another owner
+        assert_noop!(
+            TestModule::rotate_root(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                RevocationReason::Superseded,
+                possession_proof(ROOT_MANAGER_2, OFFCHAIN_CERTIFICATE_SIGNER_3)
+            ),
+            Error::<Test>::BadKeyPossessionProof
+        );
+
+        assert_ok!(TestModule::rotate_root(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            RevocationReason::Superseded,
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_3)
+        ));
+        assert_eq!(TestModule::booking_nonce(OFFCHAIN_CERTIFICATE_SIGNER_3), 1);
+    })
+}
+
+#[test]
+fn certificates_signed_before_rotation_stay_valid() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        let before = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+
+        run_to_block(5);
+        assert_ok!(TestModule::rotate_root(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            RevocationReason::Superseded,
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_3)
+        ));
+        let after = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_3,
+            SlotValidity::get(),
+        );
+
+        assert_eq!(TestModule::is_child_certificate_signed_valid(&before), true);
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![before.clone()]),
+            VerificationResult::Valid
+        );
+
+        // Without its creation date we can not tell when the child was signed
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            false
+        );
+
+        assert_eq!(TestModule::is_child_certificate_signed_valid(&after), false);
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![after]),
+            VerificationResult::Superseded(OFFCHAIN_CERTIFICATE_SIGNER_1)
+        );
+
+        // Revocations are now managed under the new key
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&before),
+            false
+        );
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![before]),
+            VerificationResult::Revoked(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+    })
+}
+
+#[test]
+fn rotating_a_compromised_root_revokes_what_it_signed() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        let before = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+
+        run_to_block(5);
+        assert_ok!(TestModule::rotate_root(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            RevocationReason::KeyCompromise,
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_3)
+        ));
+
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::Revoked
+        );
+        assert_eq!(
+            TestModule::root_certificate_revocation(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            Some(Revocation {
+                revoked_at: 5,
+                reason: RevocationReason::KeyCompromise
+            })
+        );
+
+        // Whoever holds the old key could have backdated this certificate
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&before),
+            false
+        );
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![before]),
+            VerificationResult::Revoked(OFFCHAIN_CERTIFICATE_SIGNER_1)
+        );
+
+        // Intermediates were registered on chain by the owner, they are kept
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                INTERMEDIATE_2,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::Valid
+        );
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![sign_certificate(
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                SlotValidity::get(),
+            )]),
+            VerificationResult::Valid
+        );
+    })
+}
+
+#[test]
+fn rotated_root_keeps_intermediates_and_is_pruned_with_its_replacement() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_ok!(TestModule::rotate_root(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            RevocationReason::Superseded,
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_3)
+        ));
+
+        assert_eq!(
+            TestModule::intermediates(INTERMEDIATE_1).root,
+            OFFCHAIN_CERTIFICATE_SIGNER_3
+        );
+        assert_eq!(
+            TestModule::intermediates(INTERMEDIATE_1).parent,
+            OFFCHAIN_CERTIFICATE_SIGNER_3
+        );
+        assert_eq!(
+            TestModule::intermediates(INTERMEDIATE_2).root,
+            OFFCHAIN_CERTIFICATE_SIGNER_3
+        );
+        assert_eq!(
+            TestModule::intermediates_of(OFFCHAIN_CERTIFICATE_SIGNER_3),
+            vec![INTERMEDIATE_1, INTERMEDIATE_2]
+        );
+        assert_eq!(
+            TestModule::slots_of(ROOT_MANAGER),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_3]
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            true
+        );
+
+        // The rotated slot does not expire on its own
+        run_to_block(SlotValidity::get());
+        assert_eq!(
+            TestModule::pruning_queue(SlotValidity::get() + SlotGracePeriod::get()),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_3]
+        );
+
+        run_to_block(SlotValidity::get() + SlotGracePeriod::get());
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            false
+        );
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_3),
+            false
+        );
+        assert_eq!(<Intermediates<Test>>::contains_key(&INTERMEDIATE_1), false);
+        assert_eq!(
+            TestModule::superseded_by(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            None
+        );
+        assert_eq!(TestModule::predecessor(OFFCHAIN_CERTIFICATE_SIGNER_3), None);
+        assert_eq!(<SlotsOf<Test>>::contains_key(&ROOT_MANAGER), false);
+    })
+}
+
+#[test]
+fn slots_are_suspended_and_restored_with_membership() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            MinSlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_2)
+        ));
+        assert_eq!(
+            TestModule::slots_of(ROOT_MANAGER),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_2]
+        );
+        assert_eq!(
+            TestModule::valid_slots_of(&ROOT_MANAGER),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_2]
+        );
+
+        TestModule::change_members_sorted(&[], &[ROOT_MANAGER], &[]);
+        assert_eq!(TestModule::valid_slots_of(&ROOT_MANAGER), vec![]);
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::OwnerNotAMember
+        );
+
+        // The second slot expires while its owner is not a member
+        run_to_block(MinSlotValidity::get());
+        TestModule::change_members_sorted(&[ROOT_MANAGER], &[], &[ROOT_MANAGER]);
+        assert_eq!(
+            TestModule::valid_slots_of(&ROOT_MANAGER),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1]
+        );
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_2),
+            CertificateStatus::Expired
+        );
+    })
+}
+
+#[test]
+fn leaving_the_registry_suspends_slots() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TcrModule::leave_registry(Origin::signed(ROOT_MANAGER)));
+
+        <TcrModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(ExitCooldown::get() + <system::Module<Test>>::block_number());
+        assert_eq!(TestModule::is_member(&ROOT_MANAGER), false);
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::OwnerNotAMember
+        );
+    })
+}
+
+#[test]
+fn slots_booked_before_the_owner_index_are_indexed() {
+    new_test_ext().execute_with(|| {
+        SlotsIndexed::put(false);
+        let now = <system::Module<Test>>::block_number();
+        for (owner, key) in vec![
+            (ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1),
+            (ROOT_MANAGER_2, OFFCHAIN_CERTIFICATE_SIGNER_2),
+        ] {
+            <Slots<Test>>::insert(
+                &key,
+                RootCertificate {
+                    owner,
+                    key,
+                    created: now,
+                    renewed: now,
+                    revoked: false,
+                    validity: SlotValidity::get(),
+                },
+            );
+        }
+
+        run_to_block(1);
+        run_to_block(2);
+        assert_eq!(TestModule::slots_indexed(), true);
+        assert_eq!(
+            TestModule::slots_of(ROOT_MANAGER),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1]
+        );
+        assert_eq!(
+            TestModule::slots_of(ROOT_MANAGER_2),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_2]
+        );
+    })
+}
+
+#[test]
+fn child_revocations_are_migrated() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        RevocationsMigrated::put(false);
+
+        unhashed::put(
+            &<Slots<Test>>::hashed_key_for(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            &migration::LegacyRootCertificate {
+                owner: ROOT_MANAGER,
+                key: OFFCHAIN_CERTIFICATE_SIGNER_1,
+                created: 0,
+                renewed: 0,
+                revoked: false,
+                validity: SlotValidity::get(),
+                child_revocations: vec![
+                    OFFCHAIN_CERTIFICATE_SIGNER_2,
+                    OFFCHAIN_CERTIFICATE_SIGNER_3,
+                ],
+            },
+        );
+        unhashed::put(
+            &<Intermediates<Test>>::hashed_key_for(&INTERMEDIATE_1),
+            &migration::LegacyIntermediateCertificate {
+                key: INTERMEDIATE_1,
+                parent: OFFCHAIN_CERTIFICATE_SIGNER_1,
+                root: OFFCHAIN_CERTIFICATE_SIGNER_1,
+                path_length: 0,
+                created: 0,
+                expires: SlotValidity::get(),
+                child_revocations: vec![INTERMEDIATE_2],
+            },
+        );
+
+        run_to_block(1);
+        assert_eq!(TestModule::revocations_migrated(), false);
+        run_to_block(2);
+        run_to_block(3);
+        assert_eq!(TestModule::revocations_migrated(), true);
+
+        let slot = TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1);
+        assert_eq!(slot.owner, ROOT_MANAGER);
+        assert_eq!(slot.validity, SlotValidity::get());
+        assert_eq!(
+            TestModule::intermediates(INTERMEDIATE_1).parent,
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        );
+
+        assert_eq!(
+            TestModule::child_revocations(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            Some(Revocation {
+                revoked_at: 1,
+                reason: RevocationReason::Unspecified,
+            })
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_3
+            ),
+            false
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_1, &INTERMEDIATE_2),
+            false
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_1, &INTERMEDIATE_3),
+            true
+        );
+    })
+}
+
+#[test]
+fn child_revocations_are_migrated_in_batches() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        RevocationsMigrated::put(false);
+
+        let children = vec![
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            INTERMEDIATE_1,
+            INTERMEDIATE_2,
+            INTERMEDIATE_3,
+        ];
+        unhashed::put(
+            &<Slots<Test>>::hashed_key_for(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            &migration::LegacyRootCertificate {
+                owner: ROOT_MANAGER,
+                key: OFFCHAIN_CERTIFICATE_SIGNER_1,
+                created: 0,
+                renewed: 0,
+                revoked: false,
+                validity: SlotValidity::get(),
+                child_revocations: children.clone(),
+            },
+        );
+        <PruningQueue<Test>>::insert(1, vec![OFFCHAIN_CERTIFICATE_SIGNER_1]);
+
+        run_to_block(1);
+        assert_eq!(TestModule::revocations_migrated(), false);
+        assert_eq!(
+            TestModule::child_revocations(&OFFCHAIN_CERTIFICATE_SIGNER_1, &INTERMEDIATE_3),
+            Some(Revocation {
+                revoked_at: 1,
+                reason: RevocationReason::Unspecified,
+            })
+        );
+        assert_eq!(
+            TestModule::child_revocations(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            None
+        );
+
+        // Revocations which were not migrated yet still apply
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            false
+        );
+        assert_eq!(
+            TestModule::root_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            CertificateStatus::Valid
+        );
+
+        // Slots are not modified nor pruned during the migration
+        assert_noop!(
+            TestModule::renew_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get()
+            ),
+            Error::<Test>::MigrationInProgress
+        );
+        assert_eq!(
+            TestModule::pruning_queue(2),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1]
+        );
+
+        for block in 2..=4 {
+            run_to_block(block);
+        }
+        assert_eq!(TestModule::revocations_migrated(), true);
+        assert_eq!(TestModule::migration_cursor(), None);
+
+        // Children are kept in clear in the keys, revocation lists list all of them
+        let list = pki_light_verifier::Verifier::<_, u64, u64, u64>::new(&ExternalitiesReader, 4)
+            .revocation_list(&OFFCHAIN_CERTIFICATE_SIGNER_1)
+            .expect("revocations are migrated");
+        let mut listed = list
+            .revocations
+            .into_iter()
+            .map(|(_, child, _)| child)
+            .collect::<Vec<_>>();
+        listed.sort();
+        let mut expected = children.clone();
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        for child in children {
+            assert_eq!(
+                TestModule::is_child_certificate_valid(&OFFCHAIN_CERTIFICATE_SIGNER_1, &child),
+                false
+            );
+            assert!(
+                TestModule::child_revocations(&OFFCHAIN_CERTIFICATE_SIGNER_1, &child).is_some()
+            );
+        }
+
+        assert_eq!(
+            TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).validity,
+            SlotValidity::get()
+        );
+        assert_ok!(TestModule::renew_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get()
+        ));
+    })
+}
+
+/// Key under which a map stored the value of `id` while certificates were identified
+/// by their legacy id
+fn legacy_map_key(prefix: [u8; 32], id: u64) -> Vec<u8> {
+    let mut key = prefix.to_vec();
+    key.extend_from_slice(&(id as u32).using_encoded(sp_io::hashing::blake2_256));
+    key
+}
+
+/// Same as `legacy_map_key` for the double maps keeping children in clear
+fn legacy_double_map_key(prefix: [u8; 32], issuer: u64, child: u64) -> Vec<u8> {
+    let mut key = legacy_map_key(prefix, issuer);
+    let child = (child as u32).encode();
+    key.extend_from_slice(&sp_io::hashing::blake2_128(&child));
+    key.extend_from_slice(&child);
+    key
+}
+
+#[test]
+fn certificate_ids_are_migrated() {
+    const PRUNED_ROOT: u64 = 9;
+    const SUSPENDED_CHILD: u64 = 10;
+    const VALID_CHILD: u64 = 11;
+
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        CertificateIdsMigrated::put(false);
+        RevocationsMigrated::put(false);
+        SlotsIndexed::put(false);
+
+        // A root which replaced `OFFCHAIN_CERTIFICATE_SIGNER_2` and registered an
+        // intermediate, stored with the layout in use before `CertificateId`
+        unhashed::put(
+            &legacy_map_key(<Slots<Test>>::final_prefix(), OFFCHAIN_CERTIFICATE_SIGNER_1),
+            &RootCertificate {
+                owner: ROOT_MANAGER,
+                key: OFFCHAIN_CERTIFICATE_SIGNER_1 as u32,
+                created: 0u64,
+                renewed: 0u64,
+                revoked: false,
+                validity: SlotValidity::get(),
+            },
+        );
+        unhashed::put(
+            &legacy_map_key(
+                <IntermediatesOf<Test>>::final_prefix(),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+            ),
+            &vec![INTERMEDIATE_1 as u32],
+        );
+        unhashed::put(
+            &legacy_map_key(
+                <Predecessor<Test>>::final_prefix(),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+            ),
+            &(OFFCHAIN_CERTIFICATE_SIGNER_2 as u32),
+        );
+        unhashed::put(
+            &legacy_map_key(
+                <PendingTransfers<Test>>::final_prefix(),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+            ),
+            &ROOT_MANAGER_2,
+        );
+        unhashed::put(
+            &legacy_double_map_key(
+                <ChildRevocations<Test>>::final_prefix(),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                INTERMEDIATE_2,
+            ),
+            &Revocation {
+                revoked_at: 1u64,
+                reason: RevocationReason::KeyCompromise,
+            },
+        );
+
+        // The rotated root still holds its revocations
+        unhashed::put(
+            &legacy_map_key(<Slots<Test>>::final_prefix(), OFFCHAIN_CERTIFICATE_SIGNER_2),
+            &migration::LegacyRootCertificate {
+                owner: ROOT_MANAGER,
+                key: OFFCHAIN_CERTIFICATE_SIGNER_2 as u32,
+                created: 0u64,
+                renewed: 0u64,
+                revoked: false,
+                validity: SlotValidity::get(),
+                child_revocations: vec![OFFCHAIN_CERTIFICATE_SIGNER_3 as u32],
+            },
+        );
+        unhashed::put(
+            &legacy_map_key(
+                <SupersededBy<Test>>::final_prefix(),
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+            ),
+            &(OFFCHAIN_CERTIFICATE_SIGNER_1 as u32, 1u64),
+        );
+
+        // The intermediate still holds its revocations too
+        unhashed::put(
+            &legacy_map_key(<Intermediates<Test>>::final_prefix(), INTERMEDIATE_1),
+            &migration::LegacyIntermediateCertificate {
+                key: INTERMEDIATE_1 as u32,
+                parent: OFFCHAIN_CERTIFICATE_SIGNER_1 as u32,
+                root: OFFCHAIN_CERTIFICATE_SIGNER_1 as u32,
+                path_length: 1,
+                created: 0u64,
+                expires: 1000u64,
+                child_revocations: vec![INTERMEDIATE_3 as u32],
+            },
+        );
+        unhashed::put(
+            &legacy_double_map_key(
+                <ChildSuspensions<Test>>::final_prefix(),
+                INTERMEDIATE_1,
+                SUSPENDED_CHILD,
+            ),
+            &1u64,
+        );
+        unhashed::put(
+            &legacy_map_key(<RevokedBefore<Test>>::final_prefix(), INTERMEDIATE_1),
+            &1u64,
+        );
+
+        // A revoked root waiting to be pruned
+        unhashed::put(
+            &legacy_map_key(<Slots<Test>>::final_prefix(), PRUNED_ROOT),
+            &RootCertificate {
+                owner: ROOT_MANAGER,
+                key: PRUNED_ROOT as u32,
+                created: 0u64,
+                renewed: 0u64,
+                revoked: true,
+                validity: SlotValidity::get(),
+            },
+        );
+        unhashed::put(
+            &legacy_map_key(<SlotRevocations<Test>>::final_prefix(), PRUNED_ROOT),
+            &Revocation {
+                revoked_at: 1u64,
+                reason: RevocationReason::CessationOfOperation,
+            },
+        );
+        unhashed::put(
+            &<PruningQueue<Test>>::hashed_key_for(1),
+            &vec![PRUNED_ROOT as u32],
+        );
+        unhashed::put(
+            &<ExpiringSlots<Test>>::hashed_key_for(SlotValidity::get()),
+            &vec![
+                OFFCHAIN_CERTIFICATE_SIGNER_1 as u32,
+                OFFCHAIN_CERTIFICATE_SIGNER_2 as u32,
+            ],
+        );
+
+        // Certificates are not found until they are migrated, and their keys can not
+        // be booked meanwhile
+        assert_eq!(
+            TestModule::child_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1, &VALID_CHILD),
+            VerificationResult::UnknownRoot(OFFCHAIN_CERTIFICATE_SIGNER_1)
+        );
+        assert_noop!(
+            TestModule::book_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                SlotValidity::get(),
+                possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+            ),
+            Error::<Test>::MigrationInProgress
+        );
+
+        run_to_block(1);
+        assert_eq!(TestModule::certificate_ids_migrated(), false);
+        let mut block = 1;
+        while TestModule::is_migrating() || !TestModule::slots_indexed() {
+            assert!(block < 20, "migrations are bounded");
+            block += 1;
+            run_to_block(block);
+        }
+
+        // Everything is stored under the current ids
+        assert_eq!(
+            TestModule::slots(OFFCHAIN_CERTIFICATE_SIGNER_1).key,
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        );
+        assert_eq!(
+            TestModule::intermediates_of(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            vec![INTERMEDIATE_1]
+        );
+        assert_eq!(
+            TestModule::intermediates(INTERMEDIATE_1).parent,
+            OFFCHAIN_CERTIFICATE_SIGNER_1
+        );
+        assert_eq!(
+            TestModule::superseded_by(OFFCHAIN_CERTIFICATE_SIGNER_2),
+            Some((OFFCHAIN_CERTIFICATE_SIGNER_1, 1))
+        );
+        assert_eq!(
+            TestModule::predecessor(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            Some(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+        assert_eq!(
+            TestModule::pending_transfers(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            Some(ROOT_MANAGER_2)
+        );
+        assert_eq!(TestModule::revoked_before(INTERMEDIATE_1), Some(1));
+        assert_eq!(
+            TestModule::expiring_slots(SlotValidity::get()),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_2]
+        );
+        assert_eq!(
+            TestModule::slots_of(ROOT_MANAGER),
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_2]
+        );
+        assert_eq!(
+            unhashed::exists(&legacy_map_key(
+                <Slots<Test>>::final_prefix(),
+                OFFCHAIN_CERTIFICATE_SIGNER_1
+            )),
+            false
+        );
+
+        // The revoked root was pruned once its queue was migrated
+        assert_eq!(<Slots<Test>>::contains_key(PRUNED_ROOT), false);
+        assert_eq!(
+            TestModule::slot_revocations(PRUNED_ROOT),
+            Some(Revocation {
+                revoked_at: 1,
+                reason: RevocationReason::CessationOfOperation,
+            })
+        );
+
+        // Revocations and suspensions still apply
+        assert_eq!(
+            TestModule::child_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1, &VALID_CHILD),
+            VerificationResult::Valid
+        );
+        assert_eq!(
+            TestModule::child_certificate_status(&OFFCHAIN_CERTIFICATE_SIGNER_1, &INTERMEDIATE_2),
+            VerificationResult::Revoked(INTERMEDIATE_2)
+        );
+        assert_eq!(
+            TestModule::child_certificate_status(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_3
+            ),
+            VerificationResult::Revoked(OFFCHAIN_CERTIFICATE_SIGNER_3)
+        );
+        assert_eq!(
+            TestModule::child_certificate_status(&INTERMEDIATE_1, &INTERMEDIATE_3),
+            VerificationResult::Revoked(INTERMEDIATE_3)
+        );
+        assert_eq!(
+            TestModule::child_certificate_status(&INTERMEDIATE_1, &SUSPENDED_CHILD),
+            VerificationResult::Suspended(SUSPENDED_CHILD)
+        );
+        assert_light_verifier_agrees(&[
+            (OFFCHAIN_CERTIFICATE_SIGNER_1, VALID_CHILD),
+            (OFFCHAIN_CERTIFICATE_SIGNER_1, INTERMEDIATE_2),
+            (OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_3),
+            (INTERMEDIATE_1, INTERMEDIATE_3),
+            (INTERMEDIATE_1, SUSPENDED_CHILD),
+            (INTERMEDIATE_1, VALID_CHILD),
+        ]);
+    })
+}
+
+#[test]
+fn revocations_are_pruned_with_their_slot() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            INTERMEDIATE_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            RevocationReason::KeyCompromise
+        ));
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::CessationOfOperation
+        ));
+
+        run_to_block(SlotGracePeriod::get());
+        assert_eq!(
+            TestModule::child_revocations(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            None
+        );
+        assert_eq!(
+            TestModule::child_revocations(&INTERMEDIATE_1, &OFFCHAIN_CERTIFICATE_SIGNER_3),
+            None
+        );
+    })
+}
+
+#[test]
+fn revoke_children_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+
+        // Duplicates and already revoked children are skipped
+        assert_ok!(TestModule::revoke_children(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            vec![
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                OFFCHAIN_CERTIFICATE_SIGNER_3
+            ],
+            RevocationReason::KeyCompromise
+        ));
+
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            false
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_3
+            ),
+            false
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&OFFCHAIN_CERTIFICATE_SIGNER_1, &INTERMEDIATE_1),
+            true
+        );
+    })
+}
+
+#[test]
+fn can_not_revoke_too_many_children_or_without_being_the_owner() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_noop!(
+            TestModule::revoke_children(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                vec![
+                    OFFCHAIN_CERTIFICATE_SIGNER_2,
+                    OFFCHAIN_CERTIFICATE_SIGNER_3,
+                    INTERMEDIATE_1,
+                    INTERMEDIATE_3
+                ],
+                RevocationReason::KeyCompromise
+            ),
+            Error::<Test>::TooManyRevocations
+        );
+        assert_noop!(
+            TestModule::revoke_children(
+                Origin::signed(ROOT_MANAGER_2),
+                INTERMEDIATE_1,
+                vec![OFFCHAIN_CERTIFICATE_SIGNER_2],
+                RevocationReason::KeyCompromise
+            ),
+            Error::<Test>::NotTheOwner
+        );
+        assert_noop!(
+            TestModule::revoke_children(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                vec![OFFCHAIN_CERTIFICATE_SIGNER_3],
+                RevocationReason::KeyCompromise
+            ),
+            Error::<Test>::NoLongerValid
+        );
+
+        assert_ok!(TestModule::revoke_children(
+            Origin::signed(ROOT_MANAGER),
+            INTERMEDIATE_1,
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_2],
+            RevocationReason::KeyCompromise
+        ));
+        assert_eq!(
+            TestModule::is_child_certificate_valid(&INTERMEDIATE_1, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            false
+        );
+    })
+}
+
+#[test]
+fn revoke_children_issued_before_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        let old = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+        run_to_block(5);
+        let new = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            SlotValidity::get(),
+        );
+
+        assert_noop!(
+            TestModule::revoke_children_issued_before(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                6
+            ),
+            Error::<Test>::CutoffInTheFuture
+        );
+        assert_ok!(TestModule::revoke_children_issued_before(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            5
+        ));
+
+        assert_eq!(TestModule::is_child_certificate_signed_valid(&old), false);
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![old]),
+            VerificationResult::Revoked(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+        assert_eq!(TestModule::is_child_certificate_signed_valid(&new), true);
+
+        // Intermediates registered before the cutoff are revoked too
+        assert_eq!(
+            TestModule::child_certificate_status(&INTERMEDIATE_1, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            VerificationResult::Revoked(INTERMEDIATE_1)
+        );
+
+        // A cutoff can not be moved back
+        assert_ok!(TestModule::revoke_children_issued_before(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            2
+        ));
+        assert_eq!(
+            TestModule::revoked_before(OFFCHAIN_CERTIFICATE_SIGNER_1),
+            Some(5)
+        );
+    })
+}
+
+#[test]
+fn revocations_record_block_and_reason() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            INTERMEDIATE_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, INTERMEDIATE_1)
+        ));
+
+        run_to_block(3);
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+        assert_ok!(TestModule::revoke_children(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_3],
+            RevocationReason::CessationOfOperation
+        ));
+
+        run_to_block(4);
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            INTERMEDIATE_1,
+            RevocationReason::CACompromise
+        ));
+
+        assert_eq!(
+            TestModule::child_certificate_revocation(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            Some(Revocation {
+                revoked_at: 3,
+                reason: RevocationReason::KeyCompromise,
+            })
+        );
+        assert_eq!(
+            TestModule::child_certificate_revocation(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_3
+            ),
+            Some(Revocation {
+                revoked_at: 3,
+                reason: RevocationReason::CessationOfOperation,
+            })
+        );
+        assert_eq!(
+            TestModule::child_certificate_revocation(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &INTERMEDIATE_1
+            ),
+            None
+        );
+        assert_eq!(
+            TestModule::root_certificate_revocation(&INTERMEDIATE_1),
+            Some(Revocation {
+                revoked_at: 4,
+                reason: RevocationReason::CACompromise,
+            })
+        );
+        assert_eq!(
+            TestModule::root_certificate_revocation(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            None
+        );
+
+        // Revocations are still reported after a key rotation
+        assert_ok!(TestModule::rotate_root(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_2,
+            RevocationReason::Superseded,
+            possession_proof(ROOT_MANAGER, INTERMEDIATE_2)
+        ));
+        assert_eq!(
+            TestModule::child_certificate_revocation(
+                &INTERMEDIATE_2,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            Some(Revocation {
+                revoked_at: 3,
+                reason: RevocationReason::KeyCompromise,
+            })
+        );
+
+        // The revocation record is removed along with the slot
+        run_to_block(4 + SlotGracePeriod::get());
+        assert_eq!(
+            TestModule::root_certificate_revocation(&INTERMEDIATE_1),
+            None
+        );
+    })
+}
+
+#[test]
+fn suspend_and_reinstate_child_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        let certificate = sign_certificate(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+        );
+
+        assert_ok!(TestModule::suspend_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2
+        ));
+        assert_eq!(
+            TestModule::child_certificate_status(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            VerificationResult::Suspended(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            false
+        );
+        assert_eq!(
+            TestModule::verify_certificate_chain(vec![certificate.clone()]),
+            VerificationResult::Suspended(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+        assert_eq!(
+            TestModule::child_certificate_revocation(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            None
+        );
+
+        assert_ok!(TestModule::reinstate_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2
+        ));
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            true
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_signed_valid(&certificate),
+            true
+        );
+    })
+}
+
+#[test]
+fn suspended_intermediate_invalidates_its_children() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        assert_ok!(TestModule::suspend_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_1
+        ));
+        assert_eq!(
+            TestModule::child_certificate_status(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            VerificationResult::Suspended(INTERMEDIATE_1)
+        );
+
+        // A suspended intermediate can not manage its children
+        assert_noop!(
+            TestModule::suspend_child(Origin::signed(ROOT_MANAGER), INTERMEDIATE_1, INTERMEDIATE_2),
+            Error::<Test>::NoLongerValid
+        );
+
+        assert_ok!(TestModule::reinstate_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_1
+        ));
+        assert_eq!(
+            TestModule::child_certificate_status(&INTERMEDIATE_2, &OFFCHAIN_CERTIFICATE_SIGNER_2),
+            VerificationResult::Valid
+        );
+    })
+}
+
+#[test]
+fn suspensions_survive_root_rotations() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+        assert_ok!(TestModule::suspend_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2
+        ));
+        assert_ok!(TestModule::rotate_root(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            RevocationReason::Superseded,
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_3)
+        ));
+
+        assert_eq!(
+            TestModule::child_certificate_status(
+                &OFFCHAIN_CERTIFICATE_SIGNER_3,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            VerificationResult::Suspended(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+        assert_noop!(
+            TestModule::suspend_child(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_3,
+                OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            Error::<Test>::AlreadySuspended
+        );
+
+        // The suspension made by the old key is lifted through the new one
+        assert_ok!(TestModule::reinstate_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            OFFCHAIN_CERTIFICATE_SIGNER_2
+        ));
+        assert_eq!(
+            TestModule::child_suspensions(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            None
+        );
+        assert_eq!(
+            TestModule::is_child_certificate_valid(
+                &OFFCHAIN_CERTIFICATE_SIGNER_3,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            true
+        );
+    })
+}
+
+#[test]
+fn certificate_hold_is_not_a_revocation() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        assert_noop!(
+            TestModule::revoke_child(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2,
+                RevocationReason::CertificateHold
+            ),
+            Error::<Test>::HoldIsNotARevocation
+        );
+        assert_noop!(
+            TestModule::revoke_children(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                vec![OFFCHAIN_CERTIFICATE_SIGNER_2],
+                RevocationReason::CertificateHold
+            ),
+            Error::<Test>::HoldIsNotARevocation
+        );
+        assert_noop!(
+            TestModule::revoke_slot(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                RevocationReason::CertificateHold
+            ),
+            Error::<Test>::HoldIsNotARevocation
+        );
+    })
+}
+
+#[test]
+fn can_not_suspend_or_reinstate_child_twice() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_register();
+
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_1)
+        ));
+
+        assert_noop!(
+            TestModule::reinstate_child(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            Error::<Test>::NotSuspended
+        );
+        assert_noop!(
+            TestModule::suspend_child(
+                Origin::signed(ROOT_MANAGER_2),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            Error::<Test>::NotTheOwner
+        );
+
+        assert_ok!(TestModule::suspend_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2
+        ));
+        assert_noop!(
+            TestModule::suspend_child(
+                Origin::signed(ROOT_MANAGER),
+                OFFCHAIN_CERTIFICATE_SIGNER_1,
+                OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            Error::<Test>::AlreadySuspended
+        );
+
+        // Reinstating a revoked child does not lift the revocation
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+        assert_ok!(TestModule::reinstate_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2
+        ));
+        assert_eq!(
+            TestModule::child_certificate_status(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            VerificationResult::Revoked(OFFCHAIN_CERTIFICATE_SIGNER_2)
+        );
+    })
+}
+
+#[test]
+fn child_revocation_keys_end_with_the_child_id() {
+    new_test_ext().execute_with(|| {
+        // Revocation lists are built off-chain by decoding the children from the keys
+        let key = <ChildRevocations<Test>>::hashed_key_for(
+            &OFFCHAIN_CERTIFICATE_SIGNER_1,
+            &OFFCHAIN_CERTIFICATE_SIGNER_2,
+        );
+        assert_eq!(key.ends_with(&OFFCHAIN_CERTIFICATE_SIGNER_2.encode()), true);
+    })
+}
+
+#[test]
+fn light_verifier_keys_match_the_storage() {
+    use pki_light_verifier::keys;
+
+    new_test_ext().execute_with(|| {
+        assert_eq!(
+            <Members<Test>>::hashed_key().to_vec(),
+            keys::value(b"Members")
+        );
+        assert_eq!(
+            <Slots<Test>>::hashed_key_for(&ROOT_MANAGER),
+            keys::map(b"Slots", &ROOT_MANAGER)
+        );
+        assert_eq!(
+            <ChildRevocations<Test>>::hashed_key_for(
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            ),
+            keys::double_map(
+                b"ChildRevocations",
+                &OFFCHAIN_CERTIFICATE_SIGNER_1,
+                &OFFCHAIN_CERTIFICATE_SIGNER_2
+            )
+        );
+        assert_eq!(
+            RevocationsMigrated::hashed_key().to_vec(),
+            keys::value(b"RevocationsMigrated")
+        );
+        assert_eq!(
+            CertificateIdsMigrated::hashed_key().to_vec(),
+            keys::value(b"CertificateIdsMigrated")
+        );
+    })
+}
+
+/// Storage of the test externalities, as seen by the light verifier
+struct ExternalitiesReader;
+
+impl pki_light_verifier::StorageReader for ExternalitiesReader {
+    fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, pki_light_verifier::Error> {
+        Ok(sp_io::storage::get(key))
+    }
+
+    fn read_prefix(
+        &self,
+        prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, pki_light_verifier::Error> {
+        let mut entries = Vec::new();
+        let mut key = prefix.to_vec();
+        while let Some(next) =
+            sp_io::storage::next_key(&key).filter(|next| next.starts_with(prefix))
+        {
+            entries.push((next.clone(), sp_io::storage::get(&next).unwrap_or_default()));
+            key = next;
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Check that the light verifier reaches the same statuses as the pallet for every
+/// issuer and child of `pairs`
+fn assert_light_verifier_agrees(pairs: &[(u64, u64)]) {
+    let now = <system::Module<Test>>::block_number();
+    let verifier = pki_light_verifier::Verifier::<_, u64, u64, u64>::new(&ExternalitiesReader, now);
+
+    for (issuer, child) in pairs {
+        assert_eq!(
+            verifier.root_certificate_status(issuer),
+            Ok(TestModule::root_certificate_status(issuer)),
+            "status of {}",
+            issuer
+        );
+        assert_eq!(
+            verifier.child_certificate_status(issuer, child),
+            Ok(TestModule::child_certificate_status(issuer, child)),
+            "status of {} issued by {}",
+            child,
+            issuer
+        );
+    }
+}
+
+#[test]
+fn light_verifier_agrees_with_the_pallet() {
+    new_test_ext().execute_with(|| {
+        let pairs = [
+            (OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_2),
+            (OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_3),
+            (OFFCHAIN_CERTIFICATE_SIGNER_1, OFFCHAIN_CERTIFICATE_SIGNER_1),
+            (OFFCHAIN_CERTIFICATE_SIGNER_2, OFFCHAIN_CERTIFICATE_SIGNER_3),
+            (INTERMEDIATE_1, OFFCHAIN_CERTIFICATE_SIGNER_3),
+            (INTERMEDIATE_2, OFFCHAIN_CERTIFICATE_SIGNER_3),
+            (INTERMEDIATE_3, OFFCHAIN_CERTIFICATE_SIGNER_2),
+            (INTERMEDIATE_3, OFFCHAIN_CERTIFICATE_SIGNER_3),
+            (ROOT_MANAGER_2, OFFCHAIN_CERTIFICATE_SIGNER_3),
+        ];
+
+        allocate_balances();
+        do_register();
+        assert_light_verifier_agrees(&pairs);
+
+        do_book_slot_and_intermediates();
+        assert_light_verifier_agrees(&pairs);
+
+        // Revoked and suspended children and intermediates
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            RevocationReason::KeyCompromise
+        ));
+        assert_ok!(TestModule::suspend_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            OFFCHAIN_CERTIFICATE_SIGNER_3
+        ));
+        assert_ok!(TestModule::suspend_child(
+            Origin::signed(ROOT_MANAGER),
+            INTERMEDIATE_1,
+            INTERMEDIATE_2
+        ));
+        assert_light_verifier_agrees(&pairs);
+
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            INTERMEDIATE_1,
+            RevocationReason::CessationOfOperation
+        ));
+        assert_light_verifier_agrees(&pairs);
+
+        // Superseded root and a revocation by its replacement
+        run_to_block(2);
+        assert_ok!(TestModule::book_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            SlotValidity::get(),
+            possession_proof(ROOT_MANAGER, OFFCHAIN_CERTIFICATE_SIGNER_2)
+        ));
+        assert_ok!(TestModule::rotate_root(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_2,
+            INTERMEDIATE_3,
+            RevocationReason::Superseded,
+            possession_proof(ROOT_MANAGER, INTERMEDIATE_3)
+        ));
+        assert_ok!(TestModule::revoke_child(
+            Origin::signed(ROOT_MANAGER),
+            INTERMEDIATE_3,
+            OFFCHAIN_CERTIFICATE_SIGNER_3,
+            RevocationReason::KeyCompromise
+        ));
+        assert_light_verifier_agrees(&pairs);
+
+        // Revoked root, before and after being pruned
+        run_to_block(3);
+        assert_ok!(TestModule::revoke_slot(
+            Origin::signed(ROOT_MANAGER),
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            RevocationReason::KeyCompromise
+        ));
+        assert_light_verifier_agrees(&pairs);
+        run_to_block(3 + SlotGracePeriod::get());
+        assert_eq!(
+            <Slots<Test>>::contains_key(&OFFCHAIN_CERTIFICATE_SIGNER_1),
+            false
+        );
+        assert_light_verifier_agrees(&pairs);
+
+        // Expired root
+        run_to_block(2 + SlotValidity::get());
+        assert_eq!(
+            TestModule::root_certificate_status(&INTERMEDIATE_3),
+            CertificateStatus::Expired
+        );
+        assert_light_verifier_agrees(&pairs);
+
+        // Owner no longer a member
+        <Members<Test>>::put(Vec::<u64>::new());
+        assert_light_verifier_agrees(&pairs);
+    })
+}
+
+#[test]
+fn extrinsics_are_weighted_by_members_and_inputs() {
+    new_test_ext().execute_with(|| {
+        use frame_support::weights::GetDispatchInfo;
+
+        allocate_balances();
+        do_register();
+        do_book_slot_and_intermediates();
+
+        let revoke_children = Call::<Test>::revoke_children(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            vec![OFFCHAIN_CERTIFICATE_SIGNER_2, OFFCHAIN_CERTIFICATE_SIGNER_3],
+            RevocationReason::KeyCompromise,
+        );
+        assert_eq!(
+            revoke_children.get_dispatch_info().weight,
+            <() as WeightInfo>::revoke_children(1, 2)
+        );
+
+        // Both intermediates are updated when rotating the root
+        let rotate_root = Call::<Test>::rotate_root(
+            OFFCHAIN_CERTIFICATE_SIGNER_1,
+            ROOT_MANAGER_2,
+            RevocationReason::Superseded,
+            possession_proof(ROOT_MANAGER, ROOT_MANAGER_2),
+        );
+        assert_eq!(
+            rotate_root.get_dispatch_info().weight,
+            <() as WeightInfo>::rotate_root(1, 2)
+        );
+    })
+}
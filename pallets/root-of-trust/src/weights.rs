@@ -0,0 +1,80 @@
+//! Weights of the root of trust module extrinsics and of the slot maintenance done in
+//! `on_initialize`.
+
+use frame_support::weights::Weight;
+
+/// Weight functions of the module extrinsics, implemented by the runtime with the results
+/// of the benchmarks in `benchmarking.rs`. `m` is the number of members, which are
+/// scanned whenever the validity of a slot is checked.
+pub trait WeightInfo {
+    fn book_slot(m: u32) -> Weight;
+    fn renew_slot(m: u32) -> Weight;
+    fn revoke_slot(m: u32) -> Weight;
+    fn revoke_child(m: u32) -> Weight;
+    /// `c` is the number of children to revoke
+    fn revoke_children(m: u32, c: u32) -> Weight;
+    fn suspend_child(m: u32) -> Weight;
+    fn reinstate_child(m: u32) -> Weight;
+    fn revoke_children_issued_before(m: u32) -> Weight;
+    fn transfer_slot(m: u32) -> Weight;
+    /// `s` is the number of slots of the previous owner
+    fn accept_slot_transfer(m: u32, s: u32) -> Weight;
+    /// `i` is the number of intermediates registered under the rotated root
+    fn rotate_root(m: u32, i: u32) -> Weight;
+    fn add_intermediate(m: u32) -> Weight;
+    /// `s` is the number of slots reaching their expiry in a block
+    fn expire_slots(s: u32) -> Weight;
+    /// `i` is the number of intermediates registered under the pruned slot
+    fn prune_slot(i: u32) -> Weight;
+    /// `s` is the number of slots added to the `SlotsOf` index in a block
+    fn index_slots(s: u32) -> Weight;
+}
+
+/// Rough estimates to be used by test runtimes
+impl WeightInfo for () {
+    fn book_slot(m: u32) -> Weight {
+        200_000 + 100 * m as Weight
+    }
+    fn renew_slot(m: u32) -> Weight {
+        50_000 + 100 * m as Weight
+    }
+    fn revoke_slot(m: u32) -> Weight {
+        50_000 + 100 * m as Weight
+    }
+    fn revoke_child(m: u32) -> Weight {
+        50_000 + 100 * m as Weight
+    }
+    fn revoke_children(m: u32, c: u32) -> Weight {
+        50_000 + 100 * m as Weight + 10_000 * c as Weight
+    }
+    fn suspend_child(m: u32) -> Weight {
+        50_000 + 100 * m as Weight
+    }
+    fn reinstate_child(m: u32) -> Weight {
+        50_000 + 100 * m as Weight
+    }
+    fn revoke_children_issued_before(m: u32) -> Weight {
+        50_000 + 100 * m as Weight
+    }
+    fn transfer_slot(m: u32) -> Weight {
+        50_000 + 200 * m as Weight
+    }
+    fn accept_slot_transfer(m: u32, s: u32) -> Weight {
+        50_000 + 200 * m as Weight + 100 * s as Weight
+    }
+    fn rotate_root(m: u32, i: u32) -> Weight {
+        100_000 + 100 * m as Weight + 10_000 * i as Weight
+    }
+    fn add_intermediate(m: u32) -> Weight {
+        50_000 + 100 * m as Weight
+    }
+    fn expire_slots(s: u32) -> Weight {
+        10_000 * s as Weight
+    }
+    fn prune_slot(i: u32) -> Weight {
+        50_000 + 10_000 * i as Weight
+    }
+    fn index_slots(s: u32) -> Weight {
+        10_000 * s as Weight
+    }
+}
@@ -0,0 +1,189 @@
+//! Benchmarks of the TCR module extrinsics and of the deadlines processed in
+//! `on_initialize`, their results are used to implement `WeightInfo` in the runtime.
+
+use super::*;
+use frame_benchmarking::benchmarks;
+use frame_system::RawOrigin;
+use sp_io::hashing::blake2_256;
+use sp_runtime::traits::{Bounded, OnInitialize};
+
+const MAX_METADATA_LENGTH: u32 = 1_024;
+const MAX_USERS: u32 = 1_000;
+const MAX_DEADLINES: u32 = 100;
+
+fn account<T: Trait>(name: &'static str, index: u32) -> T::AccountId {
+    let entropy = (name, index).using_encoded(blake2_256);
+    T::AccountId::decode(&mut &entropy[..]).unwrap_or_default()
+}
+
+/// An account with enough funds to pay for any deposit
+fn funded<T: Trait>(name: &'static str, index: u32) -> T::AccountId {
+    let who = account::<T>(name, index);
+    T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 2.into());
+    who
+}
+
+/// A member of the registry, added without waiting for `FinalizeApplicationPeriod`
+fn member<T: Trait>(name: &'static str, index: u32) -> T::AccountId {
+    let who = funded::<T>(name, index);
+    let _ = Module::<T>::apply(
+        RawOrigin::Signed(who.clone()).into(),
+        Vec::new(),
+        T::MinimumApplicationAmount::get(),
+    );
+    <Members<T>>::insert(who.clone(), <Applications<T>>::take(who.clone()));
+    who
+}
+
+/// Mark the storage migrations as done, they would otherwise run in the measured
+/// `on_initialize`
+fn migrated() {
+    DeadlinesMigrated::put(true);
+    StakesMigrated::put(true);
+}
+
+/// A member being challenged
+fn challenged<T: Trait>(index: u32) -> T::AccountId {
+    let who = member::<T>("member", index);
+    let challenger = funded::<T>("challenger", index);
+    let _ = Module::<T>::challenge(
+        RawOrigin::Signed(challenger).into(),
+        who.clone(),
+        T::MinimumChallengeAmount::get(),
+    );
+    who
+}
+
+/// Commit `votes` votes supporting `member`, returns the voters
+fn commit_votes<T: Trait>(member: &T::AccountId, votes: u32) -> Vec<T::AccountId> {
+    let deposit: BalanceOf<T> = 100.into();
+
+    (0..votes)
+        .map(|index| {
+            let voter = funded::<T>("voter", index);
+            let commitment =
+                Module::<T>::vote_commitment(&voter, member, true, &T::Hash::default(), deposit);
+            let _ = Module::<T>::commit_vote(
+                RawOrigin::Signed(voter.clone()).into(),
+                member.clone(),
+                commitment,
+                deposit,
+            );
+            voter
+        })
+        .collect()
+}
+
+benchmarks! {
+    _ {
+        let m in 0 .. MAX_METADATA_LENGTH => ();
+        let u in 1 .. MAX_USERS => ();
+        let v in 0 .. T::MaxVoters::get() - 1 => ();
+        let a in 1 .. MAX_DEADLINES => ();
+        let e in 1 .. MAX_DEADLINES => ();
+    }
+
+    apply {
+        let m in ...;
+        let caller = funded::<T>("candidate", m);
+    }: _(RawOrigin::Signed(caller), sp_std::vec![0; m as usize], T::MinimumApplicationAmount::get())
+
+    withdraw_application {
+        let u in ...;
+        let caller = funded::<T>("candidate", u);
+        Module::<T>::apply(RawOrigin::Signed(caller.clone()).into(), Vec::new(), T::MinimumApplicationAmount::get())?;
+    }: _(RawOrigin::Signed(caller))
+
+    leave_registry {
+        let u in ...;
+        let caller = member::<T>("member", u);
+    }: _(RawOrigin::Signed(caller))
+
+    top_up {
+        let u in ...;
+        let caller = member::<T>("member", u);
+    }: _(RawOrigin::Signed(caller), T::MinimumApplicationAmount::get())
+
+    reduce_stake {
+        let u in ...;
+        let caller = member::<T>("member", u);
+        Module::<T>::top_up(RawOrigin::Signed(caller.clone()).into(), T::MinimumApplicationAmount::get())?;
+    }: _(RawOrigin::Signed(caller), T::MinimumApplicationAmount::get())
+
+    counter {
+        let u in ...;
+        let candidate = funded::<T>("candidate", u);
+        Module::<T>::apply(RawOrigin::Signed(candidate.clone()).into(), Vec::new(), T::MinimumApplicationAmount::get())?;
+        let caller = funded::<T>("challenger", u);
+    }: _(RawOrigin::Signed(caller), candidate, T::MinimumCounterAmount::get())
+
+    // Every commitment is checked to prevent double votes
+    commit_vote {
+        let v in ...;
+        let member = challenged::<T>(0);
+        commit_votes::<T>(&member, v);
+        let caller = funded::<T>("caller", v);
+        let deposit: BalanceOf<T> = 100.into();
+        let commitment = Module::<T>::vote_commitment(&caller, &member, true, &T::Hash::default(), deposit);
+    }: _(RawOrigin::Signed(caller), member, commitment, deposit)
+
+    // The vote is added after the `v` votes revealed by the other voters
+    reveal_vote {
+        let v in ...;
+        let member = challenged::<T>(0);
+        let voters = commit_votes::<T>(&member, v + 1);
+        let challenged_block = <Challenges<T>>::get(member.clone()).challenged_block;
+        <system::Module<T>>::set_block_number(challenged_block + T::CommitPeriod::get());
+        for voter in voters.iter().skip(1) {
+            Module::<T>::reveal_vote(RawOrigin::Signed(voter.clone()).into(), member.clone(), true, T::Hash::default())?;
+        }
+    }: _(RawOrigin::Signed(voters[0].clone()), member, true, T::Hash::default())
+
+    challenge {
+        let u in ...;
+        let member = member::<T>("member", u);
+        let caller = funded::<T>("challenger", u);
+    }: _(RawOrigin::Signed(caller), member, T::MinimumChallengeAmount::get())
+
+    // Every application reaching its deadline becomes a member
+    commit_applications {
+        let a in ...;
+        migrated();
+        for index in 0..a {
+            let candidate = funded::<T>("candidate", index);
+            Module::<T>::apply(RawOrigin::Signed(candidate).into(), Vec::new(), T::MinimumApplicationAmount::get())?;
+        }
+        let block = <system::Module<T>>::block_number() + T::FinalizeApplicationPeriod::get();
+    }: {
+        <Module<T> as OnInitialize<T::BlockNumber>>::on_initialize(block);
+    }
+
+    // Every revealed vote is settled
+    resolve_challenge {
+        let v in ...;
+        migrated();
+        let member = challenged::<T>(0);
+        let voters = commit_votes::<T>(&member, v);
+        let challenged_block = <Challenges<T>>::get(member.clone()).challenged_block;
+        <system::Module<T>>::set_block_number(challenged_block + T::CommitPeriod::get());
+        for voter in voters {
+            Module::<T>::reveal_vote(RawOrigin::Signed(voter).into(), member.clone(), true, T::Hash::default())?;
+        }
+        let block = challenged_block + T::FinalizeChallengePeriod::get();
+    }: {
+        <Module<T> as OnInitialize<T::BlockNumber>>::on_initialize(block);
+    }
+
+    // Every member reaching the end of its exit cooldown leaves the registry
+    process_exits {
+        let e in ...;
+        migrated();
+        for index in 0..e {
+            let who = member::<T>("member", index);
+            Module::<T>::leave_registry(RawOrigin::Signed(who).into())?;
+        }
+        let block = <system::Module<T>>::block_number() + T::ExitCooldown::get();
+    }: {
+        <Module<T> as OnInitialize<T::BlockNumber>>::on_initialize(block);
+    }
+}
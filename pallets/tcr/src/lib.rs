@@ -4,8 +4,12 @@
 //! `AccountId`) are accepted based on the number of tokens staked in support to their
 //! application.
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+mod migration;
 #[cfg(test)]
 mod tests;
+mod weights;
 
 use codec::{Decode, Encode};
 use frame_support::{
@@ -13,10 +17,15 @@ use frame_support::{
     dispatch::{result::Result, DispatchError, DispatchResult},
     ensure,
     traits::{ChangeMembers, Currency, Get, Imbalance, ReservableCurrency},
+    weights::{DispatchClass, FunctionOf, SimpleDispatchInfo, Weight},
 };
 use frame_system::{self as system, ensure_signed};
-use sp_runtime::{traits::CheckedAdd, Perbill};
+use sp_runtime::{
+    traits::{CheckedAdd, CheckedSub, Hash, One, Zero},
+    Perbill,
+};
 use sp_std::prelude::Vec;
+pub use weights::WeightInfo;
 
 type BalanceOf<T> = <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
 type NegativeImbalanceOf<T> =
@@ -24,6 +33,12 @@ type NegativeImbalanceOf<T> =
 type PositiveImbalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as system::Trait>::AccountId>>::PositiveImbalance;
 
+/// Weight charged once for every application, challenge and member visited by the
+/// deadlines migration
+const DEADLINE_MIGRATION_WEIGHT: Weight = 10_000;
+/// Weight charged once for every member visited by the stakes migration
+const STAKE_MIGRATION_WEIGHT: Weight = 10_000;
+
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 pub struct Application<AccountId, Balance, BlockNumber> {
     candidate: AccountId,
@@ -42,13 +57,24 @@ pub struct Application<AccountId, Balance, BlockNumber> {
     challenged_block: BlockNumber,
 }
 
+/// A vote committed during a challenge, `commitment` is the hash of the voter, the
+/// challenged member, the vote choice, a salt and the deposit which stays hidden until
+/// revealed
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct VoteCommitment<AccountId, Hash, Balance> {
+    voter: AccountId,
+    commitment: Hash,
+    deposit: Balance,
+}
+
 /// The module's configuration trait.
 pub trait Trait: system::Trait {
     type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
 
     /// The currency used to represent the voting power
     type Currency: ReservableCurrency<Self::AccountId>;
-    /// Minimum amount of tokens required to apply
+    /// Minimum amount of tokens required to apply, members need to keep at least as
+    /// much staked
     type MinimumApplicationAmount: Get<BalanceOf<Self>>;
     /// Minimum amount of tokens required to counter an application
     type MinimumCounterAmount: Get<BalanceOf<Self>>;
@@ -58,9 +84,28 @@ pub trait Trait: system::Trait {
     type FinalizeApplicationPeriod: Get<Self::BlockNumber>;
     /// How many blocks we need to wait for before finalizing a challenge
     type FinalizeChallengePeriod: Get<Self::BlockNumber>;
+    /// How many blocks after a challenge starts votes can be committed, they can then
+    /// be revealed until `FinalizeChallengePeriod` ends. Needs to be shorter than
+    /// `FinalizeChallengePeriod`
+    type CommitPeriod: Get<Self::BlockNumber>;
     /// How do we slash loosing parties when challenges are finalized, application's
     /// member will be slashed at the same value
     type LoosersSlash: Get<Perbill>;
+    /// How much of their deposit voters who did not reveal their vote lose
+    type UnrevealedSlash: Get<Perbill>;
+    /// How many blocks members wait for between asking to leave the registry and being
+    /// removed, during which they can still be challenged. Members leave immediately
+    /// when set to zero
+    type ExitCooldown: Get<Self::BlockNumber>;
+    /// Maximum number of votes, revealed or not, a challenge can receive. Bounds the
+    /// cost of voting and of resolving the challenge
+    type MaxVoters: Get<u32>;
+    /// Maximum number of storage entries visited per block by storage migrations,
+    /// remaining entries are migrated in the following blocks
+    type MaxMigratedPerBlock: Get<u32>;
+    /// Weights of the extrinsics and of the deadlines processed in `on_initialize`,
+    /// typically generated from `benchmarking.rs`
+    type WeightInfo: WeightInfo;
     /// Hook that we call whenever some members are added or removed from the TCR
     type ChangeMembers: ChangeMembers<Self::AccountId>;
 }
@@ -70,13 +115,18 @@ decl_event!(
     where
         AccountId = <T as system::Trait>::AccountId,
         Balance = BalanceOf<T>,
+        BlockNumber = <T as system::Trait>::BlockNumber,
     {
         /// Someone applied to join the registry
         NewApplication(AccountId, Balance),
         /// Someone countered an application
         ApplicationCountered(AccountId, AccountId, Balance),
+        /// A vote was committed for an application, its choice is hidden until revealed
+        VoteCommitted(AccountId, AccountId, Balance),
         /// A new vote for an application has been recorded
         VoteRecorded(AccountId, AccountId, Balance, bool),
+        /// A committed vote was not revealed in time, the voter was slashed
+        VoteNotRevealed(AccountId, AccountId, Balance),
         /// An application passed without being countered
         ApplicationPassed(AccountId),
         /// A member's application is being challenged
@@ -85,6 +135,15 @@ decl_event!(
         ChallengeRefusedApplication(AccountId),
         /// A challenge accepted the application
         ChallengeAcceptedApplication(AccountId),
+        /// A candidate withdrew its application and got its deposit back
+        ApplicationWithdrawn(AccountId, Balance),
+        /// A member asked to leave the registry, it will be removed at the given block
+        /// unless it is being challenged
+        ExitRequested(AccountId, BlockNumber),
+        /// A member left the registry
+        MemberLeft(AccountId),
+        /// A member changed its stake, fields are the member and its new stake
+        StakeChanged(AccountId, Balance),
     }
 );
 
@@ -104,6 +163,26 @@ decl_error! {
         ChallengeNotFound,
         /// The account id is not a member
         MemberNotFound,
+        /// The account id is already a member, its stake can be increased with `top_up`
+        AlreadyMember,
+        /// Votes can no longer be committed for this challenge
+        CommitPeriodOver,
+        /// A vote was already committed for this challenge
+        AlreadyCommitted,
+        /// Votes can not be revealed before the end of the commit period or after the
+        /// challenge is finalized
+        NotRevealPeriod,
+        /// No vote was committed for this challenge
+        CommitmentNotFound,
+        /// The revealed vote does not match the commitment
+        CommitmentMismatch,
+        /// The member already asked to leave the registry
+        ExitPending,
+        /// The challenge already received `MaxVoters` votes
+        TooManyVoters,
+        /// Member stakes are being migrated, they can not be changed until the migration
+        /// is over
+        MigrationInProgress,
 
         ReserveOverflow,
         UnreserveOverflow,
@@ -115,6 +194,27 @@ decl_storage! {
         Applications get(applications): linked_map hasher(blake2_256) T::AccountId => Application<T::AccountId, BalanceOf<T>, T::BlockNumber>;
         Challenges get(challenges): linked_map hasher(blake2_256) T::AccountId => Application<T::AccountId, BalanceOf<T>, T::BlockNumber>;
         Members get(members): linked_map hasher(blake2_256) T::AccountId => Application<T::AccountId, BalanceOf<T>, T::BlockNumber>;
+        /// Votes committed for a challenge which were not revealed yet
+        Commitments get(commitments): map hasher(blake2_256) T::AccountId => Vec<VoteCommitment<T::AccountId, T::Hash, BalanceOf<T>>>;
+        /// Members who asked to leave the registry, mapped to the block they leave at
+        Exits get(exits): linked_map hasher(blake2_256) T::AccountId => T::BlockNumber;
+        /// Applications reaching the end of `FinalizeApplicationPeriod` at a given block,
+        /// entries are not removed when an application is withdrawn or countered so they
+        /// need to be checked against `Applications`
+        ApplicationDeadlines get(application_deadlines): map hasher(blake2_256) T::BlockNumber => Vec<T::AccountId>;
+        /// Challenges reaching the end of `FinalizeChallengePeriod` at a given block
+        ChallengeDeadlines get(challenge_deadlines): map hasher(blake2_256) T::BlockNumber => Vec<T::AccountId>;
+        /// Members leaving the registry at a given block
+        ExitDeadlines get(exit_deadlines): map hasher(blake2_256) T::BlockNumber => Vec<T::AccountId>;
+        /// Whether the applications, challenges and exits pending before the deadlines
+        /// were introduced were scheduled
+        DeadlinesMigrated get(deadlines_migrated): bool;
+        /// Whether the deposits of the members admitted while deposits were unreserved on
+        /// admission were reserved again
+        StakesMigrated get(stakes_migrated): bool;
+        /// Raw storage key of the last entry migrated by the storage migration in
+        /// progress, if any
+        MigrationCursor get(migration_cursor): Option<Vec<u8>>;
     }
 }
 
@@ -123,14 +223,22 @@ decl_module! {
     pub struct Module<T: Trait> for enum Call where origin: T::Origin {
         fn deposit_event() = default;
 
+        #[weight = FunctionOf(
+            |args: (&Vec<u8>, &BalanceOf<T>)| T::WeightInfo::apply(args.0.len() as u32),
+            DispatchClass::Normal,
+            true
+        )]
         pub fn apply(origin, metadata: Vec<u8>, deposit: BalanceOf<T>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(deposit >= T::MinimumApplicationAmount::get(), Error::<T>::DepositTooSmall);
             ensure!(!<Applications<T>>::contains_key(sender.clone()), Error::<T>::ApplicationPending);
             ensure!(!<Challenges<T>>::contains_key(sender.clone()), Error::<T>::ApplicationChallenged);
+            ensure!(!<Members<T>>::contains_key(sender.clone()), Error::<T>::AlreadyMember);
 
             Self::reserve_for(sender.clone(), deposit)?;
 
+            let now = <system::Module<T>>::block_number();
+            <ApplicationDeadlines<T>>::mutate(now + T::FinalizeApplicationPeriod::get(), |accounts| accounts.push(sender.clone()));
             <Applications<T>>::insert(sender.clone(), Application {
                 candidate: sender.clone(),
                 candidate_deposit: deposit,
@@ -144,7 +252,7 @@ decl_module! {
                 votes_against: None,
                 voters_against: Vec::new(),
 
-                created_block: <system::Module<T>>::block_number(),
+                created_block: now,
                 challenged_block: 0.into(),
             });
 
@@ -152,7 +260,89 @@ decl_module! {
             Ok(())
         }
 
+        /// Withdraw a pending application which was not countered and get the deposit back
+        #[weight = SimpleDispatchInfo::FixedNormal(T::WeightInfo::withdraw_application())]
+        pub fn withdraw_application(origin) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            ensure!(!<Challenges<T>>::contains_key(sender.clone()), Error::<T>::ApplicationChallenged);
+            ensure!(<Applications<T>>::contains_key(sender.clone()), Error::<T>::ApplicationNotFound);
+
+            let application = <Applications<T>>::take(sender.clone());
+            Self::unreserve_for(sender.clone(), application.candidate_deposit)?;
+
+            Self::deposit_event(RawEvent::ApplicationWithdrawn(sender, application.candidate_deposit));
+            Ok(())
+        }
+
+        /// Leave the registry, members are removed once `ExitCooldown` is over and any
+        /// challenge against them is resolved
+        #[weight = SimpleDispatchInfo::FixedNormal(T::WeightInfo::leave_registry())]
+        pub fn leave_registry(origin) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+            ensure!(<Members<T>>::contains_key(sender.clone()), Error::<T>::MemberNotFound);
+            ensure!(!<Challenges<T>>::contains_key(sender.clone()), Error::<T>::ApplicationChallenged);
+            ensure!(!<Exits<T>>::contains_key(sender.clone()), Error::<T>::ExitPending);
+
+            if T::ExitCooldown::get().is_zero() {
+                let member = <Members<T>>::take(sender.clone());
+                Self::unreserve_for(sender.clone(), member.candidate_deposit)?;
+                Self::notify_members_change(Vec::new(), sp_std::vec![sender.clone()]);
+
+                Self::deposit_event(RawEvent::MemberLeft(sender));
+            } else {
+                let exit_block = <system::Module<T>>::block_number() + T::ExitCooldown::get();
+                <Exits<T>>::insert(sender.clone(), exit_block);
+                <ExitDeadlines<T>>::mutate(exit_block, |accounts| accounts.push(sender.clone()));
+
+                Self::deposit_event(RawEvent::ExitRequested(sender, exit_block));
+            }
+
+            Ok(())
+        }
+
+        /// Increase the stake of a member by `amount`
+        #[weight = SimpleDispatchInfo::FixedNormal(T::WeightInfo::top_up())]
+        pub fn top_up(origin, amount: BalanceOf<T>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+            ensure!(<Members<T>>::contains_key(sender.clone()), Error::<T>::MemberNotFound);
+            ensure!(!<Challenges<T>>::contains_key(sender.clone()), Error::<T>::ApplicationChallenged);
+
+            let mut member = <Members<T>>::get(sender.clone());
+            let stake = member.candidate_deposit.checked_add(&amount).ok_or(Error::<T>::ReserveOverflow)?;
+            Self::reserve_for(sender.clone(), amount)?;
+
+            member.candidate_deposit = stake;
+            <Members<T>>::insert(sender.clone(), member);
+
+            Self::deposit_event(RawEvent::StakeChanged(sender, stake));
+            Ok(())
+        }
+
+        /// Decrease the stake of a member by `amount`, the stake can not go below
+        /// `MinimumApplicationAmount`
+        #[weight = SimpleDispatchInfo::FixedNormal(T::WeightInfo::reduce_stake())]
+        pub fn reduce_stake(origin, amount: BalanceOf<T>) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            Self::ensure_migrated()?;
+            ensure!(<Members<T>>::contains_key(sender.clone()), Error::<T>::MemberNotFound);
+            ensure!(!<Challenges<T>>::contains_key(sender.clone()), Error::<T>::ApplicationChallenged);
+
+            let mut member = <Members<T>>::get(sender.clone());
+            let stake = member.candidate_deposit.checked_sub(&amount).ok_or(Error::<T>::UnreserveOverflow)?;
+            ensure!(stake >= T::MinimumApplicationAmount::get(), Error::<T>::DepositTooSmall);
+            Self::unreserve_for(sender.clone(), amount)?;
+
+            member.candidate_deposit = stake;
+            <Members<T>>::insert(sender.clone(), member);
+
+            Self::deposit_event(RawEvent::StakeChanged(sender, stake));
+            Ok(())
+        }
+
         /// Counter a pending application, this will initiate a challenge
+        #[weight = SimpleDispatchInfo::FixedNormal(T::WeightInfo::counter())]
         pub fn counter(origin, member: T::AccountId, deposit: BalanceOf<T>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(deposit >= T::MinimumCounterAmount::get(), Error::<T>::DepositTooSmall);
@@ -165,19 +355,72 @@ decl_module! {
             application.challenger_deposit = Some(deposit);
             application.challenged_block = <system::Module<T>>::block_number();
 
+            Self::schedule_challenge(&member, application.challenged_block);
             <Challenges<T>>::insert(member.clone(), application);
 
             Self::deposit_event(RawEvent::ApplicationCountered(member, sender, deposit));
             Ok(())
         }
 
-        /// Vote in support or opposition of a given challenge
-        pub fn vote(origin, member: T::AccountId, supporting: bool, deposit: BalanceOf<T>) -> DispatchResult {
+        /// Commit a vote for a given challenge, `commitment` is the hash of the sender,
+        /// `member`, the vote choice, a salt and `deposit` as computed by
+        /// `vote_commitment`, so that it can not be copied by other voters. The vote needs
+        /// to be revealed with `reveal_vote` once `CommitPeriod` is over.
+        #[weight = SimpleDispatchInfo::FixedNormal(T::WeightInfo::commit_vote(T::MaxVoters::get()))]
+        pub fn commit_vote(origin, member: T::AccountId, commitment: T::Hash, deposit: BalanceOf<T>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(<Challenges<T>>::contains_key(member.clone()), Error::<T>::ChallengeNotFound);
 
+            let challenge = <Challenges<T>>::get(member.clone());
+            let now = <system::Module<T>>::block_number();
+            ensure!(now < challenge.challenged_block + T::CommitPeriod::get(), Error::<T>::CommitPeriodOver);
+
+            let mut commitments = <Commitments<T>>::get(member.clone());
+            ensure!(!commitments.iter().any(|c| c.voter == sender), Error::<T>::AlreadyCommitted);
+            let votes = commitments.len() + challenge.voters_for.len() + challenge.voters_against.len();
+            ensure!(votes < T::MaxVoters::get() as usize, Error::<T>::TooManyVoters);
+
             Self::reserve_for(sender.clone(), deposit)?;
 
+            commitments.push(VoteCommitment {
+                voter: sender.clone(),
+                commitment: commitment,
+                deposit: deposit,
+            });
+            <Commitments<T>>::insert(member.clone(), commitments);
+
+            Self::deposit_event(RawEvent::VoteCommitted(member, sender, deposit));
+            Ok(())
+        }
+
+        /// Reveal a vote committed with `commit_vote`, only revealed votes are counted
+        /// when the challenge is finalized
+        #[weight = SimpleDispatchInfo::FixedNormal(T::WeightInfo::reveal_vote(T::MaxVoters::get()))]
+        pub fn reveal_vote(origin, member: T::AccountId, supporting: bool, salt: T::Hash) -> DispatchResult {
+            let sender = ensure_signed(origin)?;
+            ensure!(<Challenges<T>>::contains_key(member.clone()), Error::<T>::ChallengeNotFound);
+
+            let challenged_block = <Challenges<T>>::get(member.clone()).challenged_block;
+            let now = <system::Module<T>>::block_number();
+            ensure!(
+                now >= challenged_block + T::CommitPeriod::get()
+                    && now < challenged_block + T::FinalizeChallengePeriod::get(),
+                Error::<T>::NotRevealPeriod
+            );
+
+            let mut commitments = <Commitments<T>>::get(member.clone());
+            let index = commitments
+                .iter()
+                .position(|c| c.voter == sender)
+                .ok_or(Error::<T>::CommitmentNotFound)?;
+            let deposit = commitments[index].deposit;
+            ensure!(
+                commitments[index].commitment == Self::vote_commitment(&sender, &member, supporting, &salt, deposit),
+                Error::<T>::CommitmentMismatch
+            );
+            commitments.swap_remove(index);
+            <Commitments<T>>::insert(member.clone(), commitments);
+
             let mut application = <Challenges<T>>::take(member.clone());
 
             if supporting {
@@ -195,6 +438,7 @@ decl_module! {
         }
 
         /// Trigger a new challenge to remove an existing member
+        #[weight = SimpleDispatchInfo::FixedNormal(T::WeightInfo::challenge())]
         pub fn challenge(origin, member: T::AccountId, deposit: BalanceOf<T>) -> DispatchResult {
             let sender = ensure_signed(origin)?;
             ensure!(deposit >= T::MinimumChallengeAmount::get(), Error::<T>::DepositTooSmall);
@@ -211,32 +455,113 @@ decl_module! {
             application.votes_against = None;
             application.voters_against = Vec::new();
 
+            Self::schedule_challenge(&member, application.challenged_block);
             <Challenges<T>>::insert(member.clone(), application);
 
             Self::deposit_event(RawEvent::ApplicationChallenged(member, sender, deposit));
             Ok(())
         }
 
-        /// At the end of each blocks, commit applications or challenges as needed
-        fn on_finalize(block: T::BlockNumber) {
-            let (mut new_1, mut old_1) = Self::commit_applications(block).unwrap_or((Vec::new(), Vec::new()));
-            let (new_2, old_2) = Self::resolve_challenges(block).unwrap_or((Vec::new(), Vec::new()));
+        /// At the beginning of each block, commit the applications, resolve the challenges
+        /// and process the exits reaching their deadline. Storage migrations visit at most
+        /// `MaxMigratedPerBlock` entries per block, deadlines are postponed until member
+        /// stakes are migrated.
+        fn on_initialize(block: T::BlockNumber) -> Weight {
+            let budget = T::MaxMigratedPerBlock::get();
+            let mut migrated = 0;
+            if !Self::deadlines_migrated() {
+                let (used, done) = migration::schedule_pending_deadlines::<T>(block, budget);
+                migrated = used;
+                DeadlinesMigrated::put(done);
+            }
+            // Before any application is committed, their deposits are already reserved
+            let mut staked = 0;
+            if Self::deadlines_migrated() && !Self::stakes_migrated() {
+                let (used, done) = migration::reserve_member_stakes::<T>(budget.saturating_sub(migrated));
+                staked = used;
+                StakesMigrated::put(done);
+            }
+            let migration_weight = DEADLINE_MIGRATION_WEIGHT * migrated as Weight
+                + STAKE_MIGRATION_WEIGHT * staked as Weight;
+            if Self::is_migrating() {
+                Self::postpone_deadlines(block);
+                return migration_weight;
+            }
+
+            let applications = <ApplicationDeadlines<T>>::take(block);
+            let challenges = <ChallengeDeadlines<T>>::take(block);
+            let exits = <ExitDeadlines<T>>::take(block);
+            let weight = migration_weight
+                + T::WeightInfo::commit_applications(applications.len() as u32)
+                + challenges.iter().map(Self::challenge_weight).sum::<Weight>()
+                + T::WeightInfo::process_exits(exits.len() as u32);
+
+            let (mut new_1, mut old_1) = Self::commit_applications(block, applications).unwrap_or((Vec::new(), Vec::new()));
+            let (new_2, old_2) = Self::resolve_challenges(block, challenges).unwrap_or((Vec::new(), Vec::new()));
+            let old_3 = Self::process_exits(block, exits);
 
             // TODO: optimise all those array operations
 
             // Should never be the same, so should not need some uniq checks
             new_1.extend(new_2.clone());
             old_1.extend(old_2.clone());
+            old_1.extend(old_3);
 
             new_1.sort();
             old_1.sort();
 
             Self::notify_members_change(new_1, old_1);
+
+            weight
         }
     }
 }
 
 impl<T: Trait> Module<T> {
+    /// Commitment to be submitted to `commit_vote` by `voter`, the hash of the SCALE
+    /// encoded voter, challenged member, vote choice, salt and deposit
+    pub fn vote_commitment(
+        voter: &T::AccountId,
+        member: &T::AccountId,
+        supporting: bool,
+        salt: &T::Hash,
+        deposit: BalanceOf<T>,
+    ) -> T::Hash {
+        T::Hashing::hash_of(&(voter, member, supporting, salt, deposit))
+    }
+
+    /// Whether pending deadlines are being scheduled or member stakes reserved
+    fn is_migrating() -> bool {
+        !Self::deadlines_migrated() || !Self::stakes_migrated()
+    }
+
+    /// Make sure that member stakes are not being migrated, their deposits would
+    /// otherwise be reserved or released twice
+    fn ensure_migrated() -> DispatchResult {
+        ensure!(!Self::is_migrating(), Error::<T>::MigrationInProgress);
+
+        Ok(())
+    }
+
+    /// Move the deadlines reaching `block` to the next block, members are neither
+    /// admitted nor removed while their stakes are migrated
+    fn postpone_deadlines(block: T::BlockNumber) {
+        let next = block + One::one();
+
+        let applications = <ApplicationDeadlines<T>>::take(block);
+        if !applications.is_empty() {
+            <ApplicationDeadlines<T>>::mutate(next, |accounts| accounts.extend(applications));
+        }
+        let challenges = <ChallengeDeadlines<T>>::take(block);
+        if !challenges.is_empty() {
+            <ChallengeDeadlines<T>>::mutate(next, |accounts| accounts.extend(challenges));
+        }
+        let exits = <ExitDeadlines<T>>::take(block);
+        if !exits.is_empty() {
+            <ExitDeadlines<T>>::mutate(next, |accounts| accounts.extend(exits));
+        }
+    }
+
     /// Do not just call `set_lock`, rather increase the locked amount
     fn reserve_for(who: T::AccountId, amount: BalanceOf<T>) -> DispatchResult {
         // Make sure we can lock has many funds
@@ -264,6 +589,17 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// Takes some funds away from a voter who did not reveal their vote
+    fn slash_unrevealed(who: T::AccountId, amount: BalanceOf<T>) -> NegativeImbalanceOf<T> {
+        let to_be_slashed = T::UnrevealedSlash::get() * amount;
+        if T::Currency::can_slash(&who, to_be_slashed) {
+            let (imbalance, _remaining) = T::Currency::slash(&who, to_be_slashed);
+            imbalance
+        } else {
+            <NegativeImbalanceOf<T>>::zero()
+        }
+    }
+
     /// Number of tokens supporting a given application
     fn get_supporting(
         application: Application<T::AccountId, BalanceOf<T>, T::BlockNumber>,
@@ -293,20 +629,49 @@ impl<T: Trait> Module<T> {
         }
     }
 
+    /// Resolve the challenge against `member` once `FinalizeChallengePeriod` is over
+    fn schedule_challenge(member: &T::AccountId, challenged_block: T::BlockNumber) {
+        <ChallengeDeadlines<T>>::mutate(
+            challenged_block + T::FinalizeChallengePeriod::get(),
+            |accounts| accounts.push(member.clone()),
+        );
+    }
+
+    /// Weight of resolving the challenge against `member`, which settles every vote
+    fn challenge_weight(member: &T::AccountId) -> Weight {
+        if !<Challenges<T>>::contains_key(member) {
+            return 0;
+        }
+
+        let challenge = <Challenges<T>>::get(member);
+        let votes = challenge.voters_for.len()
+            + challenge.voters_against.len()
+            + <Commitments<T>>::decode_len(member).unwrap_or(0);
+
+        T::WeightInfo::resolve_challenge(votes as u32)
+    }
+
+    /// Move the `applications` whose deadline is `block` and which were not countered to
+    /// `Members`
     fn commit_applications(
         block: T::BlockNumber,
+        applications: Vec<T::AccountId>,
     ) -> Result<(Vec<T::AccountId>, Vec<T::AccountId>), DispatchError> {
         let mut new_members = Vec::new();
 
-        for (account_id, application) in <Applications<T>>::enumerate() {
-            if block - application.clone().created_block >= T::FinalizeApplicationPeriod::get() {
+        for account_id in applications {
+            if !<Applications<T>>::contains_key(account_id.clone()) {
+                continue;
+            }
+
+            let application = <Applications<T>>::get(account_id.clone());
+            if block >= application.clone().created_block + T::FinalizeApplicationPeriod::get() {
                 // In the case of a commited application, we only move the structure
-                // to the last list.
+                // to the last list. The deposit stays reserved as the member's stake.
 
                 <Applications<T>>::remove(account_id.clone());
                 <Members<T>>::insert(account_id.clone(), application.clone());
 
-                Self::unreserve_for(account_id.clone(), application.clone().candidate_deposit)?;
                 new_members.push(account_id.clone());
 
                 Self::deposit_event(RawEvent::ApplicationPassed(account_id));
@@ -316,20 +681,29 @@ impl<T: Trait> Module<T> {
         Ok((new_members, Vec::new()))
     }
 
+    /// Settle the `challenges` whose deadline is `block`
     fn resolve_challenges(
         block: T::BlockNumber,
+        challenges: Vec<T::AccountId>,
     ) -> Result<(Vec<T::AccountId>, Vec<T::AccountId>), DispatchError> {
         let mut new_members = Vec::new();
         let mut old_members = Vec::new();
 
-        for (account_id, application) in <Challenges<T>>::enumerate() {
-            if block - application.clone().challenged_block >= T::FinalizeChallengePeriod::get() {
+        for account_id in challenges {
+            if !<Challenges<T>>::contains_key(account_id.clone()) {
+                continue;
+            }
+
+            // The member may have been challenged again, the new challenge has its own
+            // deadline
+            let application = <Challenges<T>>::get(account_id.clone());
+            if block >= application.clone().challenged_block + T::FinalizeChallengePeriod::get() {
                 let mut to_slash: Vec<(T::AccountId, BalanceOf<T>)>;
                 let mut to_reward: Vec<(T::AccountId, BalanceOf<T>)>;
+                let accepted = Self::get_supporting(application.clone())
+                    > Self::get_opposing(application.clone());
 
-                if Self::get_supporting(application.clone())
-                    > Self::get_opposing(application.clone())
-                {
+                if accepted {
                     <Members<T>>::insert(account_id.clone(), application.clone());
                     new_members.push(application.clone().candidate);
 
@@ -354,6 +728,7 @@ impl<T: Trait> Module<T> {
                     // If it is a member, remove it
                     if <Members<T>>::contains_key(application.clone().candidate) {
                         <Members<T>>::remove(application.clone().candidate);
+                        <Exits<T>>::remove(application.clone().candidate);
                         old_members.push(application.clone().candidate);
                     }
 
@@ -388,12 +763,29 @@ impl<T: Trait> Module<T> {
                     slashes_imbalance.subsume(r);
                 }
 
+                // Votes that were never revealed are not counted, their voters are slashed
+                // and the proceeds go to the winning side
+                for commitment in <Commitments<T>>::take(account_id.clone()) {
+                    Self::unreserve_for(commitment.voter.clone(), commitment.deposit)?;
+                    let r = Self::slash_unrevealed(commitment.voter.clone(), commitment.deposit);
+                    slashes_imbalance.subsume(r);
+
+                    Self::deposit_event(RawEvent::VoteNotRevealed(
+                        account_id.clone(),
+                        commitment.voter,
+                        commitment.deposit,
+                    ));
+                }
+
                 // Execute rewards
                 let mut rewards_imbalance = <PositiveImbalanceOf<T>>::zero();
                 let rewards_pool = slashes_imbalance.peek();
                 let mut allocated = 0.into();
                 for (account_id, deposit) in to_reward.clone() {
-                    Self::unreserve_for(account_id.clone(), deposit)?;
+                    // An accepted candidate keeps its deposit staked
+                    if !accepted || account_id != application.candidate {
+                        Self::unreserve_for(account_id.clone(), deposit)?;
+                    }
 
                     // deposit          deposit * pool
                     // ------- * pool = --------------
@@ -421,6 +813,40 @@ impl<T: Trait> Module<T> {
         Ok((new_members, old_members))
     }
 
+    /// Remove the `exits` members whose exit cooldown is over at `block`, members being
+    /// challenged stay until their challenge is resolved
+    fn process_exits(block: T::BlockNumber, exits: Vec<T::AccountId>) -> Vec<T::AccountId> {
+        let mut old_members = Vec::new();
+
+        for account_id in exits {
+            if !<Exits<T>>::contains_key(account_id.clone()) {
+                continue;
+            }
+
+            if <Challenges<T>>::contains_key(account_id.clone()) {
+                // Try again once the challenge is resolved, challenges are resolved before
+                // exits
+                let deadline = <Challenges<T>>::get(account_id.clone()).challenged_block
+                    + T::FinalizeChallengePeriod::get();
+                <ExitDeadlines<T>>::mutate(deadline.max(block + One::one()), |accounts| {
+                    accounts.push(account_id)
+                });
+                continue;
+            }
+
+            if block >= Self::exits(account_id.clone()) {
+                <Exits<T>>::remove(account_id.clone());
+                let member = <Members<T>>::take(account_id.clone());
+                let _ = Self::unreserve_for(account_id.clone(), member.candidate_deposit);
+                old_members.push(account_id.clone());
+
+                Self::deposit_event(RawEvent::MemberLeft(account_id));
+            }
+        }
+
+        old_members
+    }
+
     fn notify_members_change(new_members: Vec<T::AccountId>, old_members: Vec<T::AccountId>) {
         if new_members.len() > 0 || old_members.len() > 0 {
             let mut sorted_members = <Members<T>>::enumerate()
@@ -0,0 +1,124 @@
+//! Storage migrations of the TCR module.
+
+use super::*;
+
+/// Prefix of the raw storage keys of the entries of the linked map `name`, each key is
+/// followed by the `blake2_256` hash of the account id
+fn linked_map_prefix(name: &[u8]) -> [u8; 32] {
+    let mut prefix = [0u8; 32];
+    prefix[..16].copy_from_slice(&sp_io::hashing::twox_128(b"TcrModule"));
+    prefix[16..].copy_from_slice(&sp_io::hashing::twox_128(name));
+    prefix
+}
+
+/// Call `migrate` on the raw entries stored under `prefixes`, one prefix after the
+/// other, resuming after the key saved in `MigrationCursor`. Linked map values start
+/// with the value itself, followed by the keys of the neighbouring entries, so
+/// `migrate` can decode them as plain values. Every entry uses one unit of `budget`.
+/// Returns the budget used and whether every entry was migrated, the cursor being
+/// cleared in that case.
+fn migrate_entries<F>(prefixes: &[[u8; 32]], budget: u32, mut migrate: F) -> (u32, bool)
+where
+    F: FnMut(&[u8], Vec<u8>),
+{
+    let cursor = MigrationCursor::get();
+    let mut stage = cursor
+        .as_ref()
+        .and_then(|key| prefixes.iter().position(|prefix| key.starts_with(prefix)))
+        .unwrap_or(0);
+    let mut key = cursor.unwrap_or_else(|| prefixes[stage].to_vec());
+    let mut used = 0;
+
+    while used < budget {
+        let next = sp_io::storage::next_key(&key).filter(|next| next.starts_with(&prefixes[stage]));
+        match next {
+            Some(next) => {
+                let value = sp_io::storage::get(&next).unwrap_or_default();
+                migrate(&next, value);
+                used += 1;
+                key = next;
+            }
+            None if stage + 1 < prefixes.len() => {
+                stage += 1;
+                key = prefixes[stage].to_vec();
+            }
+            None => {
+                MigrationCursor::kill();
+                return (used, true);
+            }
+        }
+    }
+    MigrationCursor::put(key);
+
+    (used, false)
+}
+
+/// Schedule the applications, challenges and exits pending before the deadline
+/// schedules were introduced, at most `budget` entries at a time. Entries whose
+/// deadline already passed are scheduled at `block` so they are processed right away.
+/// Exits are scheduled along with the members leaving. Entries created since the
+/// upgrade already have a deadline and are not scheduled twice. Returns the budget
+/// used and whether every entry was scheduled.
+pub fn schedule_pending_deadlines<T: Trait>(block: T::BlockNumber, budget: u32) -> (u32, bool) {
+    let prefixes = [
+        linked_map_prefix(b"Applications"),
+        linked_map_prefix(b"Challenges"),
+        linked_map_prefix(b"Members"),
+    ];
+
+    migrate_entries(&prefixes, budget, |key, value| {
+        let application = match Application::<T::AccountId, BalanceOf<T>, T::BlockNumber>::decode(
+            &mut &value[..],
+        ) {
+            Ok(application) => application,
+            Err(_) => return,
+        };
+        let account_id = application.candidate;
+        let schedule = |accounts: &mut Vec<T::AccountId>| {
+            if !accounts.contains(&account_id) {
+                accounts.push(account_id.clone())
+            }
+        };
+
+        if key.starts_with(&prefixes[0]) {
+            let deadline = application.created_block + T::FinalizeApplicationPeriod::get();
+            <ApplicationDeadlines<T>>::mutate(deadline.max(block), schedule);
+        } else if key.starts_with(&prefixes[1]) {
+            let deadline = application.challenged_block + T::FinalizeChallengePeriod::get();
+            <ChallengeDeadlines<T>>::mutate(deadline.max(block), schedule);
+        } else if <Exits<T>>::contains_key(account_id.clone()) {
+            let exit_block = <Exits<T>>::get(account_id.clone());
+            <ExitDeadlines<T>>::mutate(exit_block.max(block), schedule);
+        }
+    })
+}
+
+/// Reserve the deposits of the members admitted while deposits were unreserved on
+/// admission, so that the stake released when they leave or slashed when they lose a
+/// challenge is actually held. Members who can no longer afford their deposit are left
+/// without stake, in the challenge they may be facing too. At most `budget` members are
+/// migrated at a time. Returns the budget used and whether every member was migrated.
+pub fn reserve_member_stakes<T: Trait>(budget: u32) -> (u32, bool) {
+    migrate_entries(&[linked_map_prefix(b"Members")], budget, |_, value| {
+        let mut member = match Application::<T::AccountId, BalanceOf<T>, T::BlockNumber>::decode(
+            &mut &value[..],
+        ) {
+            Ok(member) => member,
+            Err(_) => return,
+        };
+        let account_id = member.candidate.clone();
+        if Module::<T>::reserve_for(account_id.clone(), member.candidate_deposit).is_ok() {
+            return;
+        }
+
+        member.candidate_deposit = Zero::zero();
+        <Members<T>>::insert(account_id.clone(), member);
+        if <Challenges<T>>::contains_key(account_id.clone()) {
+            <Challenges<T>>::mutate(account_id.clone(), |challenge| {
+                challenge.candidate_deposit = Zero::zero()
+            });
+        }
+
+        Module::<T>::deposit_event(RawEvent::StakeChanged(account_id, Zero::zero()));
+    })
+}
@@ -63,10 +63,21 @@ parameter_types! {
     pub const MinimumChallengeAmount: u64 = 10000;
     pub const FinalizeApplicationPeriod: u64 = 100;
     pub const FinalizeChallengePeriod: u64 = 101; // Happens later to ease unit tests
+    pub const CommitPeriod: u64 = 50;
     pub const LoosersSlash: Perbill = Perbill::from_percent(50);
+    pub const UnrevealedSlash: Perbill = Perbill::from_percent(10);
+    pub const MaxVoters: u32 = 3;
+    pub const MaxMigratedPerBlock: u32 = 3;
 }
 thread_local! {
     static MEMBERS: RefCell<Vec<u64>> = RefCell::new(vec![]);
+    static EXIT_COOLDOWN: RefCell<u64> = RefCell::new(10);
+}
+pub struct ExitCooldown;
+impl Get<u64> for ExitCooldown {
+    fn get() -> u64 {
+        EXIT_COOLDOWN.with(|v| *v.borrow())
+    }
 }
 pub struct TestChangeMembers;
 impl ChangeMembers<u64> for TestChangeMembers {
@@ -101,7 +112,13 @@ impl Trait for Test {
     type MinimumChallengeAmount = MinimumChallengeAmount;
     type FinalizeApplicationPeriod = FinalizeApplicationPeriod;
     type FinalizeChallengePeriod = FinalizeChallengePeriod;
+    type CommitPeriod = CommitPeriod;
     type LoosersSlash = LoosersSlash;
+    type UnrevealedSlash = UnrevealedSlash;
+    type ExitCooldown = ExitCooldown;
+    type MaxVoters = MaxVoters;
+    type MaxMigratedPerBlock = MaxMigratedPerBlock;
+    type WeightInfo = ();
     type ChangeMembers = TestChangeMembers;
 }
 
@@ -120,10 +137,16 @@ type TestModule = Module<Test>;
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
 fn new_test_ext() -> sp_io::TestExternalities {
-    system::GenesisConfig::default()
+    EXIT_COOLDOWN.with(|v| *v.borrow_mut() = 10);
+    let mut ext: sp_io::TestExternalities = system::GenesisConfig::default()
         .build_storage::<Test>()
         .unwrap()
-        .into()
+        .into();
+    ext.execute_with(|| {
+        DeadlinesMigrated::put(true);
+        StakesMigrated::put(true);
+    });
+    ext
 }
 
 fn allocate_balances() {
@@ -143,6 +166,39 @@ fn allocate_balances() {
     total_imbalance.subsume(r_voter_against);
 }
 
+fn salt() -> H256 {
+    H256::repeat_byte(42)
+}
+
+fn commit(voter: u64, supporting: bool, deposit: u64) -> DispatchResult {
+    TestModule::commit_vote(
+        Origin::signed(voter),
+        CANDIDATE,
+        TestModule::vote_commitment(&voter, &CANDIDATE, supporting, &salt(), deposit),
+        deposit,
+    )
+}
+
+fn reveal(voter: u64, supporting: bool) -> DispatchResult {
+    TestModule::reveal_vote(Origin::signed(voter), CANDIDATE, supporting, salt())
+}
+
+fn do_admit_candidate() {
+    assert_ok!(TestModule::apply(
+        Origin::signed(CANDIDATE),
+        vec![],
+        MinimumApplicationAmount::get(),
+    ));
+    <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
+    assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![CANDIDATE]);
+}
+
+fn start_reveal_period() {
+    <system::Module<Test>>::set_block_number(
+        <system::Module<Test>>::block_number() + CommitPeriod::get(),
+    );
+}
+
 #[test]
 fn lock_unlock_works() {
     new_test_ext().execute_with(|| {
@@ -391,18 +447,17 @@ fn vote_positive_and_negative_works() {
             MinimumCounterAmount::get(),
         ));
 
-        assert_ok!(TestModule::vote(
-            Origin::signed(VOTER_FOR),
-            CANDIDATE,
-            true,
-            100
-        ));
-        assert_ok!(TestModule::vote(
-            Origin::signed(VOTER_AGAINST),
-            CANDIDATE,
-            false,
-            100
-        ));
+        assert_ok!(commit(VOTER_FOR, true, 100));
+        assert_ok!(commit(VOTER_AGAINST, false, 100));
+
+        // Committed votes are not counted until revealed
+        assert_eq!(<Challenges<Test>>::get(CANDIDATE).votes_for, None);
+        assert_eq!(TestModule::commitments(CANDIDATE).len(), 2);
+
+        start_reveal_period();
+        assert_ok!(reveal(VOTER_FOR, true));
+        assert_ok!(reveal(VOTER_AGAINST, false));
+        assert_eq!(TestModule::commitments(CANDIDATE).len(), 0);
 
         let challenge = <Challenges<Test>>::get(CANDIDATE);
         assert_eq!(challenge.clone().votes_for, Some(100));
@@ -427,9 +482,10 @@ fn can_not_vote_if_challenge_does_not_exists() {
         allocate_balances();
 
         assert_noop!(
-            TestModule::vote(Origin::signed(VOTER_FOR), CANDIDATE, true, 100),
+            commit(VOTER_FOR, true, 100),
             Error::<Test>::ChallengeNotFound
         );
+        assert_noop!(reveal(VOTER_FOR, true), Error::<Test>::ChallengeNotFound);
     })
 }
 
@@ -450,9 +506,156 @@ fn can_not_deposit_if_not_enough_funds() {
             MinimumCounterAmount::get(),
         ));
 
+        assert_noop!(commit(VOTER_FOR, true, 1001), Error::<Test>::NotEnoughFunds);
+    })
+}
+
+#[test]
+fn can_not_commit_twice_or_after_commit_period() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CANDIDATE),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_ok!(TestModule::counter(
+            Origin::signed(CHALLENGER_1),
+            CANDIDATE,
+            MinimumCounterAmount::get(),
+        ));
+
+        assert_ok!(commit(VOTER_FOR, true, 100));
         assert_noop!(
-            TestModule::vote(Origin::signed(VOTER_FOR), CANDIDATE, true, 1001),
-            Error::<Test>::NotEnoughFunds
+            commit(VOTER_FOR, false, 100),
+            Error::<Test>::AlreadyCommitted
+        );
+
+        start_reveal_period();
+        assert_noop!(
+            commit(VOTER_AGAINST, false, 100),
+            Error::<Test>::CommitPeriodOver
+        );
+    })
+}
+
+#[test]
+fn can_only_reveal_matching_votes_during_reveal_period() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CANDIDATE),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_ok!(TestModule::counter(
+            Origin::signed(CHALLENGER_1),
+            CANDIDATE,
+            MinimumCounterAmount::get(),
+        ));
+
+        assert_ok!(commit(VOTER_FOR, true, 100));
+        assert_noop!(reveal(VOTER_FOR, true), Error::<Test>::NotRevealPeriod);
+
+        start_reveal_period();
+        assert_noop!(
+            reveal(VOTER_AGAINST, false),
+            Error::<Test>::CommitmentNotFound
+        );
+        assert_noop!(reveal(VOTER_FOR, false), Error::<Test>::CommitmentMismatch);
+        assert_noop!(
+            TestModule::reveal_vote(
+                Origin::signed(VOTER_FOR),
+                CANDIDATE,
+                true,
+                H256::repeat_byte(1)
+            ),
+            Error::<Test>::CommitmentMismatch
+        );
+
+        <system::Module<Test>>::set_block_number(FinalizeChallengePeriod::get());
+        assert_noop!(reveal(VOTER_FOR, true), Error::<Test>::NotRevealPeriod);
+    })
+}
+
+#[test]
+fn copied_commitments_can_not_be_revealed() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CANDIDATE),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_ok!(TestModule::counter(
+            Origin::signed(CHALLENGER_1),
+            CANDIDATE,
+            MinimumCounterAmount::get(),
+        ));
+
+        // `VOTER_AGAINST` copies the commitment of `VOTER_FOR` to follow its vote
+        assert_ok!(commit(VOTER_FOR, true, 100));
+        let copied = TestModule::commitments(CANDIDATE)[0].commitment;
+        assert_ok!(TestModule::commit_vote(
+            Origin::signed(VOTER_AGAINST),
+            CANDIDATE,
+            copied,
+            100,
+        ));
+
+        start_reveal_period();
+        assert_ok!(reveal(VOTER_FOR, true));
+        assert_noop!(
+            reveal(VOTER_AGAINST, true),
+            Error::<Test>::CommitmentMismatch
+        );
+        assert_noop!(
+            reveal(VOTER_AGAINST, false),
+            Error::<Test>::CommitmentMismatch
+        );
+    })
+}
+
+#[test]
+fn unrevealed_votes_are_not_counted_and_slashed() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CANDIDATE),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_ok!(TestModule::counter(
+            Origin::signed(CHALLENGER_1),
+            CANDIDATE,
+            MinimumCounterAmount::get(),
+        ));
+
+        // Would be enough to keep the candidate if it was revealed
+        assert_ok!(commit(VOTER_FOR, true, 1000));
+
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get());
+
+        assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(<Commitments<Test>>::contains_key(CANDIDATE), false);
+
+        let unrevealed_slash = UnrevealedSlash::get() * 1000;
+        assert_eq!(BalancesModule::reserved_balance(VOTER_FOR), 0);
+        assert_eq!(
+            BalancesModule::usable_balance(VOTER_FOR),
+            1000 - unrevealed_slash
+        );
+        assert_eq!(
+            BalancesModule::usable_balance(CHALLENGER_1),
+            MinimumCounterAmount::get()
+                + (MinimumApplicationAmount::get()
+                    - LoosersSlash::get() * MinimumApplicationAmount::get())
+                + unrevealed_slash
         );
     })
 }
@@ -468,14 +671,16 @@ fn finalize_application_if_not_challenged_and_enough_time_elapsed() {
             MinimumApplicationAmount::get(),
         ));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
         assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![CANDIDATE]);
 
         assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
         assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), false);
         assert_eq!(<Members<Test>>::contains_key(CANDIDATE), true);
 
-        assert_eq!(BalancesModule::usable_balance(CANDIDATE), MinimumApplicationAmount::get());
+        // The deposit stays staked as long as the candidate is a member
+        assert_eq!(BalancesModule::usable_balance(CANDIDATE), 0);
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), MinimumApplicationAmount::get());
     })
 }
 
@@ -496,7 +701,7 @@ fn does_not_finalize_challenged_application() {
             MinimumCounterAmount::get(),
         ));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
 
         assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
         assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), true);
@@ -515,7 +720,7 @@ fn does_not_finalize_application_if_not_enough_time_elapsed() {
             MinimumApplicationAmount::get(),
         ));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number() - 1);
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number() - 1);
 
         assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), true);
         assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), false);
@@ -540,14 +745,11 @@ fn finalize_challenge_if_enough_time_elapsed_drop() {
             MinimumCounterAmount::get(),
         ));
 
-        assert_ok!(TestModule::vote(
-            Origin::signed(VOTER_FOR),
-            CANDIDATE,
-            true,
-            2,
-        ));
+        assert_ok!(commit(VOTER_FOR, true, 2));
+        start_reveal_period();
+        assert_ok!(reveal(VOTER_FOR, true));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeChallengePeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get());
 
         assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
         assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), false);
@@ -583,21 +785,14 @@ fn finalize_challenge_if_enough_time_elapsed_accept() {
             MinimumCounterAmount::get(),
         ));
 
-        assert_ok!(TestModule::vote(
-            Origin::signed(VOTER_FOR),
-            CANDIDATE,
-            true,
-            1000, //MinimumCounterAmount::get(),
-        ));
+        assert_ok!(commit(VOTER_FOR, true, 1000));
+        assert_ok!(commit(VOTER_AGAINST, false, 2));
 
-        assert_ok!(TestModule::vote(
-            Origin::signed(VOTER_AGAINST),
-            CANDIDATE,
-            false,
-            2,
-        ));
+        start_reveal_period();
+        assert_ok!(reveal(VOTER_FOR, true));
+        assert_ok!(reveal(VOTER_AGAINST, false));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeChallengePeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get());
 
         assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
         assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), false);
@@ -623,7 +818,8 @@ fn finalize_challenge_if_enough_time_elapsed_accept() {
         //assert_eq!(dust, 1);
 
         assert_eq!(BalancesModule::usable_balance(VOTER_FOR), 1000 + voter_rewards);
-        assert_eq!(BalancesModule::usable_balance(CANDIDATE), MinimumApplicationAmount::get() + candidate_rewards + dust);
+        assert_eq!(BalancesModule::usable_balance(CANDIDATE), candidate_rewards + dust);
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), MinimumApplicationAmount::get());
 
         assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![CANDIDATE]);
     })
@@ -640,7 +836,7 @@ fn finalize_challenge_if_enough_time_elapsed_drop_and_kill_member() {
             MinimumApplicationAmount::get(),
         ));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
         assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![CANDIDATE]);
 
         assert_ok!(TestModule::challenge(
@@ -649,7 +845,7 @@ fn finalize_challenge_if_enough_time_elapsed_drop_and_kill_member() {
             MinimumChallengeAmount::get(),
         ));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeChallengePeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get() + <system::Module<Test>>::block_number());
         assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![]);
 
         assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
@@ -675,7 +871,7 @@ fn does_not_finalize_challenge_if_not_enough_time_elapsed() {
             MinimumCounterAmount::get(),
         ));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeChallengePeriod::get() + <system::Module<Test>>::block_number() - 1);
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get() + <system::Module<Test>>::block_number() - 1);
 
         assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
         assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), true);
@@ -694,7 +890,7 @@ fn can_challenge_member_application() {
             MinimumApplicationAmount::get(),
         ));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
         assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![CANDIDATE]);
 
         assert_ok!(TestModule::challenge(
@@ -719,7 +915,7 @@ fn can_challenge_member_application() {
         assert_eq!(<Challenges<Test>>::get(CANDIDATE).voters_against, vec![]);
         assert_eq!(<Challenges<Test>>::get(CANDIDATE).challenged_block, <system::Module<Test>>::block_number());
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeChallengePeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get() + <system::Module<Test>>::block_number());
         assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![]);
         assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
         assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), false);
@@ -752,7 +948,7 @@ fn can_not_challenge_member_applicaton_if_not_enough_funds() {
             MinimumApplicationAmount::get(),
         ));
 
-        <TestModule as sp_runtime::traits::OnFinalize<<Test as system::Trait>::BlockNumber>>::on_finalize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get() + <system::Module<Test>>::block_number());
 
         assert_noop!(TestModule::challenge(
             Origin::signed(CHALLENGER_2),
@@ -775,3 +971,604 @@ fn can_not_challenge_member_applicaton_if_not_big_enough_deposit() {
         );
     })
 }
+
+#[test]
+fn withdraw_application_works() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CANDIDATE),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_ok!(TestModule::withdraw_application(Origin::signed(CANDIDATE)));
+
+        assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), 0);
+        assert_eq!(
+            BalancesModule::usable_balance(CANDIDATE),
+            MinimumApplicationAmount::get()
+        );
+
+        // Withdrawn applications are not committed
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get());
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+    })
+}
+
+#[test]
+fn can_not_withdraw_missing_or_countered_application() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_noop!(
+            TestModule::withdraw_application(Origin::signed(CANDIDATE)),
+            Error::<Test>::ApplicationNotFound
+        );
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CANDIDATE),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_ok!(TestModule::counter(
+            Origin::signed(CHALLENGER_1),
+            CANDIDATE,
+            MinimumCounterAmount::get(),
+        ));
+        assert_noop!(
+            TestModule::withdraw_application(Origin::signed(CANDIDATE)),
+            Error::<Test>::ApplicationChallenged
+        );
+    })
+}
+
+#[test]
+fn members_leave_after_exit_cooldown() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_candidate();
+
+        assert_noop!(
+            TestModule::leave_registry(Origin::signed(CHALLENGER_1)),
+            Error::<Test>::MemberNotFound
+        );
+        assert_ok!(TestModule::leave_registry(Origin::signed(CANDIDATE)));
+        assert_noop!(
+            TestModule::leave_registry(Origin::signed(CANDIDATE)),
+            Error::<Test>::ExitPending
+        );
+        assert_eq!(
+            TestModule::exits(CANDIDATE),
+            <system::Module<Test>>::block_number() + ExitCooldown::get()
+        );
+
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(ExitCooldown::get() - 1);
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), true);
+
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(ExitCooldown::get());
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(<Exits<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![]);
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), 0);
+        assert_eq!(
+            BalancesModule::usable_balance(CANDIDATE),
+            MinimumApplicationAmount::get()
+        );
+    })
+}
+
+#[test]
+fn members_leave_immediately_without_exit_cooldown() {
+    new_test_ext().execute_with(|| {
+        EXIT_COOLDOWN.with(|v| *v.borrow_mut() = 0);
+        allocate_balances();
+        do_admit_candidate();
+
+        assert_ok!(TestModule::leave_registry(Origin::signed(CANDIDATE)));
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(<Exits<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![]);
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), 0);
+    })
+}
+
+#[test]
+fn challenged_members_can_not_dodge_the_challenge() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_candidate();
+
+        assert_ok!(TestModule::leave_registry(Origin::signed(CANDIDATE)));
+        assert_ok!(TestModule::challenge(
+            Origin::signed(CHALLENGER_2),
+            CANDIDATE,
+            MinimumChallengeAmount::get()
+        ));
+
+        // The exit waits for the challenge to be resolved
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(ExitCooldown::get());
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), true);
+        assert_eq!(<Exits<Test>>::contains_key(CANDIDATE), true);
+
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get());
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(<Exits<Test>>::contains_key(CANDIDATE), false);
+
+        // The challenger was rewarded with the slashed deposit
+        assert_eq!(
+            BalancesModule::usable_balance(CHALLENGER_2),
+            MinimumChallengeAmount::get() + LoosersSlash::get() * MinimumApplicationAmount::get()
+        );
+        assert_eq!(MEMBERS.with(|m| m.borrow().clone()), vec![]);
+    })
+}
+
+#[test]
+fn can_not_leave_while_challenged() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_candidate();
+
+        assert_ok!(TestModule::challenge(
+            Origin::signed(CHALLENGER_2),
+            CANDIDATE,
+            MinimumChallengeAmount::get()
+        ));
+        assert_noop!(
+            TestModule::leave_registry(Origin::signed(CANDIDATE)),
+            Error::<Test>::ApplicationChallenged
+        );
+    })
+}
+
+#[test]
+fn top_up_and_reduce_stake_work() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_candidate();
+        let _ = <Test as Trait>::Currency::deposit_creating(&CANDIDATE, 500);
+
+        assert_ok!(TestModule::top_up(Origin::signed(CANDIDATE), 500));
+        assert_eq!(
+            TestModule::members(CANDIDATE).candidate_deposit,
+            MinimumApplicationAmount::get() + 500
+        );
+        assert_eq!(
+            BalancesModule::reserved_balance(CANDIDATE),
+            MinimumApplicationAmount::get() + 500
+        );
+
+        assert_ok!(TestModule::reduce_stake(Origin::signed(CANDIDATE), 200));
+        assert_eq!(
+            TestModule::members(CANDIDATE).candidate_deposit,
+            MinimumApplicationAmount::get() + 300
+        );
+        assert_eq!(
+            BalancesModule::reserved_balance(CANDIDATE),
+            MinimumApplicationAmount::get() + 300
+        );
+        assert_eq!(BalancesModule::usable_balance(CANDIDATE), 200);
+    })
+}
+
+#[test]
+fn members_can_not_apply_again() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_candidate();
+        let _ = <Test as Trait>::Currency::deposit_creating(&CANDIDATE, 500);
+
+        // A second deposit would overwrite the stake record and stay reserved forever
+        assert_noop!(
+            TestModule::apply(Origin::signed(CANDIDATE), vec![], 500),
+            Error::<Test>::AlreadyMember
+        );
+        assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(
+            BalancesModule::reserved_balance(CANDIDATE),
+            MinimumApplicationAmount::get()
+        );
+        assert_eq!(
+            TestModule::members(CANDIDATE).candidate_deposit,
+            MinimumApplicationAmount::get()
+        );
+    })
+}
+
+#[test]
+fn can_not_change_stake_below_minimum_or_while_challenged() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_noop!(
+            TestModule::top_up(Origin::signed(CANDIDATE), 1),
+            Error::<Test>::MemberNotFound
+        );
+
+        do_admit_candidate();
+        assert_noop!(
+            TestModule::top_up(Origin::signed(CANDIDATE), 1),
+            Error::<Test>::NotEnoughFunds
+        );
+        assert_noop!(
+            TestModule::reduce_stake(Origin::signed(CANDIDATE), 1),
+            Error::<Test>::DepositTooSmall
+        );
+
+        assert_ok!(TestModule::challenge(
+            Origin::signed(CHALLENGER_2),
+            CANDIDATE,
+            MinimumChallengeAmount::get()
+        ));
+        assert_noop!(
+            TestModule::reduce_stake(Origin::signed(CANDIDATE), 1),
+            Error::<Test>::ApplicationChallenged
+        );
+    })
+}
+
+#[test]
+fn challenges_slash_the_staked_deposit() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_candidate();
+        let _ = <Test as Trait>::Currency::deposit_creating(&CANDIDATE, 900);
+        assert_ok!(TestModule::top_up(Origin::signed(CANDIDATE), 900));
+
+        assert_ok!(TestModule::challenge(
+            Origin::signed(CHALLENGER_2),
+            CANDIDATE,
+            MinimumChallengeAmount::get()
+        ));
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get());
+
+        let stake = MinimumApplicationAmount::get() + 900;
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), 0);
+        assert_eq!(
+            BalancesModule::usable_balance(CANDIDATE),
+            stake - LoosersSlash::get() * stake
+        );
+        assert_eq!(
+            BalancesModule::usable_balance(CHALLENGER_2),
+            MinimumChallengeAmount::get() + LoosersSlash::get() * stake
+        );
+    })
+}
+
+#[test]
+fn only_entries_reaching_their_deadline_are_processed() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CANDIDATE),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_eq!(
+            TestModule::application_deadlines(FinalizeApplicationPeriod::get()),
+            vec![CANDIDATE]
+        );
+
+        // Nothing is due, no weight is used
+        assert_eq!(
+            <TestModule as sp_runtime::traits::OnInitialize<
+                <Test as system::Trait>::BlockNumber,
+            >>::on_initialize(FinalizeApplicationPeriod::get() - 1),
+            0
+        );
+        assert_eq!(<Applications<Test>>::contains_key(CANDIDATE), true);
+
+        assert_ok!(TestModule::counter(
+            Origin::signed(CHALLENGER_1),
+            CANDIDATE,
+            MinimumCounterAmount::get(),
+        ));
+        assert_ok!(commit(VOTER_FOR, true, 2));
+        assert_eq!(
+            TestModule::challenge_deadlines(FinalizeChallengePeriod::get()),
+            vec![CANDIDATE]
+        );
+
+        // The countered application is not committed but its deadline is still charged
+        assert_eq!(
+            <TestModule as sp_runtime::traits::OnInitialize<
+                <Test as system::Trait>::BlockNumber,
+            >>::on_initialize(FinalizeApplicationPeriod::get()),
+            <() as WeightInfo>::commit_applications(1)
+        );
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), true);
+        assert_eq!(
+            TestModule::application_deadlines(FinalizeApplicationPeriod::get()),
+            vec![]
+        );
+
+        // The challenge weight accounts for the votes to settle
+        assert_eq!(
+            <TestModule as sp_runtime::traits::OnInitialize<
+                <Test as system::Trait>::BlockNumber,
+            >>::on_initialize(FinalizeChallengePeriod::get()),
+            <() as WeightInfo>::resolve_challenge(1)
+        );
+        assert_eq!(<Challenges<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(
+            TestModule::challenge_deadlines(FinalizeChallengePeriod::get()),
+            vec![]
+        );
+    })
+}
+
+#[test]
+fn pending_entries_are_scheduled_by_the_migration() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_candidate();
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CHALLENGER_1),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_ok!(TestModule::leave_registry(Origin::signed(CANDIDATE)));
+
+        // Pretend the entries were created before the deadline schedules
+        <ApplicationDeadlines<Test>>::remove(FinalizeApplicationPeriod::get());
+        <ExitDeadlines<Test>>::remove(ExitCooldown::get());
+        DeadlinesMigrated::put(false);
+
+        // The exit is overdue and processed right away
+        let block = ExitCooldown::get() + 1;
+        assert_eq!(
+            <TestModule as sp_runtime::traits::OnInitialize<
+                <Test as system::Trait>::BlockNumber,
+            >>::on_initialize(block),
+            2 * DEADLINE_MIGRATION_WEIGHT + <() as WeightInfo>::process_exits(1)
+        );
+        assert_eq!(TestModule::deadlines_migrated(), true);
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(
+            TestModule::application_deadlines(FinalizeApplicationPeriod::get()),
+            vec![CHALLENGER_1]
+        );
+    })
+}
+
+#[test]
+fn pending_entries_are_scheduled_in_batches() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_candidate();
+
+        for applicant in vec![CHALLENGER_1, CHALLENGER_2, VOTER_FOR] {
+            assert_ok!(TestModule::apply(
+                Origin::signed(applicant),
+                vec![],
+                MinimumApplicationAmount::get(),
+            ));
+        }
+
+        // Only `CHALLENGER_1` applied after the upgrade
+        <ApplicationDeadlines<Test>>::insert(FinalizeApplicationPeriod::get(), vec![CHALLENGER_1]);
+        DeadlinesMigrated::put(false);
+
+        // Three applications and one member to visit
+        assert_eq!(
+            <TestModule as sp_runtime::traits::OnInitialize<
+                <Test as system::Trait>::BlockNumber,
+            >>::on_initialize(1),
+            MaxMigratedPerBlock::get() as Weight * DEADLINE_MIGRATION_WEIGHT
+        );
+        assert_eq!(TestModule::deadlines_migrated(), false);
+        assert!(TestModule::migration_cursor().is_some());
+
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(2);
+        assert_eq!(TestModule::deadlines_migrated(), true);
+        assert_eq!(TestModule::migration_cursor(), None);
+
+        let mut scheduled = TestModule::application_deadlines(FinalizeApplicationPeriod::get());
+        scheduled.sort();
+        assert_eq!(scheduled, vec![CHALLENGER_1, CHALLENGER_2, VOTER_FOR]);
+    })
+}
+
+/// Admit `CANDIDATE` and unreserve its deposit, as was done on admission before member
+/// stakes were kept reserved
+fn do_admit_pre_upgrade_candidate() {
+    do_admit_candidate();
+    BalancesModule::unreserve(&CANDIDATE, MinimumApplicationAmount::get());
+    StakesMigrated::put(false);
+}
+
+fn migrate_stakes() {
+    let block = <system::Module<Test>>::block_number();
+    assert_eq!(
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(block),
+        STAKE_MIGRATION_WEIGHT
+    );
+    assert_eq!(TestModule::stakes_migrated(), true);
+}
+
+#[test]
+fn pre_upgrade_members_leave_with_their_stake() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_pre_upgrade_candidate();
+        // Funds reserved for another purpose must not be released with the stake
+        drop(BalancesModule::deposit_creating(&CANDIDATE, 100));
+        assert_ok!(BalancesModule::reserve(&CANDIDATE, 100));
+
+        migrate_stakes();
+        assert_eq!(
+            BalancesModule::reserved_balance(CANDIDATE),
+            MinimumApplicationAmount::get() + 100
+        );
+
+        assert_ok!(TestModule::leave_registry(Origin::signed(CANDIDATE)));
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(TestModule::exits(CANDIDATE));
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), 100);
+        assert_eq!(
+            BalancesModule::usable_balance(CANDIDATE),
+            MinimumApplicationAmount::get()
+        );
+    })
+}
+
+#[test]
+fn pre_upgrade_members_lose_their_stake_when_challenged() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_pre_upgrade_candidate();
+        migrate_stakes();
+
+        assert_ok!(TestModule::challenge(
+            Origin::signed(CHALLENGER_2),
+            CANDIDATE,
+            MinimumChallengeAmount::get()
+        ));
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get());
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), 0);
+        assert_eq!(
+            BalancesModule::usable_balance(CANDIDATE),
+            MinimumApplicationAmount::get() - LoosersSlash::get() * MinimumApplicationAmount::get()
+        );
+        assert_eq!(
+            BalancesModule::usable_balance(CHALLENGER_2),
+            MinimumChallengeAmount::get() + LoosersSlash::get() * MinimumApplicationAmount::get()
+        );
+    })
+}
+
+#[test]
+fn pre_upgrade_members_without_funds_are_left_without_stake() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        do_admit_pre_upgrade_candidate();
+        assert_ok!(TestModule::challenge(
+            Origin::signed(CHALLENGER_2),
+            CANDIDATE,
+            MinimumChallengeAmount::get()
+        ));
+        BalancesModule::make_free_balance_be(&CANDIDATE, 10);
+
+        migrate_stakes();
+        assert_eq!(BalancesModule::reserved_balance(CANDIDATE), 0);
+        assert_eq!(TestModule::members(CANDIDATE).candidate_deposit, 0);
+        assert_eq!(TestModule::challenges(CANDIDATE).candidate_deposit, 0);
+
+        // Only the stake actually held is counted and slashed
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeChallengePeriod::get());
+        assert_eq!(<Members<Test>>::contains_key(CANDIDATE), false);
+        assert_eq!(BalancesModule::usable_balance(CANDIDATE), 10);
+        assert_eq!(
+            BalancesModule::usable_balance(CHALLENGER_2),
+            MinimumChallengeAmount::get()
+        );
+    })
+}
+
+#[test]
+fn pre_upgrade_stakes_are_reserved_in_batches() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+        let members = vec![CANDIDATE, CHALLENGER_1, CHALLENGER_2, VOTER_FOR];
+        for member in members.iter() {
+            assert_ok!(TestModule::apply(
+                Origin::signed(*member),
+                vec![],
+                MinimumApplicationAmount::get(),
+            ));
+        }
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(FinalizeApplicationPeriod::get());
+        for member in members.iter() {
+            BalancesModule::unreserve(member, MinimumApplicationAmount::get());
+        }
+        StakesMigrated::put(false);
+
+        <system::Module<Test>>::set_block_number(1);
+        assert_ok!(TestModule::apply(
+            Origin::signed(VOTER_AGAINST),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        let deadline = 1 + FinalizeApplicationPeriod::get();
+
+        // Applications are not committed until every stake is reserved
+        assert_eq!(
+            <TestModule as sp_runtime::traits::OnInitialize<
+                <Test as system::Trait>::BlockNumber,
+            >>::on_initialize(deadline),
+            MaxMigratedPerBlock::get() as Weight * STAKE_MIGRATION_WEIGHT
+        );
+        assert_eq!(TestModule::stakes_migrated(), false);
+        assert_eq!(<Members<Test>>::contains_key(VOTER_AGAINST), false);
+        assert_eq!(
+            TestModule::application_deadlines(deadline + 1),
+            vec![VOTER_AGAINST]
+        );
+        assert_noop!(
+            TestModule::top_up(Origin::signed(CHALLENGER_2), 10),
+            Error::<Test>::MigrationInProgress
+        );
+
+        <TestModule as sp_runtime::traits::OnInitialize<<Test as system::Trait>::BlockNumber>>::on_initialize(deadline + 1);
+        assert_eq!(TestModule::stakes_migrated(), true);
+        assert_eq!(TestModule::migration_cursor(), None);
+        assert_eq!(<Members<Test>>::contains_key(VOTER_AGAINST), true);
+
+        // Members admitted during the migration are not reserved twice
+        for member in members.iter().chain(Some(&VOTER_AGAINST)) {
+            assert_eq!(
+                BalancesModule::reserved_balance(member),
+                MinimumApplicationAmount::get()
+            );
+        }
+    })
+}
+
+#[test]
+fn can_not_commit_more_than_max_voters() {
+    new_test_ext().execute_with(|| {
+        allocate_balances();
+
+        assert_ok!(TestModule::apply(
+            Origin::signed(CANDIDATE),
+            vec![],
+            MinimumApplicationAmount::get(),
+        ));
+        assert_ok!(TestModule::counter(
+            Origin::signed(CHALLENGER_1),
+            CANDIDATE,
+            MinimumCounterAmount::get(),
+        ));
+
+        assert_ok!(commit(VOTER_FOR, true, 10));
+        assert_ok!(commit(VOTER_AGAINST, false, 10));
+        assert_ok!(commit(CHALLENGER_2, true, 10));
+        assert_noop!(commit(CANDIDATE, true, 10), Error::<Test>::TooManyVoters);
+    })
+}
+
+#[test]
+fn extrinsics_are_weighted_by_their_inputs() {
+    use frame_support::weights::GetDispatchInfo;
+
+    let apply = Call::<Test>::apply(vec![0; 100], MinimumApplicationAmount::get());
+    assert_eq!(
+        apply.get_dispatch_info().weight,
+        <() as WeightInfo>::apply(100)
+    );
+
+    // Votes are charged as if the challenge already had `MaxVoters` votes
+    let commit_vote = Call::<Test>::commit_vote(CANDIDATE, H256::zero(), 10);
+    assert_eq!(
+        commit_vote.get_dispatch_info().weight,
+        <() as WeightInfo>::commit_vote(MaxVoters::get())
+    );
+}
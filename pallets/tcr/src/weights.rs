@@ -0,0 +1,67 @@
+//! Weights of the TCR module extrinsics and of the deadlines processed in
+//! `on_initialize`.
+
+use frame_support::weights::Weight;
+
+/// Weight functions of the module extrinsics, implemented by the runtime with the results
+/// of the benchmarks in `benchmarking.rs`
+pub trait WeightInfo {
+    /// `m` is the length of the application metadata
+    fn apply(m: u32) -> Weight;
+    fn withdraw_application() -> Weight;
+    fn leave_registry() -> Weight;
+    fn top_up() -> Weight;
+    fn reduce_stake() -> Weight;
+    fn counter() -> Weight;
+    /// `v` is the number of votes already committed or revealed for the challenge
+    fn commit_vote(v: u32) -> Weight;
+    /// `v` is the number of votes already committed or revealed for the challenge
+    fn reveal_vote(v: u32) -> Weight;
+    fn challenge() -> Weight;
+    /// `a` is the number of applications reaching their deadline in a block
+    fn commit_applications(a: u32) -> Weight;
+    /// `v` is the number of votes committed or revealed for the resolved challenge
+    fn resolve_challenge(v: u32) -> Weight;
+    /// `e` is the number of exits reaching their deadline in a block
+    fn process_exits(e: u32) -> Weight;
+}
+
+/// Rough estimates to be used by test runtimes
+impl WeightInfo for () {
+    fn apply(m: u32) -> Weight {
+        50_000 + 10 * m as Weight
+    }
+    fn withdraw_application() -> Weight {
+        50_000
+    }
+    fn leave_registry() -> Weight {
+        50_000
+    }
+    fn top_up() -> Weight {
+        50_000
+    }
+    fn reduce_stake() -> Weight {
+        50_000
+    }
+    fn counter() -> Weight {
+        50_000
+    }
+    fn commit_vote(v: u32) -> Weight {
+        50_000 + 1_000 * v as Weight
+    }
+    fn reveal_vote(v: u32) -> Weight {
+        50_000 + 1_000 * v as Weight
+    }
+    fn challenge() -> Weight {
+        50_000
+    }
+    fn commit_applications(a: u32) -> Weight {
+        10_000 * a as Weight
+    }
+    fn resolve_challenge(v: u32) -> Weight {
+        50_000 + 10_000 * v as Weight
+    }
+    fn process_exits(e: u32) -> Weight {
+        10_000 * e as Weight
+    }
+}
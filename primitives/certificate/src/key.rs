@@ -0,0 +1,309 @@
+//! Public keys and signatures of certificates. Unlike `AccountId`, which hashes the
+//! ecdsa keys it is derived from, `CertificateKey` keeps the raw public key along
+//! with its algorithm so that certificates can be verified for every key type found
+//! in secure elements.
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use sp_core::Bytes;
+use sp_core::{ecdsa, ed25519, sr25519, H512};
+use sp_runtime::{
+    traits::{IdentifyAccount, Lazy, Verify},
+    RuntimeDebug,
+};
+use sp_std::convert::TryFrom;
+
+/// A compressed (SEC1) secp256r1, also known as P-256, public key
+#[derive(Encode, Decode, Clone, Copy)]
+pub struct P256Public(pub [u8; 33]);
+
+impl Default for P256Public {
+    fn default() -> Self {
+        P256Public([0; 33])
+    }
+}
+
+impl AsRef<[u8]> for P256Public {
+    fn as_ref(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+impl AsMut<[u8]> for P256Public {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0[..]
+    }
+}
+
+impl PartialEq for P256Public {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_ref() == other.as_ref()
+    }
+}
+
+impl Eq for P256Public {}
+
+impl PartialOrd for P256Public {
+    fn partial_cmp(&self, other: &Self) -> Option<sp_std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for P256Public {
+    fn cmp(&self, other: &Self) -> sp_std::cmp::Ordering {
+        self.as_ref().cmp(other.as_ref())
+    }
+}
+
+impl sp_std::hash::Hash for P256Public {
+    fn hash<H: sp_std::hash::Hasher>(&self, state: &mut H) {
+        self.as_ref().hash(state)
+    }
+}
+
+impl sp_std::fmt::Debug for P256Public {
+    fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+        write!(f, "P256Public({:?})", self.as_ref())
+    }
+}
+
+/// Public key of a certificate
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, RuntimeDebug)]
+#[cfg_attr(
+    feature = "std",
+    derive(Serialize, Deserialize, Hash),
+    serde(try_from = "KeyRepr", into = "KeyRepr")
+)]
+pub enum CertificateKey {
+    Ed25519(ed25519::Public),
+    Sr25519(sr25519::Public),
+    /// Compressed secp256k1 public key
+    Ecdsa(ecdsa::Public),
+    P256(P256Public),
+}
+
+impl Default for CertificateKey {
+    fn default() -> Self {
+        CertificateKey::Sr25519(Default::default())
+    }
+}
+
+impl AsRef<[u8]> for CertificateKey {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            CertificateKey::Ed25519(key) => key.as_ref(),
+            CertificateKey::Sr25519(key) => key.as_ref(),
+            CertificateKey::Ecdsa(key) => key.as_ref(),
+            CertificateKey::P256(key) => key.as_ref(),
+        }
+    }
+}
+
+impl CertificateKey {
+    /// Name of the key algorithm, as used in the text representation of the keys
+    pub fn algorithm(&self) -> &'static str {
+        match self {
+            CertificateKey::Ed25519(_) => "ed25519",
+            CertificateKey::Sr25519(_) => "sr25519",
+            CertificateKey::Ecdsa(_) => "ecdsa",
+            CertificateKey::P256(_) => "p256",
+        }
+    }
+}
+
+impl IdentifyAccount for CertificateKey {
+    type AccountId = Self;
+
+    fn into_account(self) -> Self {
+        self
+    }
+}
+
+impl From<ed25519::Public> for CertificateKey {
+    fn from(key: ed25519::Public) -> Self {
+        CertificateKey::Ed25519(key)
+    }
+}
+
+impl From<sr25519::Public> for CertificateKey {
+    fn from(key: sr25519::Public) -> Self {
+        CertificateKey::Sr25519(key)
+    }
+}
+
+impl From<ecdsa::Public> for CertificateKey {
+    fn from(key: ecdsa::Public) -> Self {
+        CertificateKey::Ecdsa(key)
+    }
+}
+
+impl From<P256Public> for CertificateKey {
+    fn from(key: P256Public) -> Self {
+        CertificateKey::P256(key)
+    }
+}
+
+/// Keys are displayed and parsed as `<algorithm>:0x<hex encoded public key>`
+#[cfg(feature = "std")]
+impl std::fmt::Display for CertificateKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:0x{}",
+            self.algorithm(),
+            sp_core::hexdisplay::HexDisplay::from(&self.as_ref())
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::str::FromStr for CertificateKey {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let algorithm = parts.next().ok_or("missing algorithm")?;
+        let encoded = parts.next().ok_or("missing public key")?;
+        let bytes =
+            hex::decode(encoded.trim_start_matches("0x")).map_err(|_| "invalid hex public key")?;
+
+        match algorithm {
+            "ed25519" => KeyRepr::Ed25519(Bytes(bytes)),
+            "sr25519" => KeyRepr::Sr25519(Bytes(bytes)),
+            "ecdsa" => KeyRepr::Ecdsa(Bytes(bytes)),
+            "p256" => KeyRepr::P256(Bytes(bytes)),
+            _ => return Err("unknown algorithm"),
+        }
+        .try_into_key()
+    }
+}
+
+/// JSON representation of the keys, matching the one of the polkadot.js enums:
+/// `{ "Sr25519": "0x..." }`
+#[cfg(feature = "std")]
+#[derive(Serialize, Deserialize)]
+enum KeyRepr {
+    Ed25519(Bytes),
+    Sr25519(Bytes),
+    Ecdsa(Bytes),
+    P256(Bytes),
+}
+
+#[cfg(feature = "std")]
+impl KeyRepr {
+    fn try_into_key(self) -> Result<CertificateKey, &'static str> {
+        fn fixed<T: Default + AsMut<[u8]>>(bytes: Bytes) -> Result<T, &'static str> {
+            let mut key = T::default();
+            if key.as_mut().len() != bytes.len() {
+                return Err("invalid public key length");
+            }
+            key.as_mut().copy_from_slice(&bytes);
+            Ok(key)
+        }
+
+        Ok(match self {
+            KeyRepr::Ed25519(bytes) => CertificateKey::Ed25519(fixed(bytes)?),
+            KeyRepr::Sr25519(bytes) => CertificateKey::Sr25519(fixed(bytes)?),
+            KeyRepr::Ecdsa(bytes) => CertificateKey::Ecdsa(fixed(bytes)?),
+            KeyRepr::P256(bytes) => CertificateKey::P256(fixed(bytes)?),
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<KeyRepr> for CertificateKey {
+    type Error = &'static str;
+
+    fn try_from(repr: KeyRepr) -> Result<Self, Self::Error> {
+        repr.try_into_key()
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<CertificateKey> for KeyRepr {
+    fn from(key: CertificateKey) -> Self {
+        let bytes = Bytes(key.as_ref().to_vec());
+        match key {
+            CertificateKey::Ed25519(_) => KeyRepr::Ed25519(bytes),
+            CertificateKey::Sr25519(_) => KeyRepr::Sr25519(bytes),
+            CertificateKey::Ecdsa(_) => KeyRepr::Ecdsa(bytes),
+            CertificateKey::P256(_) => KeyRepr::P256(bytes),
+        }
+    }
+}
+
+/// Signature of a certificate, verification dispatches on the algorithm of the
+/// signer key and fails if it does not match the one of the signature
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum CertificateSignature {
+    Ed25519(ed25519::Signature),
+    Sr25519(sr25519::Signature),
+    /// Recoverable secp256k1 signature of the blake2-256 hash of the message
+    Ecdsa(ecdsa::Signature),
+    /// Raw `r || s` P-256 signature of the SHA-256 hash of the message
+    P256(H512),
+}
+
+impl Default for CertificateSignature {
+    fn default() -> Self {
+        CertificateSignature::Sr25519(Default::default())
+    }
+}
+
+impl From<ed25519::Signature> for CertificateSignature {
+    fn from(signature: ed25519::Signature) -> Self {
+        CertificateSignature::Ed25519(signature)
+    }
+}
+
+impl From<sr25519::Signature> for CertificateSignature {
+    fn from(signature: sr25519::Signature) -> Self {
+        CertificateSignature::Sr25519(signature)
+    }
+}
+
+impl From<ecdsa::Signature> for CertificateSignature {
+    fn from(signature: ecdsa::Signature) -> Self {
+        CertificateSignature::Ecdsa(signature)
+    }
+}
+
+fn p256_verify(signature: &H512, message: &[u8], key: &P256Public) -> bool {
+    use p256::ecdsa::{signature::Verifier, Signature, VerifyingKey};
+
+    let key = match VerifyingKey::from_sec1_bytes(key.as_ref()) {
+        Ok(key) => key,
+        Err(_) => return false,
+    };
+    let signature = match Signature::try_from(signature.as_bytes()) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+
+    key.verify(message, &signature).is_ok()
+}
+
+impl Verify for CertificateSignature {
+    type Signer = CertificateKey;
+
+    fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &CertificateKey) -> bool {
+        match (self, signer) {
+            (CertificateSignature::Ed25519(signature), CertificateKey::Ed25519(key)) => {
+                signature.verify(msg, key)
+            }
+            (CertificateSignature::Sr25519(signature), CertificateKey::Sr25519(key)) => {
+                signature.verify(msg, key)
+            }
+            (CertificateSignature::Ecdsa(signature), CertificateKey::Ecdsa(key)) => {
+                signature.verify(msg, key)
+            }
+            (CertificateSignature::P256(signature), CertificateKey::P256(key)) => {
+                p256_verify(signature, msg.get(), key)
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,259 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Native implementation of the off-chain certificates issued by root certificates
+//! registered in the `root-of-trust` pallet. This mirrors the version `0.1` of the
+//! nodejs `Certificate` class but relies on SCALE to encode the signed payload,
+//! the signature covers the encoding of every field but the signature itself.
+
+#[cfg(test)]
+mod tests;
+
+mod key;
+
+pub use key::{CertificateKey, CertificateSignature, P256Public};
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
+use sp_core::Pair;
+use sp_runtime::{
+    traits::{IdentifyAccount, Verify},
+    RuntimeDebug,
+};
+use sp_std::prelude::Vec;
+
+/// Supported certificate format versions
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Version {
+    V0_1,
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::V0_1
+    }
+}
+
+/// Content of a certificate which is covered by its signature
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Payload<AccountId, Moment> {
+    pub version: Version,
+    pub device: AccountId,
+    pub signer: AccountId,
+    pub created: Moment,
+    pub expires: Moment,
+}
+
+impl<AccountId: Encode, Moment: Encode> Payload<AccountId, Moment> {
+    pub fn new(device: AccountId, signer: AccountId, created: Moment, expires: Moment) -> Self {
+        Payload {
+            version: Version::V0_1,
+            device,
+            signer,
+            created,
+            expires,
+        }
+    }
+
+    /// Attach a signature to the payload, the caller is responsible for it to be
+    /// correct.
+    pub fn into_certificate<Signature>(
+        self,
+        signature: Signature,
+    ) -> Certificate<AccountId, Moment, Signature> {
+        Certificate {
+            version: self.version,
+            device: self.device,
+            signer: self.signer,
+            created: self.created,
+            expires: self.expires,
+            signature,
+        }
+    }
+
+    /// Sign the payload with `pair`, `pair` should match `signer`.
+    #[cfg(feature = "std")]
+    pub fn sign<P: Pair, Signature: From<P::Signature>>(
+        self,
+        pair: &P,
+    ) -> Certificate<AccountId, Moment, Signature> {
+        let signature = pair.sign(&self.encode()[..]);
+        self.into_certificate(signature.into())
+    }
+}
+
+/// A signed certificate, `signer` is the public key of the root certificate and
+/// `device` the public key of the certified child.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Certificate<AccountId, Moment, Signature> {
+    pub version: Version,
+    pub device: AccountId,
+    pub signer: AccountId,
+    pub created: Moment,
+    pub expires: Moment,
+    pub signature: Signature,
+}
+
+impl<AccountId, Moment, Signature> Certificate<AccountId, Moment, Signature>
+where
+    AccountId: Encode,
+    Moment: Encode + PartialOrd,
+{
+    /// Bytes covered by the certificate's signature
+    pub fn message(&self) -> Vec<u8> {
+        (
+            &self.version,
+            &self.device,
+            &self.signer,
+            &self.created,
+            &self.expires,
+        )
+            .encode()
+    }
+
+    /// Whether `signature` was produced by `signer` over the certificate's content.
+    /// This does not check any on-chain state nor the expiry.
+    pub fn verify<Signer>(&self) -> bool
+    where
+        Signature: Verify<Signer = Signer>,
+        Signer: IdentifyAccount<AccountId = AccountId>,
+    {
+        self.signature.verify(&self.message()[..], &self.signer)
+    }
+
+    /// Whether the certificate is expired at `now`
+    pub fn is_expired(&self, now: Moment) -> bool {
+        self.expires <= now
+    }
+}
+
+/// Status of a root certificate slot
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum CertificateStatus {
+    /// The slot can be used to issue certificates
+    Valid,
+    /// No slot was booked for this certificate
+    NotFound,
+    /// The slot owner is no longer a member
+    OwnerNotAMember,
+    /// The slot was revoked by its owner
+    Revoked,
+    /// The slot was not renewed in time
+    Expired,
+    /// The slot key was rotated, certificates it signed before the rotation are
+    /// now checked against the new root certificate
+    Superseded,
+}
+
+impl CertificateStatus {
+    pub fn is_valid(&self) -> bool {
+        *self == CertificateStatus::Valid
+    }
+}
+
+/// Outcome of the verification of a certificate chain, when a certificate is
+/// found invalid its id is returned alongside the reason.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum VerificationResult<CertificateId> {
+    /// Every certificate in the chain is valid
+    Valid,
+    /// The certificate, or one of its issuers, expired
+    Expired(CertificateId),
+    /// The certificate was revoked by its issuer
+    Revoked(CertificateId),
+    /// The owner of the root certificate is no longer a member
+    RootNotAMember(CertificateId),
+    /// The certificate was not signed by its announced signer
+    BadSignature(CertificateId),
+    /// The chain does not lead to a root certificate that we know of
+    UnknownRoot(CertificateId),
+    /// The chain is empty, too long, its certificates do not link together or a
+    /// certificate issuing another one is not a registered intermediate
+    InvalidChain,
+    /// The certificate was signed by a root certificate after it was rotated
+    Superseded(CertificateId),
+    /// The certificate was temporarily suspended by its issuer
+    Suspended(CertificateId),
+}
+
+impl<CertificateId> VerificationResult<CertificateId> {
+    pub fn is_valid(&self) -> bool {
+        match self {
+            VerificationResult::Valid => true,
+            _ => false,
+        }
+    }
+}
+
+/// Why a certificate was revoked, modelled after the X.509 CRL reason codes
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum RevocationReason {
+    /// No reason was given
+    Unspecified,
+    /// The private key of the certificate was compromised
+    KeyCompromise,
+    /// The private key of the issuer was compromised
+    CACompromise,
+    /// The certificate was replaced by another one
+    Superseded,
+    /// The certificate is no longer needed
+    CessationOfOperation,
+    /// The certificate is put on hold
+    CertificateHold,
+}
+
+impl Default for RevocationReason {
+    fn default() -> Self {
+        RevocationReason::Unspecified
+    }
+}
+
+impl RevocationReason {
+    /// Whether the private key of the certificate, or of its issuer, can no longer be
+    /// trusted
+    pub fn is_compromise(&self) -> bool {
+        match self {
+            RevocationReason::KeyCompromise | RevocationReason::CACompromise => true,
+            _ => false,
+        }
+    }
+}
+
+/// When and why a certificate was revoked
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct Revocation<BlockNumber> {
+    pub revoked_at: BlockNumber,
+    pub reason: RevocationReason,
+}
+
+/// A root certificate booked in a slot of the `root-of-trust` pallet
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct RootCertificate<AccountId, CertificateId, BlockNumber> {
+    pub owner: AccountId,
+    pub key: CertificateId,
+    pub created: BlockNumber,
+    pub renewed: BlockNumber,
+    pub revoked: bool,
+    pub validity: BlockNumber,
+}
+
+/// An intermediate certificate authority, it is registered under a root slot
+/// (`root`) either directly or through other intermediates (`parent`).
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct IntermediateCertificate<CertificateId, BlockNumber> {
+    pub key: CertificateId,
+    pub parent: CertificateId,
+    pub root: CertificateId,
+    /// How many intermediates can still be chained below this one
+    pub path_length: u32,
+    pub created: BlockNumber,
+    pub expires: BlockNumber,
+}
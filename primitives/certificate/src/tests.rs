@@ -0,0 +1,279 @@
+use super::*;
+
+use hex_literal::hex;
+use sp_core::{ecdsa, ed25519, sr25519};
+
+/// Certificates as handled by the runtime: `CertificateId = CertificateKey` and
+/// `BlockNumber = u32`
+type TestCertificate = Certificate<CertificateKey, u32, CertificateSignature>;
+
+// Interop vector shared with the nodejs `Certificate` class, generated with an
+// independent ed25519 implementation. Signer and device are derived from the seeds
+// `[1; 32]` and `[2; 32]`.
+const SIGNER: [u8; 32] = hex!("8a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c");
+const DEVICE: [u8; 32] = hex!("8139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b394");
+const CREATED: u32 = 100;
+const EXPIRES: u32 = 432_100;
+const MESSAGE: [u8; 75] = hex!("00008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b394008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c64000000e4970600");
+const SIGNATURE: [u8; 64] = hex!("22d6bbb906521a5eb2ce46a4eb0d4a7e20ed7c6ff9f85d8ea9cb57b2001cc3e38a57f15133a3ec5214c4d23f16b5aaaf4adb7dadba618dbf096e7cfc86ad5403");
+
+fn vector_payload() -> Payload<CertificateKey, u32> {
+    Payload::new(
+        ed25519::Public::from_raw(DEVICE).into(),
+        ed25519::Public::from_raw(SIGNER).into(),
+        CREATED,
+        EXPIRES,
+    )
+}
+
+fn vector_signature() -> CertificateSignature {
+    ed25519::Signature::from_raw(SIGNATURE).into()
+}
+
+#[test]
+fn payload_encoding_matches_vector() {
+    assert_eq!(vector_payload().encode(), MESSAGE.to_vec());
+}
+
+#[test]
+fn message_matches_payload_encoding() {
+    let certificate: TestCertificate = vector_payload().into_certificate(vector_signature());
+
+    assert_eq!(certificate.message(), MESSAGE.to_vec());
+}
+
+#[test]
+fn ed25519_signature_matches_vector() {
+    let pair = ed25519::Pair::from_seed(&[1; 32]);
+    assert_eq!(pair.public().0, SIGNER);
+
+    let certificate: TestCertificate = vector_payload().sign(&pair);
+    assert_eq!(certificate.signature, vector_signature());
+    assert_eq!(certificate.verify(), true);
+}
+
+#[test]
+fn certificate_encoding_roundtrip() {
+    let certificate: TestCertificate = vector_payload().into_certificate(vector_signature());
+
+    let mut expected = MESSAGE.to_vec();
+    expected.push(0); // `CertificateSignature::Ed25519`
+    expected.extend_from_slice(&SIGNATURE);
+
+    let encoded = certificate.encode();
+    assert_eq!(encoded, expected);
+    assert_eq!(TestCertificate::decode(&mut &encoded[..]), Ok(certificate));
+}
+
+#[test]
+fn sr25519_sign_and_verify() {
+    let pair = sr25519::Pair::from_seed(&[1; 32]);
+    let certificate: TestCertificate = Payload::new(
+        ed25519::Public::from_raw(DEVICE).into(),
+        pair.public().into(),
+        CREATED,
+        EXPIRES,
+    )
+    .sign(&pair);
+
+    assert_eq!(certificate.verify(), true);
+}
+
+#[test]
+fn tampered_certificate_does_not_verify() {
+    let pair = ed25519::Pair::from_seed(&[1; 32]);
+    let mut certificate: TestCertificate = vector_payload().sign(&pair);
+    certificate.expires += 1;
+
+    assert_eq!(certificate.verify(), false);
+}
+
+#[test]
+fn certificate_signed_by_someone_else_does_not_verify() {
+    let pair = ed25519::Pair::from_seed(&[2; 32]);
+    let certificate: TestCertificate = vector_payload().sign(&pair);
+
+    assert_eq!(certificate.verify(), false);
+}
+
+#[test]
+fn unsupported_version_does_not_decode() {
+    let mut encoded = MESSAGE.to_vec();
+    encoded[0] = 1;
+
+    assert_eq!(
+        Payload::<CertificateKey, u32>::decode(&mut &encoded[..]).is_err(),
+        true
+    );
+}
+
+#[test]
+fn expiry() {
+    let certificate: TestCertificate = vector_payload().into_certificate(vector_signature());
+
+    assert_eq!(certificate.is_expired(EXPIRES - 1), false);
+    assert_eq!(certificate.is_expired(EXPIRES), true);
+}
+
+// Generated with an independent P-256 implementation from the private key `[1; 32]`
+const P256_PUBLIC: [u8; 33] =
+    hex!("026ff03b949241ce1dadd43519e6960e0a85b41a69a05c328103aa2bce1594ca16");
+const P256_MESSAGE: &[u8] = b"pki certificate";
+const P256_SIGNATURE: [u8; 64] = hex!("0ee37f3bdbe4cd200a19d84ff4f63bce7e737966a042aa5032b5c6ac399b216b20cff1aea33a5d4333a31313bd3b6cb550c18c765062b71dac3372c8362ec8cc");
+
+fn keyed_certificate<P: sp_core::Pair>(pair: &P) -> TestCertificate
+where
+    CertificateKey: From<P::Public>,
+    CertificateSignature: From<P::Signature>,
+{
+    let device = sr25519::Pair::from_seed(&[2; 32]).public().into();
+    Payload::new(device, pair.public().into(), CREATED, EXPIRES).sign(pair)
+}
+
+#[test]
+fn certificate_keys_verify_per_algorithm() {
+    assert_eq!(
+        keyed_certificate(&ed25519::Pair::from_seed(&[1; 32])).verify(),
+        true
+    );
+    assert_eq!(
+        keyed_certificate(&sr25519::Pair::from_seed(&[1; 32])).verify(),
+        true
+    );
+    assert_eq!(
+        keyed_certificate(&ecdsa::Pair::from_seed(&[1; 32])).verify(),
+        true
+    );
+}
+
+#[test]
+fn certificate_keys_are_not_hashed() {
+    let pair = ecdsa::Pair::from_seed(&[1; 32]);
+    let certificate = keyed_certificate(&pair);
+
+    assert_eq!(certificate.signer.as_ref(), pair.public().as_ref());
+}
+
+#[test]
+fn mismatching_algorithms_do_not_verify() {
+    let mut certificate = keyed_certificate(&ed25519::Pair::from_seed(&[1; 32]));
+    // Same public key bytes but claimed to be a sr25519 key
+    certificate.signer = CertificateKey::Sr25519(sr25519::Public::from_raw(SIGNER));
+
+    assert_eq!(certificate.verify(), false);
+}
+
+#[test]
+fn p256_signature_matches_vector() {
+    let key = CertificateKey::P256(P256Public(P256_PUBLIC));
+    let signature = CertificateSignature::P256(P256_SIGNATURE.into());
+
+    assert_eq!(signature.verify(P256_MESSAGE, &key), true);
+    assert_eq!(signature.verify(&b"another message"[..], &key), false);
+}
+
+#[test]
+fn certificate_keys_text_roundtrip() {
+    let key = CertificateKey::P256(P256Public(P256_PUBLIC));
+    let text = key.to_string();
+
+    assert_eq!(
+        text,
+        "p256:0x026ff03b949241ce1dadd43519e6960e0a85b41a69a05c328103aa2bce1594ca16"
+    );
+    assert_eq!(text.parse::<CertificateKey>(), Ok(key));
+    assert_eq!("p256:0x02".parse::<CertificateKey>().is_err(), true);
+    assert_eq!("rsa:0x02".parse::<CertificateKey>().is_err(), true);
+}
+
+// Vectors of the runtime certificate signed by a key of each algorithm, generated
+// with independent implementations. Keys and signatures are SCALE encoded, prefixed
+// by the index of their variant. The sr25519 signer is `//Alice`, the ecdsa
+// (secp256k1) and P-256 ones are derived from the private key `[1; 32]`. sr25519
+// signatures are randomized and thus only have an encoding vector.
+const ED25519_KEY: [u8; 33] =
+    hex!("008a88e3dd7409f195fd52db2d3cba5d72ca6709bf1d94121bf3748801b40f6f5c");
+const SR25519_KEY: [u8; 33] =
+    hex!("01d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d");
+const ECDSA_KEY: [u8; 34] =
+    hex!("02031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f");
+const P256_KEY: [u8; 34] =
+    hex!("03026ff03b949241ce1dadd43519e6960e0a85b41a69a05c328103aa2bce1594ca16");
+const SR25519_PAYLOAD: [u8; 75] = hex!("00008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b39401d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d64000000e4970600");
+const ECDSA_PAYLOAD: [u8; 76] = hex!("00008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b39402031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f64000000e4970600");
+const P256_PAYLOAD: [u8; 76] = hex!("00008139770ea87d175f56a35466c34c7ecccb8d8a91b4ee37a25df60f5b8fc9b39403026ff03b949241ce1dadd43519e6960e0a85b41a69a05c328103aa2bce1594ca1664000000e4970600");
+const ECDSA_PAYLOAD_SIGNATURE: [u8; 66] = hex!("028688f6c8af83c7aa9411369d604184096ac54a48afbc8d205f50fc3540c0c6c3296d3eebd75b138bbde1a5a78fb1c2d259ba418407107a3bd06096893de30a1701");
+const P256_PAYLOAD_SIGNATURE: [u8; 65] = hex!("03a804b3d83ce44a53b56f06648a60440f5cd26bba7c643bc8206a2eef3719c5e80fd3eb763a53d58c7211ca302dab50a45d40296bb78e7054b553e59f6c07a084");
+
+fn decode_key(encoded: &[u8]) -> CertificateKey {
+    CertificateKey::decode(&mut &encoded[..]).expect("valid key vector")
+}
+
+fn payload_signed_by(signer: CertificateKey) -> Payload<CertificateKey, u32> {
+    Payload::new(
+        ed25519::Public::from_raw(DEVICE).into(),
+        signer,
+        CREATED,
+        EXPIRES,
+    )
+}
+
+#[test]
+fn certificate_keys_encoding_matches_vectors() {
+    let alice = sr25519::Pair::from_string("//Alice", None).expect("valid seed");
+
+    for (key, encoded) in &[
+        (
+            CertificateKey::from(ed25519::Public::from_raw(SIGNER)),
+            &ED25519_KEY[..],
+        ),
+        (alice.public().into(), &SR25519_KEY[..]),
+        (
+            ecdsa::Pair::from_seed(&[1; 32]).public().into(),
+            &ECDSA_KEY[..],
+        ),
+        (P256Public(P256_PUBLIC).into(), &P256_KEY[..]),
+    ] {
+        assert_eq!(key.encode(), encoded.to_vec());
+        assert_eq!(decode_key(encoded), *key);
+    }
+}
+
+#[test]
+fn payload_encoding_matches_vectors_per_algorithm() {
+    for (key, payload) in &[
+        (&ED25519_KEY[..], &MESSAGE[..]),
+        (&SR25519_KEY[..], &SR25519_PAYLOAD[..]),
+        (&ECDSA_KEY[..], &ECDSA_PAYLOAD[..]),
+        (&P256_KEY[..], &P256_PAYLOAD[..]),
+    ] {
+        assert_eq!(
+            payload_signed_by(decode_key(key)).encode(),
+            payload.to_vec()
+        );
+    }
+}
+
+#[test]
+fn signatures_match_vectors_per_algorithm() {
+    for (key, signature) in &[
+        (&ECDSA_KEY[..], &ECDSA_PAYLOAD_SIGNATURE[..]),
+        (&P256_KEY[..], &P256_PAYLOAD_SIGNATURE[..]),
+    ] {
+        let signature =
+            CertificateSignature::decode(&mut &signature[..]).expect("valid signature vector");
+        let certificate: TestCertificate =
+            payload_signed_by(decode_key(key)).into_certificate(signature);
+
+        assert_eq!(certificate.verify(), true);
+    }
+}
+
+#[test]
+fn sr25519_vector_signer_signs_and_verifies() {
+    let alice = sr25519::Pair::from_string("//Alice", None).expect("valid seed");
+    let certificate: TestCertificate = payload_signed_by(decode_key(&SR25519_KEY)).sign(&alice);
+
+    assert_eq!(certificate.message(), SR25519_PAYLOAD.to_vec());
+    assert_eq!(certificate.verify(), true);
+}
@@ -0,0 +1,118 @@
+//! Revocation lists of a root certificate rebuilt from a read proof, so that offline
+//! relying parties can check the lists returned by `rootOfTrust_revocationList`
+//! against a state root they trust.
+
+use super::*;
+use pki_certificate::Revocation;
+
+/// Revocations, suspensions and revocation cutoffs in effect for every certificate
+/// chaining up to `root`
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Debug))]
+pub struct RevocationList<CertificateId, BlockNumber> {
+    pub root: CertificateId,
+    /// Status of `root` itself, none of its children are valid unless it is valid
+    pub root_status: CertificateStatus,
+    /// When and why `root` was revoked, if it was
+    pub root_revocation: Option<Revocation<BlockNumber>>,
+    /// `root`, the root certificates it replaced and its intermediates
+    pub issuers: Vec<CertificateId>,
+    /// Issuer, child and revocation details
+    pub revocations: Vec<(CertificateId, CertificateId, Revocation<BlockNumber>)>,
+    /// Issuer, child and the block at which it was suspended
+    pub suspensions: Vec<(CertificateId, CertificateId, BlockNumber)>,
+    /// Children issued before the given block by the issuer are revoked
+    pub revoked_before: Vec<(CertificateId, BlockNumber)>,
+}
+
+impl<'a, S, AccountId, CertificateId, BlockNumber>
+    Verifier<'a, S, AccountId, CertificateId, BlockNumber>
+where
+    S: StorageReader,
+    AccountId: Decode + PartialEq,
+    CertificateId: Encode + Decode + Clone + PartialEq,
+    BlockNumber: Decode + Add<Output = BlockNumber> + PartialOrd + Copy,
+{
+    /// Revocation list of `root`, every entry stored under the prefixes of its issuers
+    /// is read so that none of them can be left out
+    pub fn revocation_list(
+        &self,
+        root: &CertificateId,
+    ) -> Result<RevocationList<CertificateId, BlockNumber>, Error> {
+        if self.is_migrating()? {
+            return Err(Error::MigrationInProgress);
+        }
+
+        // Revocations made before a key rotation still apply to the new key
+        let mut issuers = self.rotation_history(root)?;
+        let intermediates: Option<Vec<CertificateId>> =
+            self.read(&keys::map(b"IntermediatesOf", root))?;
+        issuers.extend(intermediates.unwrap_or_default());
+
+        let mut list = RevocationList {
+            root: root.clone(),
+            root_status: self.root_certificate_status(root)?,
+            root_revocation: self.read(&keys::map(b"SlotRevocations", root))?,
+            issuers: Vec::new(),
+            revocations: Vec::new(),
+            suspensions: Vec::new(),
+            revoked_before: Vec::new(),
+        };
+        for issuer in issuers.iter() {
+            for (child, revocation) in self.read_children(b"ChildRevocations", issuer)? {
+                list.revocations.push((issuer.clone(), child, revocation));
+            }
+            for (child, block) in self.read_children(b"ChildSuspensions", issuer)? {
+                list.suspensions.push((issuer.clone(), child, block));
+            }
+
+            if let Some(cutoff) = self.read(&keys::map(b"RevokedBefore", issuer))? {
+                list.revoked_before.push((issuer.clone(), cutoff));
+            }
+        }
+        list.issuers = issuers;
+
+        Ok(list)
+    }
+
+    /// Children and values of a double map stored under `issuer`
+    fn read_children<V: Decode>(
+        &self,
+        name: &[u8],
+        issuer: &CertificateId,
+    ) -> Result<Vec<(CertificateId, V)>, Error> {
+        // The entries sharing a first key are prefixed by the same key as a simple map
+        let prefix = keys::map(name, issuer);
+
+        self.storage
+            .read_prefix(&prefix)?
+            .into_iter()
+            .map(|(key, value)| {
+                let child = keys::double_map_second_key(&prefix, &key).ok_or(Error::BadEncoding)?;
+                let value = V::decode(&mut &value[..]).map_err(|_| Error::BadEncoding)?;
+
+                Ok((child, value))
+            })
+            .collect()
+    }
+}
+
+/// Rebuild the revocation list of `root` at the block described by `header` from
+/// `proof`, the trie nodes returned by `rootOfTrust_revocationList`. The header has to
+/// come from a trusted source, the list returned by the node can then be compared
+/// with the one returned here.
+pub fn verify_revocation_list<Header, AccountId, CertificateId>(
+    header: &Header,
+    proof: Vec<Vec<u8>>,
+    root: &CertificateId,
+) -> Result<RevocationList<CertificateId, Header::Number>, Error>
+where
+    Header: HeaderT,
+    Header::Number: Decode,
+    AccountId: Decode + PartialEq,
+    CertificateId: Encode + Decode + Clone + PartialEq,
+{
+    let storage = ProofReader::<Header::Hashing>::new(*header.state_root(), proof);
+
+    Verifier::<_, AccountId, _, _>::new(&storage, *header.number()).revocation_list(root)
+}
@@ -0,0 +1,349 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Verification of certificates against the storage of the `root-of-trust` pallet
+//! without trusting the node answering the queries. The node returns a read proof
+//! of the storage entries involved (see `rootOfTrust_proveCertificate`), which is
+//! checked against the state root of a header the caller already trusts, typically
+//! a finalized header followed by a light client.
+//!
+//! The checks mirror the ones performed by the pallet itself, a storage entry
+//! missing from the proof makes the verification fail instead of being treated as
+//! absent.
+
+mod crl;
+#[cfg(test)]
+mod tests;
+
+pub use crl::{verify_revocation_list, RevocationList};
+
+use codec::{Decode, Encode};
+use core::ops::Add;
+use pki_certificate::{
+    CertificateStatus, IntermediateCertificate, RootCertificate, VerificationResult,
+};
+use sp_core::hashing::{blake2_128, blake2_256, twox_128};
+use sp_runtime::traits::{Hash, Header as HeaderT};
+use sp_std::prelude::Vec;
+use sp_trie::{read_trie_value, HashDBT, Layout, MemoryDB, TrieDB, TrieDBIterator, EMPTY_PREFIX};
+
+/// Name under which the root of trust module storage is declared
+const MODULE_PREFIX: &[u8] = b"RootOfTrustModule";
+/// Upper bound on the number of intermediates walked, the pallet enforces a lower
+/// one when intermediates are added so this only protects against malformed states
+const MAX_CHAIN_DEPTH: u32 = 32;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// A storage entry needed by the verification is not covered by the proof
+    IncompleteProof,
+    /// A storage entry could not be decoded
+    BadEncoding,
+    /// The pallet is migrating its revocations, some of them may not be where the
+    /// verifier looks for them
+    MigrationInProgress,
+}
+
+/// Read access to the pallet storage
+pub trait StorageReader {
+    /// Raw value stored under `key`, `Ok(None)` only if the key is known to be empty
+    fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error>;
+    /// Keys and raw values of the entries whose key starts with `prefix`, only if all
+    /// of them are known
+    fn read_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Error>;
+}
+
+/// Storage backed by a read proof checked against a state root
+pub struct ProofReader<H: Hash> {
+    db: MemoryDB<H>,
+    root: H::Output,
+}
+
+impl<H: Hash> ProofReader<H> {
+    pub fn new(state_root: H::Output, proof: Vec<Vec<u8>>) -> Self {
+        let mut db = MemoryDB::default();
+        for node in proof {
+            db.insert(EMPTY_PREFIX, &node);
+        }
+
+        ProofReader {
+            db,
+            root: state_root,
+        }
+    }
+}
+
+impl<H: Hash> StorageReader for ProofReader<H> {
+    fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        read_trie_value::<Layout<H>, _>(&self.db, &self.root, key)
+            .map_err(|_| Error::IncompleteProof)
+    }
+
+    fn read_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Error> {
+        // Iterating fails as soon as a node under `prefix` is missing from the proof, so
+        // entries can not be omitted
+        let trie =
+            TrieDB::<Layout<H>>::new(&self.db, &self.root).map_err(|_| Error::IncompleteProof)?;
+        TrieDBIterator::new_prefixed(&trie, prefix)
+            .map_err(|_| Error::IncompleteProof)?
+            .map(|entry| {
+                entry
+                    .map(|(key, value)| (key, value.to_vec()))
+                    .map_err(|_| Error::IncompleteProof)
+            })
+            .collect()
+    }
+}
+
+/// Storage keys of the root of trust module, they have to be kept in sync with its
+/// `decl_storage` declaration.
+pub mod keys {
+    use super::*;
+
+    fn storage_prefix(name: &[u8]) -> Vec<u8> {
+        let mut key = twox_128(MODULE_PREFIX).to_vec();
+        key.extend_from_slice(&twox_128(name));
+        key
+    }
+
+    /// Key of a plain storage value
+    pub fn value(name: &[u8]) -> Vec<u8> {
+        storage_prefix(name)
+    }
+
+    /// Key of a value stored under `map hasher(blake2_256)`, also the prefix shared
+    /// by the entries of a double map with the same first key
+    pub fn map<K: Encode>(name: &[u8], key: &K) -> Vec<u8> {
+        let mut final_key = storage_prefix(name);
+        final_key.extend_from_slice(&blake2_256(&key.encode()));
+        final_key
+    }
+
+    /// Key of a value stored under `double_map hasher(blake2_256), hasher(blake2_128_concat)`
+    pub fn double_map<K1: Encode, K2: Encode>(name: &[u8], key1: &K1, key2: &K2) -> Vec<u8> {
+        let encoded = key2.encode();
+        let mut final_key = map(name, key1);
+        final_key.extend_from_slice(&blake2_128(&encoded));
+        final_key.extend_from_slice(&encoded);
+        final_key
+    }
+
+    /// Recover the second key of a double map entry, `prefix` being the prefix of the
+    /// entries sharing its first key
+    pub fn double_map_second_key<K: Decode>(prefix: &[u8], final_key: &[u8]) -> Option<K> {
+        let offset = prefix.len() + 16;
+        if final_key.len() < offset {
+            return None;
+        }
+
+        K::decode(&mut &final_key[offset..]).ok()
+    }
+}
+
+/// Verifier of the certificates registered in the root of trust module
+pub struct Verifier<'a, S, AccountId, CertificateId, BlockNumber> {
+    storage: &'a S,
+    now: BlockNumber,
+    _marker: core::marker::PhantomData<(AccountId, CertificateId)>,
+}
+
+impl<'a, S, AccountId, CertificateId, BlockNumber>
+    Verifier<'a, S, AccountId, CertificateId, BlockNumber>
+where
+    S: StorageReader,
+    AccountId: Decode + PartialEq,
+    CertificateId: Encode + Decode + Clone + PartialEq,
+    BlockNumber: Decode + Add<Output = BlockNumber> + PartialOrd + Copy,
+{
+    /// Verify against `storage` as it is at block `now`
+    pub fn new(storage: &'a S, now: BlockNumber) -> Self {
+        Verifier {
+            storage,
+            now,
+            _marker: Default::default(),
+        }
+    }
+
+    fn read<V: Decode>(&self, key: &[u8]) -> Result<Option<V>, Error> {
+        match self.storage.read(key)? {
+            Some(data) => V::decode(&mut &data[..])
+                .map(Some)
+                .map_err(|_| Error::BadEncoding),
+            None => Ok(None),
+        }
+    }
+
+    fn contains(&self, key: &[u8]) -> Result<bool, Error> {
+        Ok(self.storage.read(key)?.is_some())
+    }
+
+    pub fn root_certificate_status(
+        &self,
+        cert: &CertificateId,
+    ) -> Result<CertificateStatus, Error> {
+        let slot: RootCertificate<AccountId, CertificateId, BlockNumber> =
+            match self.read(&keys::map(b"Slots", cert))? {
+                Some(slot) => slot,
+                // Revocations are kept once revoked slots are pruned
+                None if self.contains(&keys::map(b"SlotRevocations", cert))? => {
+                    return Ok(CertificateStatus::Revoked)
+                }
+                None => return Ok(CertificateStatus::NotFound),
+            };
+
+        let members: Vec<AccountId> = self.read(&keys::value(b"Members"))?.unwrap_or_default();
+        let revoked = slot.revoked || self.contains(&keys::map(b"SlotRevocations", cert))?;
+        let expired = slot.renewed + slot.validity <= self.now;
+
+        Ok(if !members.contains(&slot.owner) {
+            CertificateStatus::OwnerNotAMember
+        } else if revoked {
+            CertificateStatus::Revoked
+        } else if self.contains(&keys::map(b"SupersededBy", cert))? {
+            CertificateStatus::Superseded
+        } else if expired {
+            CertificateStatus::Expired
+        } else {
+            CertificateStatus::Valid
+        })
+    }
+
+    /// Whether the pallet still stores some certificates or revocations with their
+    /// legacy layout
+    fn is_migrating(&self) -> Result<bool, Error> {
+        let ids: Option<bool> = self.read(&keys::value(b"CertificateIdsMigrated"))?;
+        let revocations: Option<bool> = self.read(&keys::value(b"RevocationsMigrated"))?;
+
+        Ok(ids != Some(true) || revocations != Some(true))
+    }
+
+    fn rotation_history(&self, issuer: &CertificateId) -> Result<Vec<CertificateId>, Error> {
+        let mut history = Vec::new();
+        let mut current = Some(issuer.clone());
+        while let Some(issuer) = current {
+            current = self.read(&keys::map(b"Predecessor", &issuer))?;
+            history.push(issuer);
+        }
+
+        Ok(history)
+    }
+
+    fn is_revoked_by(
+        &self,
+        issuer: &CertificateId,
+        child: &CertificateId,
+        created: Option<BlockNumber>,
+    ) -> Result<bool, Error> {
+        for issuer in self.rotation_history(issuer)? {
+            if self.contains(&keys::double_map(b"ChildRevocations", &issuer, child))? {
+                return Ok(true);
+            }
+            if let Some(created) = created {
+                let cutoff: Option<BlockNumber> =
+                    self.read(&keys::map(b"RevokedBefore", &issuer))?;
+                if cutoff.map_or(false, |cutoff| created < cutoff) {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn is_suspended_by(
+        &self,
+        issuer: &CertificateId,
+        child: &CertificateId,
+    ) -> Result<bool, Error> {
+        for issuer in self.rotation_history(issuer)? {
+            if self.contains(&keys::double_map(b"ChildSuspensions", &issuer, child))? {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn check_issuer(
+        &self,
+        issuer: &CertificateId,
+    ) -> Result<VerificationResult<CertificateId>, Error> {
+        let mut current = issuer.clone();
+
+        for _ in 0..=MAX_CHAIN_DEPTH {
+            let intermediate: IntermediateCertificate<CertificateId, BlockNumber> = match self
+                .read(&keys::map(b"Intermediates", &current))?
+            {
+                Some(intermediate) => intermediate,
+                None => {
+                    return Ok(match self.root_certificate_status(&current)? {
+                        CertificateStatus::Valid => VerificationResult::Valid,
+                        CertificateStatus::NotFound => VerificationResult::UnknownRoot(current),
+                        CertificateStatus::OwnerNotAMember => {
+                            VerificationResult::RootNotAMember(current)
+                        }
+                        CertificateStatus::Revoked => VerificationResult::Revoked(current),
+                        CertificateStatus::Expired => VerificationResult::Expired(current),
+                        CertificateStatus::Superseded => VerificationResult::Superseded(current),
+                    })
+                }
+            };
+
+            if intermediate.expires <= self.now {
+                return Ok(VerificationResult::Expired(current));
+            }
+            if self.is_revoked_by(&intermediate.parent, &current, Some(intermediate.created))? {
+                return Ok(VerificationResult::Revoked(current));
+            }
+            if self.is_suspended_by(&intermediate.parent, &current)? {
+                return Ok(VerificationResult::Suspended(current));
+            }
+
+            current = intermediate.parent;
+        }
+
+        Ok(VerificationResult::InvalidChain)
+    }
+
+    /// Same as the pallet `child_certificate_status`
+    pub fn child_certificate_status(
+        &self,
+        issuer: &CertificateId,
+        child: &CertificateId,
+    ) -> Result<VerificationResult<CertificateId>, Error> {
+        if issuer == child {
+            return Ok(VerificationResult::InvalidChain);
+        }
+        if self.is_migrating()? {
+            return Err(Error::MigrationInProgress);
+        }
+        if self.is_revoked_by(issuer, child, None)? {
+            return Ok(VerificationResult::Revoked(child.clone()));
+        }
+        if self.is_suspended_by(issuer, child)? {
+            return Ok(VerificationResult::Suspended(child.clone()));
+        }
+
+        self.check_issuer(issuer)
+    }
+}
+
+/// Check that `child` is a valid certificate issued by `issuer` at the block
+/// described by `header`, `proof` being the trie nodes returned by
+/// `rootOfTrust_proveCertificate`. The header has to come from a trusted source.
+pub fn verify_certificate<Header, AccountId, CertificateId>(
+    header: &Header,
+    proof: Vec<Vec<u8>>,
+    issuer: &CertificateId,
+    child: &CertificateId,
+) -> Result<VerificationResult<CertificateId>, Error>
+where
+    Header: HeaderT,
+    Header::Number: Decode,
+    AccountId: Decode + PartialEq,
+    CertificateId: Encode + Decode + Clone + PartialEq,
+{
+    let storage = ProofReader::<Header::Hashing>::new(*header.state_root(), proof);
+
+    Verifier::<_, AccountId, _, _>::new(&storage, *header.number())
+        .child_certificate_status(issuer, child)
+}
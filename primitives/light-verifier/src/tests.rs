@@ -0,0 +1,304 @@
+use super::*;
+use pki_certificate::RootCertificate;
+use sp_runtime::{generic, traits::BlakeTwo256};
+use sp_state_machine::{prove_read, Backend, InMemoryBackend};
+use std::{cell::RefCell, collections::HashMap};
+
+type Header = generic::Header<u64, BlakeTwo256>;
+
+const OWNER: u64 = 1;
+const ROOT: u64 = 2;
+const CHILD: u64 = 3;
+const REVOKED_CHILD: u64 = 4;
+const PRUNED_ROOT: u64 = 5;
+const SUSPENDED_CHILD: u64 = 6;
+
+fn state() -> HashMap<Vec<u8>, Vec<u8>> {
+    let mut state = HashMap::new();
+    state.insert(keys::value(b"Members"), vec![OWNER].encode());
+    state.insert(keys::value(b"CertificateIdsMigrated"), true.encode());
+    state.insert(keys::value(b"RevocationsMigrated"), true.encode());
+    state.insert(
+        keys::map(b"Slots", &ROOT),
+        RootCertificate {
+            owner: OWNER,
+            key: ROOT,
+            created: 0u64,
+            renewed: 0,
+            revoked: false,
+            validity: 100,
+        }
+        .encode(),
+    );
+    state.insert(
+        keys::double_map(b"ChildRevocations", &ROOT, &REVOKED_CHILD),
+        pki_certificate::Revocation {
+            revoked_at: 5u64,
+            reason: pki_certificate::RevocationReason::KeyCompromise,
+        }
+        .encode(),
+    );
+    state.insert(
+        keys::double_map(b"ChildSuspensions", &ROOT, &SUSPENDED_CHILD),
+        4u64.encode(),
+    );
+    state.insert(keys::map(b"RevokedBefore", &ROOT), 2u64.encode());
+    // Revoked and pruned, only its revocation is left
+    state.insert(
+        keys::map(b"SlotRevocations", &PRUNED_ROOT),
+        pki_certificate::Revocation {
+            revoked_at: 5u64,
+            reason: pki_certificate::RevocationReason::CessationOfOperation,
+        }
+        .encode(),
+    );
+
+    state
+}
+
+/// Header at `number` committing to `state` and a proof of the keys read to verify
+/// `child` of `root`
+fn header_and_proof(number: u64, root: u64, child: u64) -> (Header, Vec<Vec<u8>>) {
+    header_and_proof_of(state(), number, root, child)
+}
+
+fn header_and_proof_of(
+    state: HashMap<Vec<u8>, Vec<u8>>,
+    number: u64,
+    root: u64,
+    child: u64,
+) -> (Header, Vec<Vec<u8>>) {
+    let backend = InMemoryBackend::<BlakeTwo256>::from(state);
+    let state_root = backend.storage_root(std::iter::empty()).0;
+
+    let keys = vec![
+        keys::value(b"CertificateIdsMigrated"),
+        keys::value(b"RevocationsMigrated"),
+        keys::value(b"Members"),
+        keys::map(b"Slots", &root),
+        keys::map(b"SlotRevocations", &root),
+        keys::map(b"Intermediates", &root),
+        keys::map(b"SupersededBy", &root),
+        keys::map(b"Predecessor", &root),
+        keys::double_map(b"ChildRevocations", &root, &child),
+        keys::double_map(b"ChildSuspensions", &root, &child),
+    ];
+    let proof = prove_read(backend, keys).expect("keys can be proven");
+
+    let header = Header::new(
+        number,
+        Default::default(),
+        state_root,
+        Default::default(),
+        Default::default(),
+    );
+
+    (header, proof.iter_nodes().collect())
+}
+
+#[test]
+fn valid_child_is_verified() {
+    let (header, proof) = header_and_proof(10, ROOT, CHILD);
+
+    assert_eq!(
+        verify_certificate::<_, u64, _>(&header, proof, &ROOT, &CHILD),
+        Ok(VerificationResult::Valid)
+    );
+}
+
+#[test]
+fn revoked_child_is_reported() {
+    let (header, proof) = header_and_proof(10, ROOT, REVOKED_CHILD);
+
+    assert_eq!(
+        verify_certificate::<_, u64, _>(&header, proof, &ROOT, &REVOKED_CHILD),
+        Ok(VerificationResult::Revoked(REVOKED_CHILD))
+    );
+}
+
+#[test]
+fn nothing_is_verified_during_migrations() {
+    for flag in &[&b"CertificateIdsMigrated"[..], &b"RevocationsMigrated"[..]] {
+        let mut state = state();
+        state.insert(keys::value(flag), false.encode());
+        let (header, proof) = header_and_proof_of(state, 10, ROOT, CHILD);
+
+        assert_eq!(
+            verify_certificate::<_, u64, _>(&header, proof, &ROOT, &CHILD),
+            Err(Error::MigrationInProgress)
+        );
+    }
+}
+
+#[test]
+fn expiry_is_checked_against_the_header() {
+    let (header, proof) = header_and_proof(100, ROOT, CHILD);
+
+    assert_eq!(
+        verify_certificate::<_, u64, _>(&header, proof, &ROOT, &CHILD),
+        Ok(VerificationResult::Expired(ROOT))
+    );
+}
+
+#[test]
+fn pruned_revoked_root_is_reported() {
+    let (header, proof) = header_and_proof(10, PRUNED_ROOT, CHILD);
+
+    assert_eq!(
+        verify_certificate::<_, u64, _>(&header, proof, &PRUNED_ROOT, &CHILD),
+        Ok(VerificationResult::Revoked(PRUNED_ROOT))
+    );
+}
+
+#[test]
+fn missing_entries_fail_the_verification() {
+    // The proof does not tell whether `REVOKED_CHILD` was revoked
+    let (header, proof) = header_and_proof(10, ROOT, CHILD);
+
+    assert_eq!(
+        verify_certificate::<_, u64, _>(&header, proof, &ROOT, &REVOKED_CHILD),
+        Err(Error::IncompleteProof)
+    );
+}
+
+#[test]
+fn proof_must_match_the_state_root() {
+    let (mut header, proof) = header_and_proof(10, ROOT, REVOKED_CHILD);
+    header.state_root = Default::default();
+
+    assert_eq!(
+        verify_certificate::<_, u64, _>(&header, proof, &ROOT, &REVOKED_CHILD),
+        Err(Error::IncompleteProof)
+    );
+}
+
+#[test]
+fn second_keys_can_be_recovered() {
+    let prefix = keys::map(b"ChildRevocations", &ROOT);
+    let key = keys::double_map(b"ChildRevocations", &ROOT, &REVOKED_CHILD);
+
+    assert_eq!(
+        keys::double_map_second_key::<u64>(&prefix, &key),
+        Some(REVOKED_CHILD)
+    );
+}
+
+/// Storage remembering the keys read through it, like the node does to build proofs
+struct RecordingReader {
+    state: HashMap<Vec<u8>, Vec<u8>>,
+    keys: RefCell<Vec<Vec<u8>>>,
+}
+
+impl StorageReader for RecordingReader {
+    fn read(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        self.keys.borrow_mut().push(key.to_vec());
+        Ok(self.state.get(key).cloned())
+    }
+
+    fn read_prefix(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Error> {
+        self.read(prefix)?;
+        let mut entries = self
+            .state
+            .iter()
+            .filter(|(key, _)| key.starts_with(prefix))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        entries.sort();
+        for (key, _) in entries.iter() {
+            self.read(key)?;
+        }
+
+        Ok(entries)
+    }
+}
+
+/// Revocation list of `root` built by the node, along with a header at `number` and
+/// the proof it would return
+fn revocation_list_and_proof(
+    number: u64,
+    root: u64,
+) -> (RevocationList<u64, u64>, Header, Vec<Vec<u8>>) {
+    let reader = RecordingReader {
+        state: state(),
+        keys: RefCell::new(Vec::new()),
+    };
+    let list = Verifier::<_, u64, _, _>::new(&reader, number)
+        .revocation_list(&root)
+        .expect("state is complete");
+
+    let backend = InMemoryBackend::<BlakeTwo256>::from(state());
+    let state_root = backend.storage_root(std::iter::empty()).0;
+    let proof = prove_read(backend, reader.keys.into_inner()).expect("keys can be proven");
+    let header = Header::new(
+        number,
+        Default::default(),
+        state_root,
+        Default::default(),
+        Default::default(),
+    );
+
+    (list, header, proof.iter_nodes().collect())
+}
+
+#[test]
+fn revocation_list_is_rebuilt_from_its_proof() {
+    let (list, header, proof) = revocation_list_and_proof(10, ROOT);
+
+    assert_eq!(list.root_status, CertificateStatus::Valid);
+    assert_eq!(list.issuers, vec![ROOT]);
+    assert_eq!(
+        list.revocations,
+        vec![(
+            ROOT,
+            REVOKED_CHILD,
+            pki_certificate::Revocation {
+                revoked_at: 5,
+                reason: pki_certificate::RevocationReason::KeyCompromise,
+            }
+        )]
+    );
+    assert_eq!(list.suspensions, vec![(ROOT, SUSPENDED_CHILD, 4)]);
+    assert_eq!(list.revoked_before, vec![(ROOT, 2)]);
+
+    assert_eq!(
+        verify_revocation_list::<_, u64, _>(&header, proof, &ROOT),
+        Ok(list)
+    );
+}
+
+#[test]
+fn revocation_list_reports_revoked_roots() {
+    let (list, header, proof) = revocation_list_and_proof(10, PRUNED_ROOT);
+
+    assert_eq!(list.root_status, CertificateStatus::Revoked);
+    assert_eq!(
+        list.root_revocation,
+        Some(pki_certificate::Revocation {
+            revoked_at: 5,
+            reason: pki_certificate::RevocationReason::CessationOfOperation,
+        })
+    );
+    assert_eq!(
+        verify_revocation_list::<_, u64, _>(&header, proof, &PRUNED_ROOT),
+        Ok(list)
+    );
+}
+
+#[test]
+fn revocation_list_needs_a_complete_proof() {
+    let (_, header, proof) = revocation_list_and_proof(10, ROOT);
+
+    // Entries under `ROOT` are missing from the proof built for `PRUNED_ROOT`
+    let (_, _, other_proof) = revocation_list_and_proof(10, PRUNED_ROOT);
+    assert_eq!(
+        verify_revocation_list::<_, u64, _>(&header, other_proof, &ROOT),
+        Err(Error::IncompleteProof)
+    );
+
+    let mut header = header;
+    header.state_root = Default::default();
+    assert_eq!(
+        verify_revocation_list::<_, u64, _>(&header, proof, &ROOT),
+        Err(Error::IncompleteProof)
+    );
+}
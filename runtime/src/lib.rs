@@ -8,13 +8,18 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+mod weights;
+
 use grandpa::fg_primitives;
 use grandpa::AuthorityList as GrandpaAuthorityList;
+use pki_certificate::{
+    CertificateKey, CertificateSignature, CertificateStatus, Revocation, VerificationResult,
+};
 use sp_api::impl_runtime_apis;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
-use sp_core::OpaqueMetadata;
+use sp_core::{ed25519, OpaqueMetadata};
 use sp_runtime::traits::{
-    BlakeTwo256, Block as BlockT, ConvertInto, IdentifyAccount, IdentityLookup, Verify,
+    BlakeTwo256, Block as BlockT, Convert, ConvertInto, IdentifyAccount, IdentityLookup, Verify,
 };
 use sp_runtime::{
     create_runtime_str, generic, impl_opaque_keys, transaction_validity::TransactionValidity,
@@ -49,6 +54,9 @@ pub type AccountId = <<Signature as Verify>::Signer as IdentifyAccount>::Account
 /// never know...
 pub type AccountIndex = u32;
 
+/// An off-chain certificate, signed by a root or intermediate certificate.
+pub type Certificate = pallet_root_of_trust::CertificateOf<Runtime>;
+
 /// Balance of an account.
 pub type Balance = u128;
 
@@ -90,7 +98,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("nodle-pki"),
     impl_name: create_runtime_str!("nodle-pki"),
     authoring_version: 1,
-    spec_version: 3,
+    spec_version: 4,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
 };
@@ -115,6 +123,7 @@ pub fn native_version() -> NativeVersion {
 
 parameter_types! {
     pub const BlockHashCount: BlockNumber = 250;
+    /// One second of execution, weights are expressed in nanoseconds
     pub const MaximumBlockWeight: Weight = 1_000_000_000;
     pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
     pub const MaximumBlockLength: u32 = 5 * 1024 * 1024;
@@ -222,7 +231,11 @@ parameter_types! {
     pub const MinimumChallengeAmount: Balance = 10_000_000;
     pub const FinalizeApplicationPeriod: BlockNumber = 100;
     pub const FinalizeChallengePeriod: BlockNumber = 1000;
+    pub const CommitPeriod: BlockNumber = 500;
     pub const LoosersSlash: Perbill = Perbill::from_percent(25); // Take 1/4 of the betted tokens
+    pub const UnrevealedSlash: Perbill = Perbill::from_percent(10);
+    pub const ExitCooldown: BlockNumber = 100;
+    pub const MaxVoters: u32 = 1_000;
 }
 
 impl pallet_tcr::Trait for Runtime {
@@ -233,7 +246,13 @@ impl pallet_tcr::Trait for Runtime {
     type MinimumChallengeAmount = MinimumChallengeAmount;
     type FinalizeApplicationPeriod = FinalizeApplicationPeriod;
     type FinalizeChallengePeriod = FinalizeChallengePeriod;
+    type CommitPeriod = CommitPeriod;
     type LoosersSlash = LoosersSlash;
+    type UnrevealedSlash = UnrevealedSlash;
+    type ExitCooldown = ExitCooldown;
+    type MaxVoters = MaxVoters;
+    type MaxMigratedPerBlock = MaxMigratedPerBlock;
+    type WeightInfo = weights::pallet_tcr::WeightInfo;
     type ChangeMembers = pallet_root_of_trust::Module<Runtime>;
 }
 
@@ -241,15 +260,47 @@ parameter_types! {
     pub const SlotBookingCost: Balance = 100;
     pub const SlotRenewingCost: Balance = 100;
     pub const SlotValidity: BlockNumber = 1_000_000_000;
+    pub const MinSlotValidity: BlockNumber = 7 * DAYS;
+    pub const MaxSlotValidity: BlockNumber = 2_000_000_000;
+    pub const MaxPathLength: u32 = 3;
+    pub const SlotGracePeriod: BlockNumber = 7 * DAYS;
+    pub const MaxPrunedPerBlock: u32 = 50;
+    pub const MaxExpiredPerBlock: u32 = 100;
+    pub const MaxRevocationsPerCall: u32 = 1_000;
+    pub const MaxMigratedPerBlock: u32 = 1_000;
+}
+
+/// Certificates used to be identified by their `AccountId`, and were all issued with
+/// ed25519 keys by our tooling. Their account ids are thus their raw ed25519 keys.
+pub struct LegacyCertificateIds;
+
+impl Convert<AccountId, CertificateKey> for LegacyCertificateIds {
+    fn convert(account: AccountId) -> CertificateKey {
+        let key: &[u8; 32] = account.as_ref();
+        CertificateKey::Ed25519(ed25519::Public(*key))
+    }
 }
 
 impl pallet_root_of_trust::Trait for Runtime {
     type Event = Event;
     type Currency = balances::Module<Runtime>;
-    type CertificateId = AccountId;
+    type CertificateId = CertificateKey;
+    type CertificateSignature = CertificateSignature;
+    type CertificateSigner = CertificateKey;
+    type LegacyCertificateId = AccountId;
+    type LegacyCertificateIds = LegacyCertificateIds;
     type SlotBookingCost = SlotBookingCost;
     type SlotRenewingCost = SlotRenewingCost;
     type SlotValidity = SlotValidity;
+    type MinSlotValidity = MinSlotValidity;
+    type MaxSlotValidity = MaxSlotValidity;
+    type MaxPathLength = MaxPathLength;
+    type SlotGracePeriod = SlotGracePeriod;
+    type MaxPrunedPerBlock = MaxPrunedPerBlock;
+    type MaxExpiredPerBlock = MaxExpiredPerBlock;
+    type MaxRevocationsPerCall = MaxRevocationsPerCall;
+    type MaxMigratedPerBlock = MaxMigratedPerBlock;
+    type WeightInfo = weights::pallet_root_of_trust::WeightInfo;
 
     // In this simple example we burn the funds, in production the best would
     // be to give this to a `treasury` like module.
@@ -303,6 +354,36 @@ pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExt
 pub type Executive =
     frame_executive::Executive<Runtime, Block, system::ChainContext<Runtime>, Runtime, AllModules>;
 
+/// Run the benchmarks of our own modules, they are only compiled in with the
+/// `runtime-benchmarks` feature
+#[cfg(feature = "runtime-benchmarks")]
+fn run_benchmarks(
+    module: Vec<u8>,
+    extrinsic: Vec<u8>,
+    steps: u32,
+    repeat: u32,
+) -> Option<Vec<frame_benchmarking::BenchmarkResults>> {
+    use frame_benchmarking::Benchmarking;
+
+    match module.as_slice() {
+        b"pallet-tcr" | b"tcr" => Tcr::run_benchmark(extrinsic, steps, repeat).ok(),
+        b"pallet-root-of-trust" | b"root-of-trust" => {
+            RootOfTrust::run_benchmark(extrinsic, steps, repeat).ok()
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "runtime-benchmarks"))]
+fn run_benchmarks(
+    _module: Vec<u8>,
+    _extrinsic: Vec<u8>,
+    _steps: u32,
+    _repeat: u32,
+) -> Option<Vec<frame_benchmarking::BenchmarkResults>> {
+    None
+}
+
 impl_runtime_apis! {
     impl sp_api::Core<Block> for Runtime {
         fn version() -> RuntimeVersion {
@@ -393,13 +474,62 @@ impl_runtime_apis! {
         }
     }
 
-    impl pallet_root_of_trust_runtime_api::RootOfTrustApi<Block, AccountId> for Runtime {
-        fn is_root_certificate_valid(cert: &AccountId) -> bool {
+    impl pallet_root_of_trust_runtime_api::RootOfTrustApi<Block, CertificateKey, Certificate, BlockNumber> for Runtime {
+        fn is_root_certificate_valid(cert: &CertificateKey) -> bool {
             RootOfTrust::is_root_certificate_valid(cert)
         }
 
-        fn is_child_certificate_valid(root: &AccountId, child: &AccountId) -> bool {
+        fn is_child_certificate_valid(root: &CertificateKey, child: &CertificateKey) -> bool {
             RootOfTrust::is_child_certificate_valid(root, child)
         }
+
+        fn root_certificate_status(cert: &CertificateKey) -> CertificateStatus {
+            RootOfTrust::root_certificate_status(cert)
+        }
+
+        fn child_certificate_status(root: &CertificateKey, child: &CertificateKey) -> VerificationResult<CertificateKey> {
+            RootOfTrust::child_certificate_status(root, child)
+        }
+
+        fn verify_certificate_chain(chain: Vec<Certificate>) -> VerificationResult<CertificateKey> {
+            RootOfTrust::verify_certificate_chain(chain)
+        }
+
+        fn root_certificate_revocation(cert: &CertificateKey) -> Option<Revocation<BlockNumber>> {
+            RootOfTrust::root_certificate_revocation(cert)
+        }
+
+        fn child_certificate_revocation(root: &CertificateKey, child: &CertificateKey) -> Option<Revocation<BlockNumber>> {
+            RootOfTrust::child_certificate_revocation(root, child)
+        }
+    }
+
+    impl frame_benchmarking::Benchmark<Block> for Runtime {
+        fn dispatch_benchmark(
+            module: Vec<u8>,
+            extrinsic: Vec<u8>,
+            steps: u32,
+            repeat: u32,
+        ) -> Option<Vec<frame_benchmarking::BenchmarkResults>> {
+            run_benchmarks(module, extrinsic, steps, repeat)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shortest_slot_validity_is_not_free() {
+        // 100 * 7 days / 1 billion blocks is far below a single unit
+        assert_eq!(
+            RootOfTrust::fee_for(SlotBookingCost::get(), MinSlotValidity::get()),
+            1
+        );
+        assert_eq!(
+            RootOfTrust::fee_for(SlotRenewingCost::get(), MinSlotValidity::get()),
+            1
+        );
     }
 }
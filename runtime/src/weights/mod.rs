@@ -0,0 +1,24 @@
+//! Weights of the extrinsics of our own modules for this runtime, see the
+//! `benchmarking.rs` file of each module for the matching benchmarks. One unit of
+//! weight is one nanosecond of execution on the reference hardware.
+
+use frame_support::weights::Weight;
+
+pub mod pallet_root_of_trust;
+pub mod pallet_tcr;
+
+/// Weight of reading one storage entry from RocksDB
+const READ: Weight = 25_000;
+/// Weight of writing or removing one storage entry in RocksDB
+const WRITE: Weight = 100_000;
+
+/// Weight of `r` storage reads, `frame_support` has no `RuntimeDbWeight` yet. Reads of
+/// the block number and writes of the events are not counted, like in the benchmarks.
+fn reads(r: Weight) -> Weight {
+    READ.saturating_mul(r)
+}
+
+/// Weight of `w` storage writes
+fn writes(w: Weight) -> Weight {
+    WRITE.saturating_mul(w)
+}
@@ -0,0 +1,123 @@
+//! Weights of `pallet_root_of_trust`: the execution time of each extrinsic, in the
+//! format of the `pallet-root-of-trust` benchmarks, plus the storage entries it reads
+//! and writes.
+
+use super::{reads, writes};
+use frame_support::weights::Weight;
+
+/// Cost of scanning one more member of `Members`
+const PER_MEMBER: Weight = 200;
+
+/// Reads of `ensure_can_revoke` when the issuer is the last intermediate of a
+/// `MaxPathLength` (3) long chain: the intermediate, its revocation, suspension and
+/// revocation cutoff and the predecessor of its parent for each intermediate, then the
+/// slot, `Members`, the slot revocation and rotation of the root certificate and
+/// `RevocationsMigrated`.
+const ISSUER_CHECK_READS: Weight = 3 * 5 + 5;
+
+pub struct WeightInfo;
+
+impl pallet_root_of_trust::WeightInfo for WeightInfo {
+    fn book_slot(m: u32) -> Weight {
+        (250_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add(reads(11))
+            .saturating_add(writes(6))
+    }
+    fn renew_slot(m: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add(reads(9))
+            .saturating_add(writes(4))
+    }
+    fn revoke_slot(m: u32) -> Weight {
+        (40_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add(reads(7))
+            .saturating_add(writes(3))
+    }
+    fn revoke_child(m: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add(reads(ISSUER_CHECK_READS + 1))
+            .saturating_add(writes(1))
+    }
+    fn revoke_children(m: u32, c: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add((10_000 as Weight).saturating_mul(c as Weight))
+            .saturating_add(reads(ISSUER_CHECK_READS))
+            .saturating_add(reads(1).saturating_mul(c as Weight))
+            .saturating_add(writes(1).saturating_mul(c as Weight))
+    }
+    fn suspend_child(m: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add(reads(ISSUER_CHECK_READS + 2))
+            .saturating_add(writes(1))
+    }
+    fn reinstate_child(m: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add(reads(ISSUER_CHECK_READS + 2))
+            .saturating_add(writes(1))
+    }
+    fn revoke_children_issued_before(m: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add(reads(ISSUER_CHECK_READS + 1))
+            .saturating_add(writes(1))
+    }
+    fn transfer_slot(m: u32) -> Weight {
+        (40_000 as Weight)
+            .saturating_add((2 * PER_MEMBER).saturating_mul(m as Weight))
+            .saturating_add(reads(4))
+            .saturating_add(writes(1))
+    }
+    fn accept_slot_transfer(m: u32, s: u32) -> Weight {
+        (60_000 as Weight)
+            .saturating_add((2 * PER_MEMBER).saturating_mul(m as Weight))
+            .saturating_add((500 as Weight).saturating_mul(s as Weight))
+            .saturating_add(reads(9))
+            .saturating_add(writes(4))
+    }
+    fn rotate_root(m: u32, i: u32) -> Weight {
+        (250_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add((10_000 as Weight).saturating_mul(i as Weight))
+            .saturating_add(reads(14))
+            .saturating_add(reads(1).saturating_mul(i as Weight))
+            .saturating_add(writes(10))
+            .saturating_add(writes(1).saturating_mul(i as Weight))
+    }
+    fn add_intermediate(m: u32) -> Weight {
+        (250_000 as Weight)
+            .saturating_add(PER_MEMBER.saturating_mul(m as Weight))
+            .saturating_add(reads(ISSUER_CHECK_READS + 7))
+            .saturating_add(writes(3))
+    }
+    fn expire_slots(s: u32) -> Weight {
+        (10_000 as Weight)
+            .saturating_add((20_000 as Weight).saturating_mul(s as Weight))
+            .saturating_add(reads(2))
+            .saturating_add(reads(3).saturating_mul(s as Weight))
+            .saturating_add(writes(2))
+            .saturating_add(writes(1).saturating_mul(s as Weight))
+    }
+    fn prune_slot(i: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add((20_000 as Weight).saturating_mul(i as Weight))
+            .saturating_add(reads(5))
+            .saturating_add(reads(3).saturating_mul(i as Weight))
+            .saturating_add(writes(10))
+            .saturating_add(writes(5).saturating_mul(i as Weight))
+    }
+    fn index_slots(s: u32) -> Weight {
+        (10_000 as Weight)
+            .saturating_mul(s as Weight)
+            .saturating_add(reads(1))
+            .saturating_add(reads(2).saturating_mul(s as Weight))
+            .saturating_add(writes(1))
+            .saturating_add(writes(1).saturating_mul(s as Weight))
+    }
+}
@@ -0,0 +1,86 @@
+//! Weights of `pallet_tcr`: the execution time of each extrinsic, in the format of the
+//! `pallet-tcr` benchmarks, plus the storage entries it reads and writes. Inserting an
+//! entry in a `linked_map` reads and writes its head, removing one reads and writes its
+//! neighbours. Balances are read and written once per account.
+
+use super::{reads, writes};
+use frame_support::weights::Weight;
+
+pub struct WeightInfo;
+
+impl pallet_tcr::WeightInfo for WeightInfo {
+    fn apply(m: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add((10 as Weight).saturating_mul(m as Weight))
+            .saturating_add(reads(7))
+            .saturating_add(writes(5))
+    }
+    fn withdraw_application() -> Weight {
+        (40_000 as Weight)
+            .saturating_add(reads(5))
+            .saturating_add(writes(4))
+    }
+    fn leave_registry() -> Weight {
+        (40_000 as Weight)
+            .saturating_add(reads(8))
+            .saturating_add(writes(4))
+    }
+    fn top_up() -> Weight {
+        (40_000 as Weight)
+            .saturating_add(reads(5))
+            .saturating_add(writes(2))
+    }
+    fn reduce_stake() -> Weight {
+        (40_000 as Weight)
+            .saturating_add(reads(5))
+            .saturating_add(writes(2))
+    }
+    fn counter() -> Weight {
+        (50_000 as Weight)
+            .saturating_add(reads(7))
+            .saturating_add(writes(8))
+    }
+    fn commit_vote(v: u32) -> Weight {
+        (50_000 as Weight)
+            .saturating_add((2_000 as Weight).saturating_mul(v as Weight))
+            .saturating_add(reads(3))
+            .saturating_add(writes(2))
+    }
+    fn reveal_vote(v: u32) -> Weight {
+        (60_000 as Weight)
+            .saturating_add((3_000 as Weight).saturating_mul(v as Weight))
+            .saturating_add(reads(6))
+            .saturating_add(writes(7))
+    }
+    fn challenge() -> Weight {
+        (50_000 as Weight)
+            .saturating_add(reads(5))
+            .saturating_add(writes(5))
+    }
+    fn commit_applications(a: u32) -> Weight {
+        // The migration flags and the application and challenge deadlines are read on
+        // every block
+        (20_000 as Weight)
+            .saturating_mul(a as Weight)
+            .saturating_add(reads(4))
+            .saturating_add(reads(5).saturating_mul(a as Weight))
+            .saturating_add(writes(2))
+            .saturating_add(writes(6).saturating_mul(a as Weight))
+    }
+    fn resolve_challenge(v: u32) -> Weight {
+        (100_000 as Weight)
+            .saturating_add((20_000 as Weight).saturating_mul(v as Weight))
+            .saturating_add(reads(12))
+            .saturating_add(reads(1).saturating_mul(v as Weight))
+            .saturating_add(writes(13))
+            .saturating_add(writes(1).saturating_mul(v as Weight))
+    }
+    fn process_exits(e: u32) -> Weight {
+        (20_000 as Weight)
+            .saturating_mul(e as Weight)
+            .saturating_add(reads(1))
+            .saturating_add(reads(7).saturating_mul(e as Weight))
+            .saturating_add(writes(1))
+            .saturating_add(writes(7).saturating_mul(e as Weight))
+    }
+}
@@ -0,0 +1,131 @@
+//! Just enough DER to write and read back the certificates issued by this crate.
+
+use crate::Error;
+
+pub const BOOLEAN: u8 = 0x01;
+pub const INTEGER: u8 = 0x02;
+pub const BIT_STRING: u8 = 0x03;
+pub const OCTET_STRING: u8 = 0x04;
+pub const OBJECT_IDENTIFIER: u8 = 0x06;
+pub const UTF8_STRING: u8 = 0x0c;
+pub const UTC_TIME: u8 = 0x17;
+pub const GENERALIZED_TIME: u8 = 0x18;
+pub const SEQUENCE: u8 = 0x30;
+pub const SET: u8 = 0x31;
+
+/// Tag of an explicitly tagged, context specific, field
+pub const fn context(number: u8) -> u8 {
+    0xa0 | number
+}
+
+/// Encode a value with the given tag
+pub fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut encoded = vec![tag];
+    let length = content.len();
+    if length < 0x80 {
+        encoded.push(length as u8);
+    } else {
+        let bytes = length.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        encoded.push(0x80 | (bytes.len() - skip) as u8);
+        encoded.extend_from_slice(&bytes[skip..]);
+    }
+    encoded.extend_from_slice(content);
+    encoded
+}
+
+pub fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    tlv(SEQUENCE, &items.concat())
+}
+
+/// Positive integer from its big endian representation
+pub fn unsigned_integer(bytes: &[u8]) -> Vec<u8> {
+    let skip = bytes
+        .iter()
+        .take_while(|b| **b == 0)
+        .count()
+        .min(bytes.len() - 1);
+    let mut content = Vec::new();
+    if bytes[skip] & 0x80 != 0 {
+        content.push(0);
+    }
+    content.extend_from_slice(&bytes[skip..]);
+    tlv(INTEGER, &content)
+}
+
+/// Bit string without unused bits
+pub fn bit_string(bytes: &[u8]) -> Vec<u8> {
+    let mut content = vec![0];
+    content.extend_from_slice(bytes);
+    tlv(BIT_STRING, &content)
+}
+
+/// Reader over consecutive DER values
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Reader { data }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Tag of the next value, if any
+    pub fn peek(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    /// Read the next value, returning it whole along with its content
+    pub fn read_raw(&mut self) -> Result<(u8, &'a [u8], &'a [u8]), Error> {
+        let tag = *self.data.get(0).ok_or(Error::Malformed)?;
+        let first = *self.data.get(1).ok_or(Error::Malformed)? as usize;
+
+        let (length, header) = if first < 0x80 {
+            (first, 2)
+        } else {
+            let count = first & 0x7f;
+            if count == 0 || count > 4 {
+                return Err(Error::Malformed);
+            }
+            let bytes = self.data.get(2..2 + count).ok_or(Error::Malformed)?;
+            let length = bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize);
+            (length, 2 + count)
+        };
+
+        let whole = self.data.get(..header + length).ok_or(Error::Malformed)?;
+        self.data = &self.data[header + length..];
+
+        Ok((tag, whole, &whole[header..]))
+    }
+
+    /// Read the content of the next value, which must have the given tag
+    pub fn read(&mut self, expected: u8) -> Result<&'a [u8], Error> {
+        let (tag, _, content) = self.read_raw()?;
+        if tag != expected {
+            return Err(Error::Malformed);
+        }
+
+        Ok(content)
+    }
+
+    /// Read the next value, which must have the given tag, whole
+    pub fn read_whole(&mut self, expected: u8) -> Result<&'a [u8], Error> {
+        let (tag, whole, _) = self.read_raw()?;
+        if tag != expected {
+            return Err(Error::Malformed);
+        }
+
+        Ok(whole)
+    }
+
+    pub fn read_bit_string(&mut self) -> Result<&'a [u8], Error> {
+        match self.read(BIT_STRING)?.split_first() {
+            Some((0, bytes)) => Ok(bytes),
+            _ => Err(Error::Malformed),
+        }
+    }
+}
@@ -0,0 +1,418 @@
+//! Issuance of X.509 certificates by the key of a root certificate booked in the
+//! `root-of-trust` pallet, so that standard TLS stacks can consume certificates
+//! anchored on chain.
+//!
+//! The public key of the root slot is embedded in a non critical extension of the device
+//! certificates (`CERTIFICATE_ID_OID`), validators are expected to check the slot
+//! and the device against the chain, for instance with the
+//! `rootOfTrust_isChildCertificateValid` RPC. Only ed25519 signatures are understood
+//! by common TLS stacks, sr25519 keys are identified by an OID of our own.
+
+#[cfg(test)]
+mod tests;
+
+pub mod der;
+
+use chrono::{DateTime, Datelike, NaiveDateTime, TimeZone, Utc};
+use pki_certificate::CertificateKey;
+use sp_core::{
+    crypto::{AccountId32, Ss58Codec},
+    ed25519,
+    hashing::blake2_128,
+    sr25519, Pair,
+};
+use std::fmt;
+
+/// `2.25.206581945952620772455408541981998984007.1`, extension holding the id of the
+/// root slot a certificate chains to. Our OIDs live under an arc derived from a UUID
+/// as permitted by ITU-T X.667.
+pub const CERTIFICATE_ID_OID: &[u8] = &[
+    0x69, 0x82, 0xb6, 0xea, 0x9e, 0x93, 0xc5, 0xf1, 0xea, 0xba, 0x95, 0xb7, 0xe2, 0xcf, 0xa1, 0xf6,
+    0xd0, 0xf8, 0xd6, 0x47, 0x01,
+];
+/// `2.25.206581945952620772455408541981998984007.2`, sr25519 keys and signatures
+pub const SR25519_OID: &[u8] = &[
+    0x69, 0x82, 0xb6, 0xea, 0x9e, 0x93, 0xc5, 0xf1, 0xea, 0xba, 0x95, 0xb7, 0xe2, 0xcf, 0xa1, 0xf6,
+    0xd0, 0xf8, 0xd6, 0x47, 0x02,
+];
+/// `1.3.101.112`, ed25519 keys and signatures (RFC 8410)
+pub const ED25519_OID: &[u8] = &[0x2b, 0x65, 0x70];
+/// `2.5.4.3`, common name attribute
+const COMMON_NAME_OID: &[u8] = &[0x55, 0x04, 0x03];
+/// `2.5.29.19`, basic constraints extension
+const BASIC_CONSTRAINTS_OID: &[u8] = &[0x55, 0x1d, 0x13];
+
+const PEM_LABEL: &str = "CERTIFICATE";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// The certificate could not be decoded
+    Malformed,
+    /// A key or signature algorithm other than ed25519 and sr25519 is used
+    UnsupportedAlgorithm,
+    /// The certificate does not carry the id of its root slot
+    MissingCertificateId,
+    /// The issuer name does not match the root slot id
+    IssuerMismatch,
+    /// The signature was not made by the root slot key
+    BadSignature,
+    NotYetValid,
+    Expired,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Key types of the accounts used as certificate ids
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum KeyType {
+    Ed25519,
+    Sr25519,
+}
+
+impl KeyType {
+    fn oid(&self) -> &'static [u8] {
+        match self {
+            KeyType::Ed25519 => ED25519_OID,
+            KeyType::Sr25519 => SR25519_OID,
+        }
+    }
+
+    fn from_oid(oid: &[u8]) -> Result<Self, Error> {
+        match oid {
+            ED25519_OID => Ok(KeyType::Ed25519),
+            SR25519_OID => Ok(KeyType::Sr25519),
+            _ => Err(Error::UnsupportedAlgorithm),
+        }
+    }
+
+    fn algorithm_identifier(&self) -> Vec<u8> {
+        der::sequence(&[der::tlv(der::OBJECT_IDENTIFIER, self.oid())])
+    }
+
+    /// On chain representation of a public key of this type
+    pub fn certificate_key(&self, public: &AccountId32) -> CertificateKey {
+        let raw: &[u8; 32] = public.as_ref();
+        match self {
+            KeyType::Ed25519 => CertificateKey::Ed25519(ed25519::Public(*raw)),
+            KeyType::Sr25519 => CertificateKey::Sr25519(sr25519::Public(*raw)),
+        }
+    }
+}
+
+/// Key of a root slot, the slot id being its public key
+pub enum Issuer {
+    Ed25519(ed25519::Pair),
+    Sr25519(sr25519::Pair),
+}
+
+impl Issuer {
+    pub fn key_type(&self) -> KeyType {
+        match self {
+            Issuer::Ed25519(_) => KeyType::Ed25519,
+            Issuer::Sr25519(_) => KeyType::Sr25519,
+        }
+    }
+
+    pub fn certificate_id(&self) -> AccountId32 {
+        match self {
+            Issuer::Ed25519(pair) => pair.public().0.into(),
+            Issuer::Sr25519(pair) => pair.public().into(),
+        }
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            Issuer::Ed25519(pair) => pair.sign(message).0.to_vec(),
+            Issuer::Sr25519(pair) => pair.sign(message).0.to_vec(),
+        }
+    }
+}
+
+/// Device certificate to be issued
+pub struct CertificateParams {
+    pub device: AccountId32,
+    pub device_key_type: KeyType,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+}
+
+/// A certificate issued by a root slot
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct X509Certificate {
+    pub serial: Vec<u8>,
+    /// Root slot which issued the certificate, as found in its extension
+    pub root: AccountId32,
+    pub root_key_type: KeyType,
+    pub device: AccountId32,
+    pub device_key_type: KeyType,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    issuer_name: String,
+    tbs_certificate: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+fn name(common_name: &str) -> Vec<u8> {
+    let attribute = der::sequence(&[
+        der::tlv(der::OBJECT_IDENTIFIER, COMMON_NAME_OID),
+        der::tlv(der::UTF8_STRING, common_name.as_bytes()),
+    ]);
+
+    der::sequence(&[der::tlv(der::SET, &attribute)])
+}
+
+fn read_common_name(content: &[u8]) -> Result<String, Error> {
+    let mut rdns = der::Reader::new(content);
+    while !rdns.is_empty() {
+        let mut attributes = der::Reader::new(rdns.read(der::SET)?);
+        while !attributes.is_empty() {
+            let mut attribute = der::Reader::new(attributes.read(der::SEQUENCE)?);
+            if attribute.read(der::OBJECT_IDENTIFIER)? == COMMON_NAME_OID {
+                let value = attribute.read(der::UTF8_STRING)?;
+                return String::from_utf8(value.to_vec()).map_err(|_| Error::Malformed);
+            }
+        }
+    }
+
+    Err(Error::Malformed)
+}
+
+/// RFC 5280 requires dates before 2050 to be encoded as `UTCTime`
+fn time(time: &DateTime<Utc>) -> Vec<u8> {
+    if time.year() < 2050 {
+        der::tlv(
+            der::UTC_TIME,
+            time.format("%y%m%d%H%M%SZ").to_string().as_bytes(),
+        )
+    } else {
+        der::tlv(
+            der::GENERALIZED_TIME,
+            time.format("%Y%m%d%H%M%SZ").to_string().as_bytes(),
+        )
+    }
+}
+
+fn read_time(reader: &mut der::Reader) -> Result<DateTime<Utc>, Error> {
+    let (tag, _, content) = reader.read_raw()?;
+    let text = std::str::from_utf8(content).map_err(|_| Error::Malformed)?;
+    let full = match tag {
+        der::UTC_TIME => {
+            let year: u32 = text
+                .get(..2)
+                .and_then(|y| y.parse().ok())
+                .ok_or(Error::Malformed)?;
+            format!("{}{}", if year >= 50 { "19" } else { "20" }, text)
+        }
+        der::GENERALIZED_TIME => text.to_string(),
+        _ => return Err(Error::Malformed),
+    };
+
+    NaiveDateTime::parse_from_str(&full, "%Y%m%d%H%M%SZ")
+        .map(|time| Utc.from_utc_datetime(&time))
+        .map_err(|_| Error::Malformed)
+}
+
+fn account(bytes: &[u8]) -> Result<AccountId32, Error> {
+    if bytes.len() != 32 {
+        return Err(Error::Malformed);
+    }
+
+    let mut account = [0u8; 32];
+    account.copy_from_slice(bytes);
+    Ok(account.into())
+}
+
+/// Issue a DER encoded certificate for a device
+pub fn issue(issuer: &Issuer, params: &CertificateParams) -> Vec<u8> {
+    let root = issuer.certificate_id();
+    let serial = blake2_128(
+        &[
+            AsRef::<[u8]>::as_ref(&root),
+            AsRef::<[u8]>::as_ref(&params.device),
+            &params.not_before.timestamp().to_be_bytes(),
+        ]
+        .concat(),
+    );
+
+    let basic_constraints = der::sequence(&[
+        der::tlv(der::OBJECT_IDENTIFIER, BASIC_CONSTRAINTS_OID),
+        der::tlv(der::BOOLEAN, &[0xff]),
+        // Not a certificate authority
+        der::tlv(der::OCTET_STRING, &der::sequence(&[])),
+    ]);
+    let certificate_id = der::sequence(&[
+        der::tlv(der::OBJECT_IDENTIFIER, CERTIFICATE_ID_OID),
+        der::tlv(
+            der::OCTET_STRING,
+            &der::tlv(der::OCTET_STRING, AsRef::<[u8]>::as_ref(&root)),
+        ),
+    ]);
+
+    let tbs_certificate = der::sequence(&[
+        // Version 3
+        der::tlv(der::context(0), &der::tlv(der::INTEGER, &[2])),
+        der::unsigned_integer(&serial),
+        issuer.key_type().algorithm_identifier(),
+        name(&root.to_ss58check()),
+        der::sequence(&[time(&params.not_before), time(&params.not_after)]),
+        name(&params.device.to_ss58check()),
+        der::sequence(&[
+            params.device_key_type.algorithm_identifier(),
+            der::bit_string(AsRef::<[u8]>::as_ref(&params.device)),
+        ]),
+        der::tlv(
+            der::context(3),
+            &der::sequence(&[basic_constraints, certificate_id]),
+        ),
+    ]);
+    let signature = issuer.sign(&tbs_certificate);
+
+    der::sequence(&[
+        tbs_certificate,
+        issuer.key_type().algorithm_identifier(),
+        der::bit_string(&signature),
+    ])
+}
+
+/// PEM encoding of a DER certificate
+pub fn to_pem(der: &[u8]) -> String {
+    let encoded = base64::encode(der);
+    let mut pem = format!("-----BEGIN {}-----\n", PEM_LABEL);
+    for line in encoded.as_bytes().chunks(64) {
+        pem.push_str(std::str::from_utf8(line).expect("base64 is ascii"));
+        pem.push('\n');
+    }
+    pem.push_str(&format!("-----END {}-----\n", PEM_LABEL));
+    pem
+}
+
+/// DER encoding of the first certificate of a PEM document
+pub fn from_pem(pem: &str) -> Result<Vec<u8>, Error> {
+    let begin = format!("-----BEGIN {}-----", PEM_LABEL);
+    let end = format!("-----END {}-----", PEM_LABEL);
+
+    let start = pem.find(&begin).ok_or(Error::Malformed)? + begin.len();
+    let stop = pem[start..].find(&end).ok_or(Error::Malformed)? + start;
+    let encoded: String = pem[start..stop]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    base64::decode(&encoded).map_err(|_| Error::Malformed)
+}
+
+impl X509Certificate {
+    pub fn from_der(data: &[u8]) -> Result<Self, Error> {
+        let mut certificate = der::Reader::new(der::Reader::new(data).read(der::SEQUENCE)?);
+        let tbs_certificate = certificate.read_whole(der::SEQUENCE)?;
+        let mut algorithm = der::Reader::new(certificate.read(der::SEQUENCE)?);
+        let root_key_type = KeyType::from_oid(algorithm.read(der::OBJECT_IDENTIFIER)?)?;
+        let signature = certificate.read_bit_string()?;
+
+        let mut tbs = der::Reader::new(der::Reader::new(tbs_certificate).read(der::SEQUENCE)?);
+        if tbs.peek() == Some(der::context(0)) {
+            tbs.read(der::context(0))?;
+        }
+        let serial = tbs.read(der::INTEGER)?;
+        let mut inner_algorithm = der::Reader::new(tbs.read(der::SEQUENCE)?);
+        if KeyType::from_oid(inner_algorithm.read(der::OBJECT_IDENTIFIER)?)? != root_key_type {
+            return Err(Error::Malformed);
+        }
+        let issuer_name = read_common_name(tbs.read(der::SEQUENCE)?)?;
+        let mut validity = der::Reader::new(tbs.read(der::SEQUENCE)?);
+        let not_before = read_time(&mut validity)?;
+        let not_after = read_time(&mut validity)?;
+        tbs.read(der::SEQUENCE)?; // Subject, the device is read from its key
+
+        let mut public_key_info = der::Reader::new(tbs.read(der::SEQUENCE)?);
+        let mut key_algorithm = der::Reader::new(public_key_info.read(der::SEQUENCE)?);
+        let device_key_type = KeyType::from_oid(key_algorithm.read(der::OBJECT_IDENTIFIER)?)?;
+        let device = account(public_key_info.read_bit_string()?)?;
+
+        let mut root = None;
+        while !tbs.is_empty() {
+            let (tag, _, content) = tbs.read_raw()?;
+            if tag != der::context(3) {
+                continue;
+            }
+
+            let mut extensions = der::Reader::new(der::Reader::new(content).read(der::SEQUENCE)?);
+            while !extensions.is_empty() {
+                let mut extension = der::Reader::new(extensions.read(der::SEQUENCE)?);
+                if extension.read(der::OBJECT_IDENTIFIER)? != CERTIFICATE_ID_OID {
+                    continue;
+                }
+                if extension.peek() == Some(der::BOOLEAN) {
+                    extension.read(der::BOOLEAN)?;
+                }
+                let value = extension.read(der::OCTET_STRING)?;
+                root = Some(account(der::Reader::new(value).read(der::OCTET_STRING)?)?);
+            }
+        }
+
+        Ok(X509Certificate {
+            serial: serial.to_vec(),
+            root: root.ok_or(Error::MissingCertificateId)?,
+            root_key_type,
+            device,
+            device_key_type,
+            not_before,
+            not_after,
+            issuer_name,
+            tbs_certificate: tbs_certificate.to_vec(),
+            signature: signature.to_vec(),
+        })
+    }
+
+    pub fn from_pem(pem: &str) -> Result<Self, Error> {
+        Self::from_der(&from_pem(pem)?)
+    }
+
+    /// Id of the root slot on chain
+    pub fn root_key(&self) -> CertificateKey {
+        self.root_key_type.certificate_key(&self.root)
+    }
+
+    /// Id of the device on chain
+    pub fn device_key(&self) -> CertificateKey {
+        self.device_key_type.certificate_key(&self.device)
+    }
+
+    /// Whether the certificate was signed by the key of its root slot
+    pub fn verify_signature(&self) -> bool {
+        let root: &[u8] = self.root.as_ref();
+        match self.root_key_type {
+            KeyType::Ed25519 => {
+                ed25519::Pair::verify_weak(&self.signature, &self.tbs_certificate, root)
+            }
+            KeyType::Sr25519 => {
+                sr25519::Pair::verify_weak(&self.signature, &self.tbs_certificate, root)
+            }
+        }
+    }
+
+    /// Offline checks of the certificate, its root slot and device still have to be
+    /// checked against the chain
+    pub fn validate(&self, now: DateTime<Utc>) -> Result<(), Error> {
+        if self.issuer_name != self.root.to_ss58check() {
+            return Err(Error::IssuerMismatch);
+        }
+        if !self.verify_signature() {
+            return Err(Error::BadSignature);
+        }
+        if now < self.not_before {
+            return Err(Error::NotYetValid);
+        }
+        if now >= self.not_after {
+            return Err(Error::Expired);
+        }
+
+        Ok(())
+    }
+}
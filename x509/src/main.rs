@@ -0,0 +1,182 @@
+//! Issue X.509 certificates with the key of a root slot and validate them against
+//! the chain.
+
+use chrono::{Duration, Utc};
+use hyper::{Body, Client, Request, Uri};
+use pki_certificate::CertificateKey;
+use pki_x509::{issue, to_pem, CertificateParams, Issuer, KeyType, X509Certificate};
+use serde_json::{json, Value};
+use sp_core::{
+    crypto::{AccountId32, Ss58Codec},
+    ed25519, sr25519, Pair,
+};
+use std::{fs, path::PathBuf, str::FromStr};
+use structopt::StructOpt;
+
+type Error = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, Copy)]
+struct KeyTypeArg(KeyType);
+
+impl FromStr for KeyTypeArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ed25519" => Ok(KeyTypeArg(KeyType::Ed25519)),
+            "sr25519" => Ok(KeyTypeArg(KeyType::Sr25519)),
+            _ => Err(format!("unsupported key type {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "pki-x509")]
+enum Opt {
+    /// Issue a certificate for a device
+    Issue {
+        /// Secret URI of the root slot key
+        #[structopt(long)]
+        root_key: String,
+
+        /// Type of the root slot key, `ed25519` or `sr25519`
+        #[structopt(long, default_value = "ed25519")]
+        root_key_type: KeyTypeArg,
+
+        /// SS58 address of the device
+        #[structopt(long)]
+        device: String,
+
+        /// Type of the device key, `ed25519` or `sr25519`
+        #[structopt(long, default_value = "sr25519")]
+        device_key_type: KeyTypeArg,
+
+        /// Number of days the certificate is valid for
+        #[structopt(long, default_value = "365")]
+        days: i64,
+
+        /// Write a DER certificate instead of a PEM one
+        #[structopt(long)]
+        der: bool,
+
+        /// Where to write the certificate, defaults to the standard output
+        #[structopt(long, parse(from_os_str))]
+        out: Option<PathBuf>,
+    },
+    /// Validate a certificate and check its root slot and device on chain
+    Verify {
+        /// PEM or DER certificate
+        #[structopt(parse(from_os_str))]
+        certificate: PathBuf,
+
+        /// HTTP JSON-RPC endpoint of a node
+        #[structopt(long, default_value = "http://localhost:9933")]
+        rpc_url: Uri,
+    },
+}
+
+fn issuer(key: &str, key_type: KeyType) -> Result<Issuer, Error> {
+    Ok(match key_type {
+        KeyType::Ed25519 => {
+            Issuer::Ed25519(ed25519::Pair::from_string(key, None).map_err(|_| "invalid root key")?)
+        }
+        KeyType::Sr25519 => {
+            Issuer::Sr25519(sr25519::Pair::from_string(key, None).map_err(|_| "invalid root key")?)
+        }
+    })
+}
+
+async fn is_child_certificate_valid(
+    rpc_url: Uri,
+    root: &CertificateKey,
+    child: &CertificateKey,
+) -> Result<bool, Error> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "rootOfTrust_isChildCertificateValid",
+        "params": [root, child],
+    });
+    let request = Request::post(rpc_url)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))?;
+
+    let response = Client::new().request(request).await?;
+    let bytes = hyper::body::to_bytes(response.into_body()).await?;
+    let reply: Value = serde_json::from_slice(&bytes)?;
+
+    reply["result"]
+        .as_bool()
+        .ok_or_else(|| format!("unexpected reply {}", reply).into())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    match Opt::from_args() {
+        Opt::Issue {
+            root_key,
+            root_key_type,
+            device,
+            device_key_type,
+            days,
+            der,
+            out,
+        } => {
+            let issuer = issuer(&root_key, root_key_type.0)?;
+            let now = Utc::now();
+            let params = CertificateParams {
+                device: AccountId32::from_ss58check(&device).map_err(|_| "invalid device")?,
+                device_key_type: device_key_type.0,
+                not_before: now,
+                not_after: now + Duration::days(days),
+            };
+
+            let certificate = issue(&issuer, &params);
+            let encoded = if der {
+                certificate
+            } else {
+                to_pem(&certificate).into_bytes()
+            };
+            match out {
+                Some(path) => fs::write(path, encoded)?,
+                None => std::io::Write::write_all(&mut std::io::stdout(), &encoded)?,
+            }
+        }
+        Opt::Verify {
+            certificate,
+            rpc_url,
+        } => {
+            let data = fs::read(certificate)?;
+            let certificate = match std::str::from_utf8(&data) {
+                Ok(pem) if pem.contains("-----BEGIN") => X509Certificate::from_pem(pem)?,
+                _ => X509Certificate::from_der(&data)?,
+            };
+            certificate.validate(Utc::now())?;
+
+            // The extension is only trusted once the chain confirms the slot still
+            // vouches for the device
+            if !is_child_certificate_valid(
+                rpc_url,
+                &certificate.root_key(),
+                &certificate.device_key(),
+            )
+            .await?
+            {
+                return Err(format!(
+                    "{} is not a valid child of the slot {}",
+                    certificate.device.to_ss58check(),
+                    certificate.root.to_ss58check()
+                )
+                .into());
+            }
+
+            println!(
+                "valid certificate for {} issued by the slot {}",
+                certificate.device.to_ss58check(),
+                certificate.root.to_ss58check()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use super::*;
+use chrono::Duration;
+
+fn issuer(key_type: KeyType) -> Issuer {
+    match key_type {
+        KeyType::Ed25519 => Issuer::Ed25519(ed25519::Pair::from_string("//Root", None).unwrap()),
+        KeyType::Sr25519 => Issuer::Sr25519(sr25519::Pair::from_string("//Root", None).unwrap()),
+    }
+}
+
+fn params() -> CertificateParams {
+    CertificateParams {
+        device: sr25519::Pair::from_string("//Device", None)
+            .unwrap()
+            .public()
+            .into(),
+        device_key_type: KeyType::Sr25519,
+        not_before: Utc.ymd(2020, 4, 1).and_hms(0, 0, 0),
+        not_after: Utc.ymd(2021, 4, 1).and_hms(0, 0, 0),
+    }
+}
+
+fn now() -> DateTime<Utc> {
+    Utc.ymd(2020, 6, 1).and_hms(12, 0, 0)
+}
+
+#[test]
+fn issued_certificates_can_be_read_back() {
+    for key_type in &[KeyType::Ed25519, KeyType::Sr25519] {
+        let issuer = issuer(*key_type);
+        let params = params();
+        let certificate = X509Certificate::from_der(&issue(&issuer, &params)).unwrap();
+
+        assert_eq!(certificate.root, issuer.certificate_id());
+        assert_eq!(certificate.root_key_type, *key_type);
+        assert_eq!(certificate.device, params.device);
+        assert_eq!(certificate.device_key_type, params.device_key_type);
+        assert_eq!(certificate.not_before, params.not_before);
+        assert_eq!(certificate.not_after, params.not_after);
+        assert_eq!(certificate.validate(now()), Ok(()));
+    }
+}
+
+#[test]
+fn keys_map_to_their_on_chain_representation() {
+    let issuer = issuer(KeyType::Ed25519);
+    let certificate = X509Certificate::from_der(&issue(&issuer, &params())).unwrap();
+
+    match (issuer, certificate.root_key()) {
+        (Issuer::Ed25519(pair), CertificateKey::Ed25519(key)) => assert_eq!(pair.public(), key),
+        _ => panic!("root key type does not match the issuer"),
+    }
+    match certificate.device_key() {
+        CertificateKey::Sr25519(key) => {
+            assert_eq!(key.0, *AsRef::<[u8; 32]>::as_ref(&certificate.device))
+        }
+        _ => panic!("device key type does not match the certificate"),
+    }
+}
+
+#[test]
+fn pem_round_trip() {
+    let der = issue(&issuer(KeyType::Ed25519), &params());
+    let pem = to_pem(&der);
+
+    assert_eq!(pem.starts_with("-----BEGIN CERTIFICATE-----\n"), true);
+    assert_eq!(pem.lines().all(|line| line.len() <= 64), true);
+    assert_eq!(from_pem(&pem), Ok(der));
+}
+
+#[test]
+fn dates_after_2049_use_generalized_time() {
+    let mut params = params();
+    params.not_after = Utc.ymd(2050, 1, 1).and_hms(0, 0, 0);
+    let certificate =
+        X509Certificate::from_der(&issue(&issuer(KeyType::Ed25519), &params)).unwrap();
+
+    assert_eq!(certificate.not_after, params.not_after);
+}
+
+#[test]
+fn validity_period_is_enforced() {
+    let params = params();
+    let certificate =
+        X509Certificate::from_der(&issue(&issuer(KeyType::Ed25519), &params)).unwrap();
+
+    assert_eq!(
+        certificate.validate(params.not_before - Duration::seconds(1)),
+        Err(Error::NotYetValid)
+    );
+    assert_eq!(certificate.validate(params.not_after), Err(Error::Expired));
+}
+
+#[test]
+fn tampered_certificates_are_rejected() {
+    let mut certificate =
+        X509Certificate::from_der(&issue(&issuer(KeyType::Sr25519), &params())).unwrap();
+    certificate.root = ed25519::Pair::from_string("//Other", None)
+        .unwrap()
+        .public()
+        .0
+        .into();
+
+    assert_eq!(certificate.validate(now()), Err(Error::IssuerMismatch));
+
+    certificate.issuer_name = certificate.root.to_ss58check();
+    assert_eq!(certificate.validate(now()), Err(Error::BadSignature));
+}
+
+#[test]
+fn garbage_is_rejected() {
+    assert_eq!(
+        X509Certificate::from_der(&[0x30, 0x82, 0xff]),
+        Err(Error::Malformed)
+    );
+    assert_eq!(from_pem("not a certificate"), Err(Error::Malformed));
+}